@@ -1,17 +1,84 @@
-use crate::Expr;
+use crate::{system, Expr, ExprKind, RuleRef, Symbol};
 use indexmap::map::IndexMap;
 use std::collections::{BTreeMap, HashMap};
-use std::fmt::{Debug, Display, Formatter};
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
 use std::ops::{Deref, DerefMut};
 
 /// A map from variable names to expressions.
+///
+/// Two associations are equal if they contain the same rules in the same order, as in
+/// [`SameQ`](https://reference.wolfram.com/language/ref/SameQ.html) <sub>WL</sub>.
 #[derive(Debug, Clone)]
 pub struct Association {
     /// key -> (is_delayed, value)
     records: IndexMap<Expr, (bool, Expr)>,
 }
 
+/// The kind of rule associating a key with its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuleKind {
+    /// [`Rule`](https://reference.wolfram.com/language/ref/Rule.html) <sub>WL</sub>:
+    /// `key -> value`.
+    Rule,
+    /// [`RuleDelayed`](https://reference.wolfram.com/language/ref/RuleDelayed.html)
+    /// <sub>WL</sub>: `key :> value`.
+    RuleDelayed,
+}
+
+impl RuleKind {
+    /// Returns [`RuleKind::RuleDelayed`] if `is_delayed` is `true`, and
+    /// [`RuleKind::Rule`] otherwise.
+    pub fn from_delayed(is_delayed: bool) -> Self {
+        match is_delayed {
+            true => RuleKind::RuleDelayed,
+            false => RuleKind::Rule,
+        }
+    }
+
+    /// Returns `true` for [`RuleKind::RuleDelayed`].
+    pub fn is_delayed(self) -> bool {
+        self == RuleKind::RuleDelayed
+    }
+
+    /// The head of a rule of this kind: `Rule` or `RuleDelayed`.
+    pub fn symbol(self) -> Symbol {
+        match self {
+            RuleKind::Rule => Symbol::from(system::Rule),
+            RuleKind::RuleDelayed => Symbol::from(system::RuleDelayed),
+        }
+    }
+
+    /// Construct the rule `key -> value` or `key :> value`.
+    pub fn to_expr(self, key: Expr, value: Expr) -> Expr {
+        match self {
+            RuleKind::Rule => Expr::rule(key, value),
+            RuleKind::RuleDelayed => Expr::rule_delayed(key, value),
+        }
+    }
+
+    /// The infix operator of this kind of rule: `->` or `:>`.
+    fn operator(self) -> &'static str {
+        match self {
+            RuleKind::Rule => "->",
+            RuleKind::RuleDelayed => ":>",
+        }
+    }
+}
+
+/// Error returned when an [`Expr`] cannot be converted to an [`Association`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssociationError {
+    /// `expr` is not an `Association[...]` expression.
+    #[allow(missing_docs)]
+    NotAssociation { expr: Expr },
+    /// `elem` is not a rule, a list of rules, or an association.
+    #[allow(missing_docs)]
+    InvalidElement { elem: Expr },
+}
+
 impl Deref for Association {
     type Target = IndexMap<Expr, (bool, Expr)>;
 
@@ -53,10 +120,238 @@ impl Association {
         let value = value.into();
         self.records.insert(key, (true, value));
     }
+    /// Inserts a key-value pair using a rule of the given kind.
+    pub fn insert_rule<K, V>(&mut self, key: K, kind: RuleKind, value: V)
+    where
+        K: Into<Expr>,
+        V: Into<Expr>,
+    {
+        self.records
+            .insert(key.into(), (kind.is_delayed(), value.into()));
+    }
     /// Convert to [`Expr`]
     pub fn as_expr(&self) -> Expr {
         Expr::from(self.clone())
     }
+
+    //==================================
+    // Lookups
+    //==================================
+
+    /// Iterate over the rules of this association in order, as `(key, kind, value)`
+    /// tuples.
+    pub fn rules(&self) -> impl Iterator<Item = (&Expr, RuleKind, &Expr)> {
+        self.records
+            .iter()
+            .map(|(key, (delayed, value))| (key, RuleKind::from_delayed(*delayed), value))
+    }
+
+    /// Returns the kind of rule and value associated with `key`.
+    pub fn get_rule(&self, key: &Expr) -> Option<(RuleKind, &Expr)> {
+        let (delayed, value) = self.records.get(key)?;
+        Some((RuleKind::from_delayed(*delayed), value))
+    }
+
+    /// Returns the value associated with the string key `key`.
+    ///
+    /// ```
+    /// use wolfram_expr::{Association, Expr};
+    ///
+    /// let mut assoc = Association::new();
+    /// assoc.insert("a", Expr::from(1));
+    ///
+    /// assert_eq!(assoc.get_str("a"), Some(&Expr::from(1)));
+    /// assert_eq!(assoc.get_str("b"), None);
+    /// ```
+    pub fn get_str(&self, key: &str) -> Option<&Expr> {
+        self.records.get(&Expr::string(key)).map(|(_, value)| value)
+    }
+
+    /// Returns the value at a path of string keys through nested associations, as in
+    /// `assoc["a", "b", ...]`.
+    ///
+    /// The value associated with each key except the last must be an
+    /// `Association[...]` expression. Returns `None` if `path` is empty or any key is
+    /// absent.
+    ///
+    /// ```
+    /// use wolfram_expr::{Association, Expr};
+    ///
+    /// // <|"a" -> <|"b" -> 1|>|>
+    /// let mut inner = Association::new();
+    /// inner.insert("b", Expr::from(1));
+    /// let mut assoc = Association::new();
+    /// assoc.insert("a", inner);
+    ///
+    /// assert_eq!(assoc.lookup_path(&["a", "b"]), Some(&Expr::from(1)));
+    /// assert_eq!(assoc.lookup_path(&["a", "c"]), None);
+    /// ```
+    pub fn lookup_path(&self, path: &[&str]) -> Option<&Expr> {
+        let (first, rest) = path.split_first()?;
+
+        rest.iter().try_fold(self.get_str(first)?, |value, key| {
+            lookup(value, &Expr::string(*key))
+        })
+    }
+
+    /// Returns the value associated with `key`, or ``Missing["KeyAbsent", key]`` if
+    /// there is none, as in
+    /// [`Lookup`](https://reference.wolfram.com/language/ref/Lookup.html)
+    /// <sub>WL</sub>.
+    pub fn get_or_missing(&self, key: &Expr) -> Expr {
+        match self.records.get(key) {
+            Some((_, value)) => value.clone(),
+            None => Expr::function(system::Missing, vec![
+                Expr::string("KeyAbsent"),
+                key.clone(),
+            ]),
+        }
+    }
+
+    //==================================
+    // Transformations
+    //==================================
+
+    /// Returns a copy of this association with its keys sorted into canonical order, as
+    /// in [`KeySort`](https://reference.wolfram.com/language/ref/KeySort.html)
+    /// <sub>WL</sub>.
+    pub fn key_sort(&self) -> Association {
+        let mut records = self.records.clone();
+        records.sort_keys();
+        Association { records }
+    }
+
+    /// Returns a copy of this association without the given keys, as in
+    /// [`KeyDrop`](https://reference.wolfram.com/language/ref/KeyDrop.html)
+    /// <sub>WL</sub>.
+    pub fn key_drop<I, K>(&self, keys: I) -> Association
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<Expr>,
+    {
+        let mut records = self.records.clone();
+        for key in keys {
+            records.shift_remove(&key.into());
+        }
+        Association { records }
+    }
+
+    /// Returns an association containing only the given keys, in the order they are
+    /// given, as in [`KeyTake`](https://reference.wolfram.com/language/ref/KeyTake.html)
+    /// <sub>WL</sub>.
+    ///
+    /// Keys which are not present in this association are ignored.
+    pub fn key_take<I, K>(&self, keys: I) -> Association
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<Expr>,
+    {
+        let mut records = IndexMap::new();
+        for key in keys {
+            let key = key.into();
+            if let Some(record) = self.records.get(&key) {
+                records.insert(key, record.clone());
+            }
+        }
+        Association { records }
+    }
+
+    /// Merge several associations, as in
+    /// [`Merge`](https://reference.wolfram.com/language/ref/Merge.html) <sub>WL</sub>.
+    ///
+    /// Keys appear in the order they are first encountered. The value of each key is
+    /// the result of calling `combiner` with the values associated with that key, in
+    /// the order of `assocs`.
+    ///
+    /// ```
+    /// use wolfram_expr::{Association, Expr};
+    ///
+    /// let mut a = Association::new();
+    /// a.insert("x", Expr::from(1));
+    /// let mut b = Association::new();
+    /// b.insert("x", Expr::from(2));
+    /// b.insert("y", Expr::from(3));
+    ///
+    /// // Merge[{<|"x" -> 1|>, <|"x" -> 2, "y" -> 3|>}, Length]
+    /// let length = |values: &[Expr]| Expr::from(values.len() as i64);
+    /// let merged = Association::merge(&[a, b], length);
+    ///
+    /// assert_eq!(merged.to_string(), r#"<|"x" -> 2, "y" -> 1|>"#);
+    /// ```
+    pub fn merge<'a, I, F>(assocs: I, mut combiner: F) -> Association
+    where
+        I: IntoIterator<Item = &'a Association>,
+        F: FnMut(&[Expr]) -> Expr,
+    {
+        let mut values: IndexMap<Expr, Vec<Expr>> = IndexMap::new();
+        for assoc in assocs {
+            for (key, (_, value)) in &assoc.records {
+                values.entry(key.clone()).or_default().push(value.clone());
+            }
+        }
+
+        let records = values
+            .into_iter()
+            .map(|(key, values)| (key, (false, combiner(&values))))
+            .collect();
+
+        Association { records }
+    }
+}
+
+impl Association {
+    /// Construct a new association by applying `f` to each key and value, keeping the
+    /// kind of each rule.
+    pub(crate) fn map_rules<F: FnMut(&Expr) -> Expr>(&self, mut f: F) -> Association {
+        let mut assoc = Association::new();
+        for (key, kind, value) in self.rules() {
+            let key = f(key);
+            assoc.insert_rule(key, kind, f(value));
+        }
+        assoc
+    }
+}
+
+/// Look up the value of `key` in `assoc`, if it is an association or an
+/// `Association[rules...]` expression.
+///
+/// If `key` appears more than once in an `Association[rules...]` expression, the value
+/// of the last occurrence is returned.
+pub(crate) fn lookup<'e>(assoc: &'e Expr, key: &Expr) -> Option<&'e Expr> {
+    if let Some(assoc) = assoc.try_as_association() {
+        return assoc.get(key).map(|(_, value)| value);
+    }
+
+    let normal = assoc.try_as_normal()?;
+    if !normal.has_head(system::Association.symbol()) {
+        return None;
+    }
+
+    normal.elements().iter().rev().find_map(|rule| {
+        let rule = rule.try_as_normal()?;
+        match (rule.head.try_as_symbol()?.as_str(), rule.elements()) {
+            ("System`Rule" | "System`RuleDelayed", [lhs, rhs]) if lhs == key => Some(rhs),
+            _ => None,
+        }
+    })
+}
+
+impl PartialEq for Association {
+    fn eq(&self, other: &Association) -> bool {
+        self.records.len() == other.records.len()
+            && self.records.iter().eq(other.records.iter())
+    }
+}
+
+impl Eq for Association {}
+
+impl Hash for Association {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.records.len().hash(state);
+        for record in &self.records {
+            record.hash(state);
+        }
+    }
 }
 
 macro_rules! map_like {
@@ -84,15 +379,89 @@ map_like![HashMap, BTreeMap, IndexMap];
 
 impl From<Association> for Expr {
     fn from(map: Association) -> Self {
-        let mut elements = vec![];
-        for (key, (rule, value)) in map.records {
-            let item = match rule {
-                true => Expr::rule(key, value),
-                false => Expr::rule_delayed(key, value),
-            };
-            elements.push(item)
+        Expr::new(ExprKind::Association(map))
+    }
+}
+
+/// Parse an `Association[...]` expression.
+///
+/// An [`ExprKind::Association`] is returned as is. A [`Normal`][crate::Normal]
+/// expression with head `Association` is parsed from its elements.
+///
+/// As in the Wolfram Language, the elements may be `Rule` and `RuleDelayed`
+/// expressions, (nested) lists of rules, or associations. If a key appears more than
+/// once, the association keeps the position of its first occurrence and the value of
+/// its last.
+///
+/// # Example
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use wolfram_expr::{Association, Expr};
+///
+/// // Association["a" -> 1, {"b" :> 2}]
+/// let expr = Expr::function("System`Association", vec![
+///     Expr::rule("a", Expr::from(1)),
+///     Expr::list(vec![Expr::rule_delayed("b", Expr::from(2))]),
+/// ]);
+///
+/// let assoc = Association::try_from(&expr).unwrap();
+///
+/// assert_eq!(assoc.to_string(), r#"<|"a" -> 1, "b" :> 2|>"#);
+/// ```
+impl TryFrom<&Expr> for Association {
+    type Error = AssociationError;
+
+    fn try_from(expr: &Expr) -> Result<Self, AssociationError> {
+        if let Some(assoc) = expr.try_as_association() {
+            return Ok(assoc.clone());
+        }
+
+        let normal = match expr.try_as_normal() {
+            Some(normal) if normal.has_head(system::Association.symbol()) => normal,
+            _ => {
+                return Err(AssociationError::NotAssociation { expr: expr.clone() })
+            },
+        };
+
+        let mut assoc = Association::new();
+        for elem in normal.elements() {
+            assoc.insert_element(elem)?;
+        }
+        Ok(assoc)
+    }
+}
+
+impl Association {
+    /// Insert a rule, or each rule in a list of rules or association, into `self`.
+    fn insert_element(&mut self, elem: &Expr) -> Result<(), AssociationError> {
+        let invalid = || AssociationError::InvalidElement { elem: elem.clone() };
+
+        if let Ok(rule) = RuleRef::try_from(elem) {
+            self.insert_rule(rule.lhs().clone(), rule.kind(), rule.rhs().clone());
+            return Ok(());
+        }
+
+        let normal = match elem.kind() {
+            ExprKind::Normal(normal) => normal,
+            ExprKind::Association(assoc) => {
+                for (key, kind, value) in assoc.rules() {
+                    self.insert_rule(key.clone(), kind, value.clone());
+                }
+                return Ok(());
+            },
+            _ => return Err(invalid()),
+        };
+
+        match normal.head.try_as_symbol().map(Symbol::as_str) {
+            Some("System`List" | "System`Association") => {
+                for elem in normal.elements() {
+                    self.insert_element(elem)?;
+                }
+                Ok(())
+            },
+            _ => Err(invalid()),
         }
-        Expr::function("System`Association", elements)
     }
 }
 
@@ -115,15 +484,12 @@ impl Association {
             *indent += 4;
             writeln!(f)?
         }
-        for (i, (key, (rule, value))) in self.records.iter().enumerate() {
+        for (i, (key, kind, value)) in self.rules().enumerate() {
             let is_last = i == self.records.len() - 1;
             if alternate {
                 write!(f, "{}", " ".repeat(*indent))?
             }
-            match rule {
-                true => write!(f, "{} :> {}", key, value)?,
-                false => write!(f, "{} -> {}", key, value)?,
-            }
+            write!(f, "{} {} {}", key, kind.operator(), value)?;
             if !is_last {
                 match alternate {
                     true => writeln!(f, ",")?,
@@ -138,3 +504,21 @@ impl Association {
         write!(f, "|>")
     }
 }
+
+impl Display for AssociationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AssociationError::NotAssociation { expr } => {
+                write!(f, "{} is not an association.", expr)
+            },
+            AssociationError::InvalidElement { elem } => write!(
+                f,
+                "{} is not a valid Association element: a rule, list of rules or \
+                 association was expected.",
+                elem
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AssociationError {}
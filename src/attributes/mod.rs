@@ -0,0 +1,514 @@
+//! Symbol [attributes](https://reference.wolfram.com/language/ref/Attributes.html)
+//! <sub>WL</sub>, and canonicalization of expressions according to them.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::{Expr, ExprKind, Normal, Symbol};
+
+/// Attribute of a Wolfram Language symbol.
+///
+/// Only the attributes which affect the structure of expressions or the order in which
+/// they are evaluated are represented.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum Attribute {
+    Flat = 1 << 0,
+    Orderless = 1 << 1,
+    OneIdentity = 1 << 2,
+    Listable = 1 << 3,
+    HoldAll = 1 << 4,
+    HoldFirst = 1 << 5,
+    HoldRest = 1 << 6,
+    Protected = 1 << 7,
+}
+
+/// Set of [`Attribute`]s.
+///
+/// ```
+/// use wolfram_expr::{Attribute, Attributes};
+///
+/// let attrs = Attributes::from(&[Attribute::Flat, Attribute::Orderless][..]);
+///
+/// assert!(attrs.contains(Attribute::Flat));
+/// assert!(!attrs.contains(Attribute::HoldAll));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Attributes(u16);
+
+/// Table associating symbols with their [`Attributes`].
+///
+/// [`AttributeTable::system()`] contains the attributes of common `` System` `` symbols.
+/// Clone it to add definitions for other symbols:
+///
+/// ```
+/// use wolfram_expr::{Attribute, AttributeTable, Symbol};
+///
+/// let mut table = AttributeTable::system().clone();
+/// table.insert(Symbol::new("Global`f"), Attribute::Orderless);
+///
+/// assert!(table.get(&Symbol::new("Global`f")).contains(Attribute::Orderless));
+/// assert!(table.get(&Symbol::new("System`Plus")).contains(Attribute::Flat));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AttributeTable {
+    table: HashMap<Symbol, Attributes>,
+}
+
+impl Attribute {
+    /// All attributes, in alphabetical order.
+    pub const ALL: [Attribute; 8] = [
+        Attribute::Flat,
+        Attribute::HoldAll,
+        Attribute::HoldFirst,
+        Attribute::HoldRest,
+        Attribute::Listable,
+        Attribute::OneIdentity,
+        Attribute::Orderless,
+        Attribute::Protected,
+    ];
+
+    /// Get the `` System` `` symbol name of this attribute.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Attribute::Flat => "System`Flat",
+            Attribute::Orderless => "System`Orderless",
+            Attribute::OneIdentity => "System`OneIdentity",
+            Attribute::Listable => "System`Listable",
+            Attribute::HoldAll => "System`HoldAll",
+            Attribute::HoldFirst => "System`HoldFirst",
+            Attribute::HoldRest => "System`HoldRest",
+            Attribute::Protected => "System`Protected",
+        }
+    }
+
+    /// Get the attribute named by `symbol`, if it is one of the supported attributes.
+    pub fn from_symbol(symbol: &Symbol) -> Option<Attribute> {
+        Attribute::ALL
+            .iter()
+            .copied()
+            .find(|attr| attr.as_str() == symbol.as_str())
+    }
+}
+
+impl Attributes {
+    /// The empty set of attributes.
+    pub const fn empty() -> Self {
+        Attributes(0)
+    }
+
+    /// Returns `true` if `attr` is in this set.
+    pub fn contains(&self, attr: Attribute) -> bool {
+        self.0 & (attr as u16) != 0
+    }
+
+    /// Returns `true` if this set contains no attributes.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Add `attr` to this set.
+    pub fn insert(&mut self, attr: Attribute) {
+        self.0 |= attr as u16
+    }
+
+    /// Remove `attr` from this set.
+    pub fn remove(&mut self, attr: Attribute) {
+        self.0 &= !(attr as u16)
+    }
+
+    /// Iterate over the attributes in this set, in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = Attribute> {
+        let attrs = *self;
+        Attribute::ALL
+            .iter()
+            .copied()
+            .filter(move |attr| attrs.contains(*attr))
+    }
+
+    /// Returns `true` if the element at the 0-based position `index` of an expression
+    /// whose head has these attributes is held unevaluated.
+    pub fn holds(&self, index: usize) -> bool {
+        self.contains(Attribute::HoldAll)
+            || (index == 0 && self.contains(Attribute::HoldFirst))
+            || (index > 0 && self.contains(Attribute::HoldRest))
+    }
+
+    /// Construct the list of attribute symbols, as returned by `Attributes[sym]`.
+    pub fn to_expr(&self) -> Expr {
+        Expr::list(
+            self.iter()
+                .map(|attr| Expr::symbol(unsafe { Symbol::unchecked_new(attr.as_str()) }))
+                .collect(),
+        )
+    }
+}
+
+impl From<Attribute> for Attributes {
+    fn from(attr: Attribute) -> Self {
+        Attributes(attr as u16)
+    }
+}
+
+impl From<&[Attribute]> for Attributes {
+    fn from(attrs: &[Attribute]) -> Self {
+        attrs.iter().copied().collect()
+    }
+}
+
+impl std::iter::FromIterator<Attribute> for Attributes {
+    fn from_iter<I: IntoIterator<Item = Attribute>>(iter: I) -> Self {
+        let mut attrs = Attributes::empty();
+        for attr in iter {
+            attrs.insert(attr);
+        }
+        attrs
+    }
+}
+
+impl AttributeTable {
+    /// Construct a new table with no entries.
+    pub fn new() -> Self {
+        AttributeTable::default()
+    }
+
+    /// The attributes of common `` System` `` symbols.
+    pub fn system() -> &'static AttributeTable {
+        static SYSTEM: OnceLock<AttributeTable> = OnceLock::new();
+
+        SYSTEM.get_or_init(|| {
+            let mut table = AttributeTable::new();
+            for (attrs, names) in SYSTEM_ATTRIBUTES {
+                let attrs = Attributes::from(*attrs);
+                for name in *names {
+                    let symbol = Symbol::new(&format!("System`{}", name));
+                    table.set(symbol, attrs);
+                }
+            }
+            table
+        })
+    }
+
+    /// Get the attributes of `symbol`.
+    ///
+    /// Symbols with no entry in this table have no attributes.
+    pub fn get(&self, symbol: &Symbol) -> Attributes {
+        self.table.get(symbol).copied().unwrap_or_default()
+    }
+
+    /// Set the attributes of `symbol`, replacing any previous attributes.
+    pub fn set(&mut self, symbol: Symbol, attrs: Attributes) {
+        if attrs.is_empty() {
+            self.table.remove(&symbol);
+        } else {
+            self.table.insert(symbol, attrs);
+        }
+    }
+
+    /// Add `attr` to the attributes of `symbol`.
+    pub fn insert(&mut self, symbol: Symbol, attr: Attribute) {
+        self.table.entry(symbol).or_default().insert(attr)
+    }
+
+    /// Remove `attr` from the attributes of `symbol`.
+    pub fn remove(&mut self, symbol: &Symbol, attr: Attribute) {
+        let mut attrs = self.get(symbol);
+        attrs.remove(attr);
+        self.set(symbol.clone(), attrs)
+    }
+
+    /// Get the attributes of the head of `normal`, if it is a symbol.
+    pub fn of_head(&self, normal: &Normal) -> Attributes {
+        match normal.head.kind() {
+            ExprKind::Symbol(head) => self.get(head),
+            _ => Attributes::empty(),
+        }
+    }
+}
+
+impl Symbol {
+    /// Get the attributes of this symbol from [`AttributeTable::system()`].
+    pub fn attributes(&self) -> Attributes {
+        AttributeTable::system().get(self)
+    }
+}
+
+//======================================
+// Canonicalization
+//======================================
+
+impl Expr {
+    /// Put this expression into the canonical form determined by the attributes of the
+    /// symbols it contains, using [`AttributeTable::system()`].
+    ///
+    /// See [`Expr::canonicalize_with()`].
+    ///
+    /// # Example
+    ///
+    /// `Plus[b, a, Plus[c]]` is canonicalized to `Plus[a, b, c]`:
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, Symbol};
+    ///
+    /// let plus = |elems| Expr::function(Symbol::new("System`Plus"), elems);
+    /// let sym = |name| Expr::symbol(Symbol::new(name));
+    ///
+    /// let expr = plus(vec![sym("Global`b"), sym("Global`a"), plus(vec![sym("Global`c")])]);
+    ///
+    /// assert_eq!(
+    ///     expr.canonicalize(),
+    ///     plus(vec![sym("Global`a"), sym("Global`b"), sym("Global`c")])
+    /// );
+    /// ```
+    pub fn canonicalize(&self) -> Expr {
+        self.canonicalize_with(AttributeTable::system())
+    }
+
+    /// Put this expression into the canonical form determined by the attributes of the
+    /// symbols it contains, using the attributes in `table`.
+    ///
+    /// For each normal expression with a symbol head, from the innermost outwards:
+    ///
+    /// * If the head is [`Flat`][Attribute::Flat], elements with the same head are
+    ///   spliced into the expression.
+    /// * If the head is [`Orderless`][Attribute::Orderless], the elements are sorted into
+    ///   [canonical order][Expr::canonical_cmp].
+    /// * If the head is [`OneIdentity`][Attribute::OneIdentity] and the expression has a
+    ///   single element, it is replaced by that element.
+    ///
+    /// Elements held by [`HoldAll`][Attribute::HoldAll],
+    /// [`HoldFirst`][Attribute::HoldFirst] or [`HoldRest`][Attribute::HoldRest] are left
+    /// unchanged.
+    pub fn canonicalize_with(&self, table: &AttributeTable) -> Expr {
+        let normal = match self.kind() {
+            ExprKind::Normal(normal) => normal,
+            _ => return self.clone(),
+        };
+
+        let head = normal.head.canonicalize_with(table);
+        let attrs = match head.kind() {
+            ExprKind::Symbol(symbol) => table.get(symbol),
+            _ => Attributes::empty(),
+        };
+
+        let contents: Vec<Expr> = normal
+            .contents
+            .iter()
+            .enumerate()
+            .map(|(index, elem)| {
+                if attrs.holds(index) {
+                    elem.clone()
+                } else {
+                    elem.canonicalize_with(table)
+                }
+            })
+            .collect();
+
+        let mut contents = flatten_and_sort(&head, contents, attrs);
+
+        if attrs.contains(Attribute::OneIdentity) && contents.len() == 1 {
+            return contents.pop().unwrap();
+        }
+
+        Expr::normal(head, contents)
+    }
+}
+
+/// Splice elements with the same head as `head` into `contents` if `attrs` contains
+/// [`Attribute::Flat`], and then sort them if `attrs` contains
+/// [`Attribute::Orderless`].
+pub(crate) fn flatten_and_sort(
+    head: &Expr,
+    contents: Vec<Expr>,
+    attrs: Attributes,
+) -> Vec<Expr> {
+    let mut contents = if attrs.contains(Attribute::Flat)
+        && contents.iter().any(|elem| elem.normal_head().as_ref() == Some(head))
+    {
+        let mut flat = Vec::with_capacity(contents.len());
+        for elem in contents {
+            match elem.kind() {
+                ExprKind::Normal(inner) if inner.head == *head => {
+                    flat.extend(inner.contents.iter().cloned())
+                },
+                _ => flat.push(elem),
+            }
+        }
+        flat
+    } else {
+        contents
+    };
+
+    if attrs.contains(Attribute::Orderless) {
+        contents.sort();
+    }
+
+    contents
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+//======================================
+// System` attributes
+//======================================
+
+use self::Attribute::*;
+
+/// Attributes of `` System` `` symbols, as given by `Attributes[sym]` in the Wolfram
+/// Language.
+const SYSTEM_ATTRIBUTES: &[(&[Attribute], &[&str])] = &[
+    (&[Flat, Listable, OneIdentity, Orderless, Protected], &[
+        "Plus", "Times", "GCD", "LCM", "BitAnd", "BitOr", "BitXor",
+    ]),
+    (&[Flat, OneIdentity, Orderless, Protected], &[
+        "Max",
+        "Min",
+        "Intersection",
+        "Xor",
+    ]),
+    (&[Flat, OneIdentity, Protected], &[
+        "Dot",
+        "Join",
+        "StringJoin",
+        "Union",
+        "Composition",
+        "NonCommutativeMultiply",
+        "StringExpression",
+        "Alternatives",
+    ]),
+    (&[Flat, HoldAll, OneIdentity, Protected], &["And", "Or", "Nand", "Nor"]),
+    (&[Listable, OneIdentity, Protected], &["Power"]),
+    (&[Listable, Protected], &[
+        "Abs",
+        "ArcCos",
+        "ArcCosh",
+        "ArcCot",
+        "ArcCoth",
+        "ArcCsc",
+        "ArcCsch",
+        "ArcSec",
+        "ArcSech",
+        "ArcSin",
+        "ArcSinh",
+        "ArcTan",
+        "ArcTanh",
+        "Ceiling",
+        "Conjugate",
+        "Cos",
+        "Cosh",
+        "Cot",
+        "Coth",
+        "Csc",
+        "Csch",
+        "Divide",
+        "Exp",
+        "Factorial",
+        "Floor",
+        "Im",
+        "Log",
+        "Mod",
+        "Quotient",
+        "Re",
+        "Round",
+        "Sec",
+        "Sech",
+        "Sign",
+        "Sin",
+        "Sinh",
+        "Sqrt",
+        "StringLength",
+        "Subtract",
+        "Tan",
+        "Tanh",
+        "ToLowerCase",
+        "ToUpperCase",
+        "EvenQ",
+        "OddQ",
+        "Minus",
+    ]),
+    (&[HoldAll, Protected], &[
+        "Block",
+        "CompoundExpression",
+        "Condition",
+        "Do",
+        "For",
+        "Function",
+        "Hold",
+        "HoldForm",
+        "HoldComplete",
+        "HoldPattern",
+        "Module",
+        "SetDelayed",
+        "Table",
+        "TagSetDelayed",
+        "Timing",
+        "AbsoluteTiming",
+        "Which",
+        "While",
+        "With",
+        "Clear",
+        "ClearAll",
+        "Unevaluated",
+        "Attributes",
+    ]),
+    (&[HoldFirst, Protected], &[
+        "AddTo",
+        "AppendTo",
+        "Decrement",
+        "DivideBy",
+        "Increment",
+        "Pattern",
+        "PreDecrement",
+        "PreIncrement",
+        "PrependTo",
+        "Set",
+        "SubtractFrom",
+        "TimesBy",
+        "Unset",
+    ]),
+    (&[HoldRest, Protected], &["If", "RuleDelayed", "Switch"]),
+    (&[Protected], &[
+        "List",
+        "Rule",
+        "Sequence",
+        "Equal",
+        "Unequal",
+        "Less",
+        "LessEqual",
+        "Greater",
+        "GreaterEqual",
+        "SameQ",
+        "UnsameQ",
+        "Not",
+        "True",
+        "False",
+        "Null",
+        "Length",
+        "Part",
+        "Take",
+        "Drop",
+        "Range",
+        "Map",
+        "Select",
+        "Total",
+        "Sort",
+        "Apply",
+        "StringSplit",
+        "Rational",
+        "Complex",
+        "N",
+        "D",
+        "Pi",
+        "E",
+        "I",
+        "Infinity",
+        "ComplexInfinity",
+        "Indeterminate",
+    ]),
+];
@@ -0,0 +1,523 @@
+//! Compilation of numeric expressions into flat bytecode, for fast repeated evaluation
+//! with machine real arguments.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{numerical, Expr, ExprKind, Normal, Symbol};
+
+/// A numeric expression compiled into a function of machine real arguments.
+///
+/// Constructed by [`Expr::compile()`]. The expression is lowered into a flat sequence
+/// of instructions, each of which computes one value from the arguments, a constant, or
+/// the values of earlier instructions, so evaluating the function does not traverse
+/// the original expression.
+///
+/// Evaluation uses IEEE floating-point semantics: arguments outside the domain of a
+/// function produce NaN instead of a complex number, e.g. `Sqrt[x]` for negative `x`.
+#[derive(Debug, Clone)]
+pub struct CompiledFn {
+    params: Vec<Symbol>,
+    ops: Vec<Op>,
+}
+
+/// Error returned by [`Expr::compile()`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileError {
+    /// `symbol` is neither a parameter of the function nor a known real constant.
+    #[allow(missing_docs)]
+    UnknownSymbol { symbol: Symbol },
+    /// `expr` is not a number, or an arithmetic or elementary function that can be
+    /// compiled.
+    #[allow(missing_docs)]
+    Unsupported { expr: Expr },
+}
+
+/// A single instruction. Operands are indices of earlier instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Op {
+    /// A constant, stored as the bits of an `f64` so that `Op` can be hashed.
+    Const(u64),
+    /// The argument at an index.
+    Arg(usize),
+    Unary(Unary, usize),
+    Binary(Binary, usize, usize),
+    /// Raise a value to an integer power.
+    Powi(usize, i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Unary {
+    Neg,
+    Recip,
+    Sqrt,
+    Exp,
+    Log,
+    Abs,
+    Sin,
+    Cos,
+    Tan,
+    ArcSin,
+    ArcCos,
+    ArcTan,
+    Sinh,
+    Cosh,
+    Tanh,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Binary {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    /// `ArcTan[x, y]`
+    ArcTan2,
+}
+
+impl Expr {
+    /// Compile this expression into a function of `params`.
+    ///
+    /// The expression may contain numbers, `Rational[n, d]`, the symbols in `params`,
+    /// the constants `Pi`, `E`, `Degree`, `GoldenRatio` and `EulerGamma`, and the
+    /// functions `Plus`, `Times`, `Power`, `Subtract`, `Minus`, `Divide`, `Sqrt`, `Exp`,
+    /// `Log`, `Abs`, `Sin`, `Cos`, `Tan`, `Cot`, `Sec`, `Csc`, `ArcSin`, `ArcCos`,
+    /// `ArcTan`, `Sinh`, `Cosh`, `Tanh` and `N`.
+    ///
+    /// Sub-expressions that do not depend on `params` are evaluated once, during
+    /// compilation, and identical sub-expressions are only computed once per call.
+    ///
+    /// # Example
+    ///
+    /// Compile `x^2 + Sin[y]`:
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, Symbol};
+    ///
+    /// let x = Symbol::new("Global`x");
+    /// let y = Symbol::new("Global`y");
+    ///
+    /// let expr = Expr::function(Symbol::new("System`Plus"), vec![
+    ///     Expr::function(Symbol::new("System`Power"), vec![
+    ///         Expr::from(x.clone()),
+    ///         Expr::from(2),
+    ///     ]),
+    ///     Expr::function(Symbol::new("System`Sin"), vec![Expr::from(y.clone())]),
+    /// ]);
+    ///
+    /// let function = expr.compile(&[x, y]).unwrap();
+    ///
+    /// assert_eq!(function.call(&[3.0, 0.0]), 9.0);
+    /// ```
+    pub fn compile(&self, params: &[Symbol]) -> Result<CompiledFn, CompileError> {
+        let mut compiler = Compiler {
+            params,
+            ops: Vec::new(),
+            interned: HashMap::new(),
+            lowered: HashMap::new(),
+        };
+
+        let result = compiler.lower(self)?;
+
+        Ok(CompiledFn {
+            params: params.to_vec(),
+            ops: live_ops(compiler.ops, result),
+        })
+    }
+}
+
+impl CompiledFn {
+    /// Evaluate this function with the given arguments, in the same order as its
+    /// [parameters][CompiledFn::params].
+    ///
+    /// This allocates space for intermediate values on each call. Use
+    /// [`CompiledFn::call_with()`] to reuse an allocation across calls.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the number of `args` does not match the number of
+    /// parameters.
+    pub fn call(&self, args: &[f64]) -> f64 {
+        self.call_with(args, &mut Vec::with_capacity(self.ops.len()))
+    }
+
+    /// Evaluate this function with the given arguments, using `registers` as storage
+    /// for intermediate values.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the number of `args` does not match the number of
+    /// parameters.
+    pub fn call_with(&self, args: &[f64], registers: &mut Vec<f64>) -> f64 {
+        assert_eq!(
+            args.len(),
+            self.params.len(),
+            "CompiledFn::call: wrong number of arguments"
+        );
+
+        registers.clear();
+
+        for op in &self.ops {
+            let value = match *op {
+                Op::Const(bits) => f64::from_bits(bits),
+                Op::Arg(index) => args[index],
+                Op::Unary(function, x) => function.apply(registers[x]),
+                Op::Binary(function, x, y) => function.apply(registers[x], registers[y]),
+                Op::Powi(x, exp) => registers[x].powi(exp),
+            };
+            registers.push(value);
+        }
+
+        // The last instruction computes the result.
+        registers[self.ops.len() - 1]
+    }
+
+    /// The parameters of this function.
+    pub fn params(&self) -> &[Symbol] {
+        &self.params
+    }
+
+    /// The number of instructions executed on each call.
+    pub fn instruction_count(&self) -> usize {
+        self.ops.len()
+    }
+}
+
+//======================================
+// Lowering
+//======================================
+
+struct Compiler<'p> {
+    params: &'p [Symbol],
+    ops: Vec<Op>,
+    /// Index of the instruction computing each distinct `Op`.
+    interned: HashMap<Op, usize>,
+    /// Index of the instruction computing each sub-expression lowered so far, keyed by
+    /// its address so that a lookup does not hash the whole sub-expression. The `Expr`
+    /// is kept so that the address is not reused; equal sub-expressions at different
+    /// addresses still share instructions through `interned`.
+    lowered: HashMap<*const ExprKind, (Expr, usize)>,
+}
+
+impl<'p> Compiler<'p> {
+    fn lower(&mut self, expr: &Expr) -> Result<usize, CompileError> {
+        let key = Arc::as_ptr(&expr.inner);
+        if let Some((_, index)) = self.lowered.get(&key) {
+            return Ok(*index);
+        }
+
+        let index = match expr.kind() {
+            ExprKind::Integer(value) => self.constant(*value as f64),
+            ExprKind::Real(value) => self.constant(**value),
+            ExprKind::String(_) | ExprKind::Association(_) => {
+                return Err(unsupported(expr))
+            },
+            ExprKind::Symbol(symbol) => self.lower_symbol(symbol)?,
+            ExprKind::Normal(normal) => self.lower_normal(expr, normal)?,
+        };
+
+        self.lowered.insert(key, (expr.clone(), index));
+
+        Ok(index)
+    }
+
+    fn lower_symbol(&mut self, symbol: &Symbol) -> Result<usize, CompileError> {
+        if let Some(index) = self.params.iter().position(|param| param == symbol) {
+            return Ok(self.push(Op::Arg(index)));
+        }
+
+        match numerical::constant(symbol.as_str()) {
+            Some(value) if value.is_real() => Ok(self.constant(value.re)),
+            _ => Err(CompileError::UnknownSymbol {
+                symbol: symbol.clone(),
+            }),
+        }
+    }
+
+    fn lower_normal(
+        &mut self,
+        expr: &Expr,
+        normal: &Normal,
+    ) -> Result<usize, CompileError> {
+        let name = match normal.head.try_as_symbol() {
+            Some(head) => head.as_str(),
+            None => return Err(unsupported(expr)),
+        };
+
+        let args = normal.elements();
+
+        let unary = match (name, args) {
+            ("System`Sqrt", [_]) => Unary::Sqrt,
+            ("System`Exp", [_]) => Unary::Exp,
+            ("System`Log", [_]) => Unary::Log,
+            ("System`Abs", [_]) => Unary::Abs,
+            ("System`Sin", [_]) => Unary::Sin,
+            ("System`Cos", [_]) => Unary::Cos,
+            ("System`Tan", [_]) => Unary::Tan,
+            ("System`ArcSin", [_]) => Unary::ArcSin,
+            ("System`ArcCos", [_]) => Unary::ArcCos,
+            ("System`ArcTan", [_]) => Unary::ArcTan,
+            ("System`Sinh", [_]) => Unary::Sinh,
+            ("System`Cosh", [_]) => Unary::Cosh,
+            ("System`Tanh", [_]) => Unary::Tanh,
+            ("System`Minus", [_]) => Unary::Neg,
+            _ => return self.lower_special(expr, name, args),
+        };
+
+        let x = self.lower(&args[0])?;
+
+        Ok(self.unary(unary, x))
+    }
+
+    /// Lower the functions which are not a single [`Unary`] operation.
+    fn lower_special(
+        &mut self,
+        expr: &Expr,
+        name: &str,
+        args: &[Expr],
+    ) -> Result<usize, CompileError> {
+        let index = match (name, args) {
+            ("System`Plus", _) => {
+                let mut sum = self.constant(0.0);
+                for arg in args {
+                    let arg = self.lower(arg)?;
+                    sum = self.binary(Binary::Add, sum, arg);
+                }
+                sum
+            },
+            ("System`Times", _) => {
+                let mut product = self.constant(1.0);
+                for arg in args {
+                    let arg = self.lower(arg)?;
+                    product = self.binary(Binary::Mul, product, arg);
+                }
+                product
+            },
+            ("System`Power", [base, exp]) => {
+                let base = self.lower(base)?;
+                let exp = self.lower(exp)?;
+                self.power(base, exp)
+            },
+            ("System`Subtract", [x, y]) => {
+                let (x, y) = (self.lower(x)?, self.lower(y)?);
+                self.binary(Binary::Sub, x, y)
+            },
+            ("System`Divide", [x, y]) | ("System`Rational", [x, y]) => {
+                let (x, y) = (self.lower(x)?, self.lower(y)?);
+                self.binary(Binary::Div, x, y)
+            },
+            ("System`Log", [base, x]) => {
+                let (base, x) = (self.lower(base)?, self.lower(x)?);
+                let (base, x) = (self.unary(Unary::Log, base), self.unary(Unary::Log, x));
+                self.binary(Binary::Div, x, base)
+            },
+            ("System`ArcTan", [x, y]) => {
+                let (x, y) = (self.lower(x)?, self.lower(y)?);
+                self.binary(Binary::ArcTan2, x, y)
+            },
+            ("System`Cot", [x]) | ("System`Sec", [x]) | ("System`Csc", [x]) => {
+                let function = match name {
+                    "System`Cot" => Unary::Tan,
+                    "System`Sec" => Unary::Cos,
+                    _ => Unary::Sin,
+                };
+                let x = self.lower(x)?;
+                let value = self.unary(function, x);
+                self.unary(Unary::Recip, value)
+            },
+            ("System`N", [x]) | ("System`N", [x, _]) => self.lower(x)?,
+            _ => return Err(unsupported(expr)),
+        };
+
+        Ok(index)
+    }
+
+    //==================================
+    // Instructions
+    //==================================
+
+    /// Add `op`, or return the index of an identical existing instruction.
+    fn push(&mut self, op: Op) -> usize {
+        if let Some(index) = self.interned.get(&op) {
+            return *index;
+        }
+
+        let index = self.ops.len();
+        self.ops.push(op);
+        self.interned.insert(op, index);
+        index
+    }
+
+    fn constant(&mut self, value: f64) -> usize {
+        self.push(Op::Const(value.to_bits()))
+    }
+
+    fn constant_value(&self, index: usize) -> Option<f64> {
+        match self.ops[index] {
+            Op::Const(bits) => Some(f64::from_bits(bits)),
+            _ => None,
+        }
+    }
+
+    fn unary(&mut self, function: Unary, x: usize) -> usize {
+        match self.constant_value(x) {
+            Some(x) => self.constant(function.apply(x)),
+            None => self.push(Op::Unary(function, x)),
+        }
+    }
+
+    fn binary(&mut self, function: Binary, x: usize, y: usize) -> usize {
+        let (x_value, y_value) = (self.constant_value(x), self.constant_value(y));
+
+        if let (Some(x), Some(y)) = (x_value, y_value) {
+            return self.constant(function.apply(x, y));
+        }
+
+        // `x * 0` is not folded, as it is NaN for infinite or NaN `x`.
+        match (function, x_value, y_value) {
+            (Binary::Add, Some(0.0), _) => return y,
+            (Binary::Add, _, Some(0.0)) | (Binary::Sub, _, Some(0.0)) => return x,
+            (Binary::Mul, Some(1.0), _) => return y,
+            (Binary::Mul, _, Some(1.0)) | (Binary::Div, _, Some(1.0)) => return x,
+            (Binary::Mul, Some(-1.0), _) => return self.unary(Unary::Neg, y),
+            (Binary::Mul, _, Some(-1.0)) => return self.unary(Unary::Neg, x),
+            (Binary::Div, Some(1.0), _) => return self.unary(Unary::Recip, y),
+            _ => (),
+        }
+
+        // Order the operands of commutative operations so that `x + y` and `y + x` are
+        // the same instruction.
+        let (x, y) = match function {
+            Binary::Add | Binary::Mul if y < x => (y, x),
+            _ => (x, y),
+        };
+
+        self.push(Op::Binary(function, x, y))
+    }
+
+    fn power(&mut self, base: usize, exp: usize) -> usize {
+        let exp_value = match self.constant_value(exp) {
+            Some(exp_value) => exp_value,
+            None => return self.binary(Binary::Pow, base, exp),
+        };
+
+        if let Some(base_value) = self.constant_value(base) {
+            return self.constant(base_value.powf(exp_value));
+        }
+
+        if exp_value == 0.5 {
+            return self.unary(Unary::Sqrt, base);
+        }
+
+        if exp_value.fract() == 0.0 && exp_value.abs() <= f64::from(i32::MAX) {
+            return match exp_value as i32 {
+                0 => self.constant(1.0),
+                1 => base,
+                -1 => self.unary(Unary::Recip, base),
+                exp => self.push(Op::Powi(base, exp)),
+            };
+        }
+
+        self.binary(Binary::Pow, base, exp)
+    }
+}
+
+/// Remove the instructions which do not contribute to the instruction at `result`,
+/// and make `result` the last instruction.
+fn live_ops(ops: Vec<Op>, result: usize) -> Vec<Op> {
+    let mut live = vec![false; ops.len()];
+    live[result] = true;
+
+    // Operands always come before the instructions that use them.
+    for index in (0..=result).rev() {
+        if !live[index] {
+            continue;
+        }
+        match ops[index] {
+            Op::Const(_) | Op::Arg(_) => (),
+            Op::Unary(_, x) | Op::Powi(x, _) => live[x] = true,
+            Op::Binary(_, x, y) => {
+                live[x] = true;
+                live[y] = true;
+            },
+        }
+    }
+
+    let mut new_index = vec![usize::MAX; ops.len()];
+    let mut compacted = Vec::with_capacity(ops.len());
+
+    for (index, op) in ops.into_iter().enumerate().take(result + 1) {
+        if !live[index] {
+            continue;
+        }
+        new_index[index] = compacted.len();
+        compacted.push(match op {
+            Op::Const(_) | Op::Arg(_) => op,
+            Op::Unary(function, x) => Op::Unary(function, new_index[x]),
+            Op::Binary(function, x, y) => {
+                Op::Binary(function, new_index[x], new_index[y])
+            },
+            Op::Powi(x, exp) => Op::Powi(new_index[x], exp),
+        });
+    }
+
+    compacted
+}
+
+impl Unary {
+    fn apply(self, x: f64) -> f64 {
+        match self {
+            Unary::Neg => -x,
+            Unary::Recip => 1.0 / x,
+            Unary::Sqrt => x.sqrt(),
+            Unary::Exp => x.exp(),
+            Unary::Log => x.ln(),
+            Unary::Abs => x.abs(),
+            Unary::Sin => x.sin(),
+            Unary::Cos => x.cos(),
+            Unary::Tan => x.tan(),
+            Unary::ArcSin => x.asin(),
+            Unary::ArcCos => x.acos(),
+            Unary::ArcTan => x.atan(),
+            Unary::Sinh => x.sinh(),
+            Unary::Cosh => x.cosh(),
+            Unary::Tanh => x.tanh(),
+        }
+    }
+}
+
+impl Binary {
+    fn apply(self, x: f64, y: f64) -> f64 {
+        match self {
+            Binary::Add => x + y,
+            Binary::Sub => x - y,
+            Binary::Mul => x * y,
+            Binary::Div => x / y,
+            Binary::Pow => x.powf(y),
+            Binary::ArcTan2 => y.atan2(x),
+        }
+    }
+}
+
+fn unsupported(expr: &Expr) -> CompileError {
+    CompileError::Unsupported { expr: expr.clone() }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompileError::UnknownSymbol { symbol } => write!(
+                f,
+                "{} is not a parameter or a real-valued constant.",
+                symbol
+            ),
+            CompileError::Unsupported { expr } => {
+                write!(f, "{} cannot be compiled.", expr)
+            },
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
@@ -9,7 +9,27 @@ impl Expr {
             ExprKind::Symbol(_)
             | ExprKind::String(_)
             | ExprKind::Integer(_)
-            | ExprKind::Real(_) => None,
+            | ExprKind::Real(_)
+            | ExprKind::Association(_) => None,
+        }
+    }
+
+    /// If this is a [`Normal`] expression, return mutable access to it. Otherwise return
+    /// None.
+    ///
+    /// If the reference count of this expression is not equal to 1, the [`Normal`] will
+    /// be cloned to make it unique, as with [`Expr::kind_mut()`].
+    pub fn try_as_normal_mut(&mut self) -> Option<&mut Normal> {
+        // Check the variant first, so that atomic expressions are not made unique.
+        self.try_as_normal()?;
+
+        match self.kind_mut() {
+            ExprKind::Normal(normal) => Some(normal),
+            ExprKind::Symbol(_)
+            | ExprKind::String(_)
+            | ExprKind::Integer(_)
+            | ExprKind::Real(_)
+            | ExprKind::Association(_) => None,
         }
     }
 
@@ -40,7 +60,8 @@ impl Expr {
             ExprKind::Normal(_)
             | ExprKind::String(_)
             | ExprKind::Integer(_)
-            | ExprKind::Real(_) => None,
+            | ExprKind::Real(_)
+            | ExprKind::Association(_) => None,
         }
     }
 
@@ -49,7 +70,37 @@ impl Expr {
         match self.kind() {
             ExprKind::Integer(int) => Some(Number::Integer(*int)),
             ExprKind::Real(real) => Some(Number::Real(*real)),
-            ExprKind::Normal(_) | ExprKind::String(_) | ExprKind::Symbol(_) => None,
+            ExprKind::Normal(_)
+            | ExprKind::String(_)
+            | ExprKind::Symbol(_)
+            | ExprKind::Association(_) => None,
+        }
+    }
+
+    /// If this is an [`Association`] expression, return that. Otherwise return None.
+    ///
+    /// An `Association[...]` [`Normal`] expression, such as an unevaluated association
+    /// built with [`Expr::normal()`], is not an [`Association`]; see
+    /// [`Association::try_from()`][TryFrom::try_from] for parsing one.
+    pub fn try_as_association(&self) -> Option<&Association> {
+        match self.kind() {
+            ExprKind::Association(assoc) => Some(assoc),
+            _ => None,
+        }
+    }
+
+    /// If this is an [`Association`] expression, return mutable access to it. Otherwise
+    /// return None.
+    ///
+    /// If the reference count of this expression is not equal to 1, the [`Association`]
+    /// will be cloned to make it unique, as with [`Expr::kind_mut()`].
+    pub fn try_as_association_mut(&mut self) -> Option<&mut Association> {
+        // Check the variant first, so that other expressions are not made unique.
+        self.try_as_association()?;
+
+        match self.kind_mut() {
+            ExprKind::Association(assoc) => Some(assoc),
+            _ => None,
         }
     }
 }
@@ -81,8 +132,8 @@ impl From<Normal> for Expr {
 impl From<bool> for Expr {
     fn from(value: bool) -> Expr {
         match value {
-            true => Expr::symbol(Symbol::new("System`True")),
-            false => Expr::symbol(Symbol::new("System`False")),
+            true => Expr::from(system::True),
+            false => Expr::from(system::False),
         }
     }
 }
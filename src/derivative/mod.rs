@@ -0,0 +1,314 @@
+//! Symbolic differentiation, as in
+//! [`D`](https://reference.wolfram.com/language/ref/D.html) <sub>WL</sub>.
+
+use crate::{
+    evaluate::as_normal_with_head, simplify::arithmetic, Association, Expr, ExprKind,
+    Normal, Symbol,
+};
+
+impl Expr {
+    /// Compute the partial derivative of this expression with respect to `x`.
+    ///
+    /// The derivatives of `Plus`, `Times`, `Power`, `Subtract`, `Minus`, `Divide`,
+    /// `Sqrt`, `Exp`, `Log`, the trigonometric and hyperbolic functions and their
+    /// inverses are computed using the sum, product, quotient and chain rules. Lists are
+    /// differentiated element-wise. Any other function `f` is assumed to depend on its
+    /// arguments, and its derivative is expressed using
+    /// [`Derivative`](https://reference.wolfram.com/language/ref/Derivative.html)
+    /// <sub>WL</sub>, e.g. the derivative of `f[x, x^2]` is
+    /// ``Derivative[1, 0][f][x, x^2] + 2 x Derivative[0, 1][f][x, x^2]``.
+    ///
+    /// The result is simplified using the same arithmetic rules as [`Evaluator`]
+    /// (numbers are combined, like terms and factors are collected, and the elements of
+    /// sums and products are sorted into [canonical order][Expr::canonical_cmp]).
+    ///
+    /// [`Evaluator`]: crate::Evaluator
+    ///
+    /// # Example
+    ///
+    /// Differentiate `x^3 Sin[x]` to get `Cos[x] x^3 + 3 Sin[x] x^2`:
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, Symbol};
+    ///
+    /// let call = |head: &str, elems| Expr::function(Symbol::new(head), elems);
+    /// let x = Symbol::new("Global`x");
+    /// let power = |exp: i64| call("System`Power", vec![x.clone().into(), exp.into()]);
+    /// let sin = call("System`Sin", vec![x.clone().into()]);
+    /// let cos = call("System`Cos", vec![x.clone().into()]);
+    ///
+    /// let expr = call("System`Times", vec![power(3), sin.clone()]);
+    ///
+    /// assert_eq!(
+    ///     expr.derivative(&x),
+    ///     call("System`Plus", vec![
+    ///         call("System`Times", vec![cos, power(3)]),
+    ///         call("System`Times", vec![Expr::from(3), sin, power(2)]),
+    ///     ])
+    /// );
+    /// ```
+    pub fn derivative(&self, x: &Symbol) -> Expr {
+        if !depends_on(self, x) {
+            return Expr::from(0);
+        }
+
+        match self.kind() {
+            ExprKind::Normal(normal) => normal_derivative(self, normal, x),
+            // Associations are differentiated value-wise, like lists.
+            ExprKind::Association(assoc) => {
+                let mut derivative = Association::new();
+                for (key, kind, value) in assoc.rules() {
+                    derivative.insert_rule(key.clone(), kind, value.derivative(x));
+                }
+                Expr::from(derivative)
+            },
+            // The only atom which depends on `x` is `x` itself.
+            _ => Expr::from(1),
+        }
+    }
+}
+
+/// Returns `true` if `x` appears anywhere in `expr`.
+fn depends_on(expr: &Expr, x: &Symbol) -> bool {
+    match expr.kind() {
+        ExprKind::Symbol(symbol) => symbol == x,
+        ExprKind::Normal(normal) => {
+            depends_on(&normal.head, x)
+                || normal.elements().iter().any(|elem| depends_on(elem, x))
+        },
+        ExprKind::Association(assoc) => assoc
+            .rules()
+            .any(|(key, _, value)| depends_on(key, x) || depends_on(value, x)),
+        ExprKind::Integer(_) | ExprKind::Real(_) | ExprKind::String(_) => false,
+    }
+}
+
+fn normal_derivative(expr: &Expr, normal: &Normal, x: &Symbol) -> Expr {
+    let name = normal.head.try_as_symbol().map(Symbol::as_str);
+    let args = normal.elements();
+    let d = |elem: &Expr| elem.derivative(x);
+
+    match (name, args) {
+        (Some("System`Plus"), _) => plus(args.iter().map(d).collect()),
+        (Some("System`List"), _) => Expr::list(args.iter().map(d).collect()),
+        (Some("System`Times"), _) => product_rule(args, x),
+        (Some("System`Power"), [base, exp]) => power_rule(expr, base, exp, x),
+        (Some("System`Subtract"), [a, b]) => {
+            plus(vec![d(a), times(vec![Expr::from(-1), d(b)])])
+        },
+        (Some("System`Minus"), [a]) => times(vec![Expr::from(-1), d(a)]),
+        // The remaining cases are rewritten in terms of Times and Power.
+        (Some("System`Divide"), [a, b]) => {
+            times(vec![a.clone(), power(b.clone(), Expr::from(-1))]).derivative(x)
+        },
+        (Some("System`Sqrt"), [u]) => power(u.clone(), rational(1, 2)).derivative(x),
+        (Some("System`Log"), [base, u]) => {
+            let log_base = call("System`Log", vec![base.clone()]);
+            let log_u = call("System`Log", vec![u.clone()]);
+            times(vec![log_u, power(log_base, Expr::from(-1))]).derivative(x)
+        },
+        (Some(name), [u]) => match outer_derivative(name, u) {
+            Some(outer) => times(vec![outer, d(u)]),
+            None => chain_rule(normal, x),
+        },
+        _ => chain_rule(normal, x),
+    }
+}
+
+/// `D[f1 * f2 * ..., x]`: `D[f1, x] * f2 * ... + f1 * D[f2, x] * ... + ...`
+fn product_rule(factors: &[Expr], x: &Symbol) -> Expr {
+    let terms = (0..factors.len())
+        .filter(|&index| depends_on(&factors[index], x))
+        .map(|index| {
+            let mut factors = factors.to_vec();
+            factors[index] = factors[index].derivative(x);
+            times(factors)
+        })
+        .collect();
+
+    plus(terms)
+}
+
+/// `D[u^v, x]`, where `expr` is `u^v`.
+fn power_rule(expr: &Expr, base: &Expr, exp: &Expr, x: &Symbol) -> Expr {
+    // D[u^n, x] == n u^(n - 1) D[u, x]
+    if !depends_on(exp, x) {
+        let reduced = power(base.clone(), plus(vec![exp.clone(), Expr::from(-1)]));
+        return times(vec![exp.clone(), reduced, base.derivative(x)]);
+    }
+
+    let log_base = call("System`Log", vec![base.clone()]);
+
+    // D[a^v, x] == a^v Log[a] D[v, x]
+    if !depends_on(base, x) {
+        return match base.try_as_symbol().map(Symbol::as_str) {
+            Some("System`E") => times(vec![expr.clone(), exp.derivative(x)]),
+            _ => times(vec![expr.clone(), log_base, exp.derivative(x)]),
+        };
+    }
+
+    // D[u^v, x] == u^v (D[v, x] Log[u] + v D[u, x] / u)
+    times(vec![
+        expr.clone(),
+        plus(vec![
+            times(vec![exp.derivative(x), log_base]),
+            times(vec![
+                exp.clone(),
+                base.derivative(x),
+                power(base.clone(), Expr::from(-1)),
+            ]),
+        ]),
+    ])
+}
+
+/// The derivative `f'[u]` of the built-in function `f` of one argument named `name`,
+/// or `None` if it is not known.
+fn outer_derivative(name: &str, u: &Expr) -> Option<Expr> {
+    let apply = |name: &str| call(name, vec![u.clone()]);
+    let negate = |expr: Expr| times(vec![Expr::from(-1), expr]);
+    let square = |expr: Expr| power(expr, Expr::from(2));
+    // 1 + u^2 and 1 - u^2
+    let one_plus_square = || plus(vec![Expr::from(1), square(u.clone())]);
+    let one_minus_square = || plus(vec![Expr::from(1), negate(square(u.clone()))]);
+    // 1 / (u^2 Sqrt[1 + sign / u^2])
+    let inverse_square_root = |sign: i64| {
+        let inverse_square = power(u.clone(), Expr::from(-2));
+        let root = plus(vec![
+            Expr::from(1),
+            times(vec![Expr::from(sign), inverse_square.clone()]),
+        ]);
+        times(vec![inverse_square, power(root, rational(-1, 2))])
+    };
+
+    let derivative = match name {
+        "System`Exp" => apply("System`Exp"),
+        "System`Log" => power(u.clone(), Expr::from(-1)),
+        "System`Sin" => apply("System`Cos"),
+        "System`Cos" => negate(apply("System`Sin")),
+        "System`Tan" => square(apply("System`Sec")),
+        "System`Cot" => negate(square(apply("System`Csc"))),
+        "System`Sec" => times(vec![apply("System`Sec"), apply("System`Tan")]),
+        "System`Csc" => negate(times(vec![apply("System`Cot"), apply("System`Csc")])),
+        "System`ArcSin" => power(one_minus_square(), rational(-1, 2)),
+        "System`ArcCos" => negate(power(one_minus_square(), rational(-1, 2))),
+        "System`ArcTan" => power(one_plus_square(), Expr::from(-1)),
+        "System`ArcCot" => negate(power(one_plus_square(), Expr::from(-1))),
+        "System`ArcSec" => inverse_square_root(-1),
+        "System`ArcCsc" => negate(inverse_square_root(-1)),
+        "System`Sinh" => apply("System`Cosh"),
+        "System`Cosh" => apply("System`Sinh"),
+        "System`Tanh" => square(apply("System`Sech")),
+        "System`Coth" => negate(square(apply("System`Csch"))),
+        "System`Sech" => negate(times(vec![apply("System`Sech"), apply("System`Tanh")])),
+        "System`Csch" => negate(times(vec![apply("System`Coth"), apply("System`Csch")])),
+        "System`ArcSinh" => power(one_plus_square(), rational(-1, 2)),
+        "System`ArcCosh" => times(vec![
+            power(plus(vec![Expr::from(-1), u.clone()]), rational(-1, 2)),
+            power(plus(vec![Expr::from(1), u.clone()]), rational(-1, 2)),
+        ]),
+        "System`ArcTanh" | "System`ArcCoth" => power(one_minus_square(), Expr::from(-1)),
+        // -1 / (u (1 + u) Sqrt[(1 - u) / (1 + u)])
+        "System`ArcSech" => {
+            let one_plus_u = || plus(vec![Expr::from(1), u.clone()]);
+            let one_minus_u = plus(vec![Expr::from(1), negate(u.clone())]);
+            let ratio = times(vec![one_minus_u, power(one_plus_u(), Expr::from(-1))]);
+            negate(times(vec![
+                power(u.clone(), Expr::from(-1)),
+                power(one_plus_u(), Expr::from(-1)),
+                power(ratio, rational(-1, 2)),
+            ]))
+        },
+        "System`ArcCsch" => negate(inverse_square_root(1)),
+        _ => return None,
+    };
+
+    Some(derivative)
+}
+
+/// `D[f[u1, u2, ...], x]` for an unknown function `f`:
+/// `Derivative[1, 0, ...][f][u1, u2, ...] D[u1, x] + ...`
+fn chain_rule(normal: &Normal, x: &Symbol) -> Expr {
+    let args = normal.elements();
+
+    // Derivative[n1, n2, ...][f][u1, u2, ...] is differentiated by incrementing the
+    // order of the derivative in each argument.
+    let (function, orders) = match derivative_head(&normal.head) {
+        Some((function, orders)) if orders.len() == args.len() => (function, orders),
+        _ => (normal.head.clone(), vec![0; args.len()]),
+    };
+
+    let terms = (0..args.len())
+        .filter(|&index| depends_on(&args[index], x))
+        .map(|index| {
+            let head = match orders[index].checked_add(1) {
+                Some(order) => {
+                    let mut orders = orders.clone();
+                    orders[index] = order;
+                    derivative_operator(&function, orders)
+                },
+                // The order would overflow, so `Derivative[n1, n2, ...][f]` is
+                // differentiated as an unknown function instead.
+                None => {
+                    let mut orders = vec![0; args.len()];
+                    orders[index] = 1;
+                    derivative_operator(&normal.head, orders)
+                },
+            };
+            times(vec![Expr::normal(head, args.to_vec()), args[index].derivative(x)])
+        })
+        .collect();
+
+    plus(terms)
+}
+
+/// `Derivative[n1, n2, ...][f]`
+fn derivative_operator(function: &Expr, orders: Vec<i64>) -> Expr {
+    let orders = orders.into_iter().map(Expr::from).collect();
+    Expr::normal(call("System`Derivative", orders), vec![function.clone()])
+}
+
+/// Split `Derivative[n1, n2, ...][f]` into `f` and the orders `n1, n2, ...`.
+fn derivative_head(head: &Expr) -> Option<(Expr, Vec<i64>)> {
+    let (normal, function) = match head.kind() {
+        ExprKind::Normal(normal) => match normal.elements() {
+            [function] => (normal, function),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let orders = as_normal_with_head(&normal.head, "System`Derivative")?
+        .elements()
+        .iter()
+        .map(|order| match order.kind() {
+            ExprKind::Integer(order) if *order >= 0 => Some(*order),
+            _ => None,
+        })
+        .collect::<Option<Vec<i64>>>()?;
+
+    Some((function.clone(), orders))
+}
+
+//======================================
+// Simplifying constructors
+//======================================
+
+fn plus(terms: Vec<Expr>) -> Expr {
+    arithmetic("System`Plus", terms)
+}
+
+fn times(factors: Vec<Expr>) -> Expr {
+    arithmetic("System`Times", factors)
+}
+
+fn power(base: Expr, exp: Expr) -> Expr {
+    arithmetic("System`Power", vec![base, exp])
+}
+
+fn rational(numer: i64, denom: i64) -> Expr {
+    call("System`Rational", vec![Expr::from(numer), Expr::from(denom)])
+}
+
+fn call(head: &str, contents: Vec<Expr>) -> Expr {
+    Expr::function(Symbol::new(head), contents)
+}
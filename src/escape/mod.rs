@@ -0,0 +1,211 @@
+//! Escaping and unescaping of Wolfram Language string literals.
+//!
+//! [`Escaped`] writes a string as a WL string literal that contains only printable
+//! ASCII characters, so that it reads back as the same string regardless of the
+//! character encoding used to transmit it:
+//!
+//! Character                       | Escape
+//! --------------------------------|-------------------------------
+//! `"`, `\`                        | `\"`, `\\`
+//! newline, tab, carriage return   | `\n`, `\t`, `\r`
+//! named characters, like `α`      | `\[Alpha]`
+//! other characters up to `U+FFFF` | `\:xxxx`
+//! characters above `U+FFFF`       | `\|xxxxxx`
+//!
+//! [`unescape()`] accepts each of these escapes, along with `\b`, `\f` and the
+//! `\.xx` form for characters up to `U+FF`.
+
+use std::fmt::{self, Write};
+
+/// Display wrapper which writes a string as an escaped WL string literal, including
+/// the surrounding quotes.
+pub(crate) struct Escaped<'s>(pub &'s str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_char('"')?;
+
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\t' => f.write_str("\\t")?,
+                '\r' => f.write_str("\\r")?,
+                ' '..='~' => f.write_char(c)?,
+                _ => match character_name(c) {
+                    Some(name) => write!(f, "\\[{}]", name)?,
+                    None if c <= '\u{FFFF}' => write!(f, "\\:{:04x}", c as u32)?,
+                    None => write!(f, "\\|{:06x}", c as u32)?,
+                },
+            }
+        }
+
+        f.write_char('"')
+    }
+}
+
+/// Error returned by [`unescape()`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct UnescapeError {
+    /// The byte offset of the invalid escape sequence in the input.
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+/// Replace the escape sequences in the contents of a WL string literal with the
+/// characters they represent.
+///
+/// An unrecognized escape, like `\q`, is kept as written, backslash included.
+pub(crate) fn unescape(literal: &str) -> Result<String, UnescapeError> {
+    let mut string = String::with_capacity(literal.len());
+    let mut rest = literal;
+
+    while let Some(index) = rest.find('\\') {
+        string.push_str(&rest[..index]);
+
+        let offset = literal.len() - rest.len() + index;
+        let error = |message| UnescapeError { offset, message };
+
+        let escape = &rest[index + 1..];
+        let (c, len) = match escape.chars().next() {
+            Some('"') => ('"', 1),
+            Some('\\') => ('\\', 1),
+            Some('n') => ('\n', 1),
+            Some('t') => ('\t', 1),
+            Some('r') => ('\r', 1),
+            Some('b') => ('\u{8}', 1),
+            Some('f') => ('\u{c}', 1),
+            Some(prefix @ ('.' | ':' | '|')) => {
+                let digits = match prefix {
+                    '.' => 2,
+                    ':' => 4,
+                    _ => 6,
+                };
+                match hex_character(&escape[1..], digits) {
+                    Some(c) => (c, digits + 1),
+                    None => return Err(error("invalid hexadecimal character escape")),
+                }
+            },
+            Some('[') => {
+                let name = escape[1..]
+                    .find(']')
+                    .map(|end| &escape[1..end + 1])
+                    .ok_or_else(|| error("unterminated named character"))?;
+                match named_character(name) {
+                    Some(c) => (c, name.len() + 2),
+                    None => return Err(error("unknown named character")),
+                }
+            },
+            Some(other) => {
+                string.push('\\');
+                (other, other.len_utf8())
+            },
+            None => return Err(error("incomplete escape sequence")),
+        };
+
+        string.push(c);
+        rest = &escape[len..];
+    }
+
+    string.push_str(rest);
+    Ok(string)
+}
+
+/// Parse exactly `digits` hexadecimal digits at the start of `input` as a character.
+fn hex_character(input: &str, digits: usize) -> Option<char> {
+    let hex = input.get(..digits)?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    std::char::from_u32(u32::from_str_radix(hex, 16).ok()?)
+}
+
+//======================================
+// Named characters
+//======================================
+
+/// A subset of the WL named characters, written as `\[Name]`.
+///
+/// Note that `\[Epsilon]` and `\[Phi]` are the symbol forms `ϵ` and `ϕ`; the more
+/// common letter forms `ε` and `φ` are `\[CurlyEpsilon]` and `\[CurlyPhi]`.
+const NAMED_CHARACTERS: &[(&str, char)] = &[
+    ("Alpha", 'α'),
+    ("Beta", 'β'),
+    ("Gamma", 'γ'),
+    ("Delta", 'δ'),
+    ("Epsilon", 'ϵ'),
+    ("CurlyEpsilon", 'ε'),
+    ("Zeta", 'ζ'),
+    ("Eta", 'η'),
+    ("Theta", 'θ'),
+    ("CurlyTheta", 'ϑ'),
+    ("Iota", 'ι'),
+    ("Kappa", 'κ'),
+    ("Lambda", 'λ'),
+    ("Mu", 'μ'),
+    ("Nu", 'ν'),
+    ("Xi", 'ξ'),
+    ("Omicron", 'ο'),
+    ("Pi", 'π'),
+    ("Rho", 'ρ'),
+    ("FinalSigma", 'ς'),
+    ("Sigma", 'σ'),
+    ("Tau", 'τ'),
+    ("Upsilon", 'υ'),
+    ("Phi", 'ϕ'),
+    ("CurlyPhi", 'φ'),
+    ("Chi", 'χ'),
+    ("Psi", 'ψ'),
+    ("Omega", 'ω'),
+    ("CapitalAlpha", 'Α'),
+    ("CapitalBeta", 'Β'),
+    ("CapitalGamma", 'Γ'),
+    ("CapitalDelta", 'Δ'),
+    ("CapitalEpsilon", 'Ε'),
+    ("CapitalZeta", 'Ζ'),
+    ("CapitalEta", 'Η'),
+    ("CapitalTheta", 'Θ'),
+    ("CapitalIota", 'Ι'),
+    ("CapitalKappa", 'Κ'),
+    ("CapitalLambda", 'Λ'),
+    ("CapitalMu", 'Μ'),
+    ("CapitalNu", 'Ν'),
+    ("CapitalXi", 'Ξ'),
+    ("CapitalOmicron", 'Ο'),
+    ("CapitalPi", 'Π'),
+    ("CapitalRho", 'Ρ'),
+    ("CapitalSigma", 'Σ'),
+    ("CapitalTau", 'Τ'),
+    ("CapitalPhi", 'Φ'),
+    ("CapitalChi", 'Χ'),
+    ("CapitalPsi", 'Ψ'),
+    ("CapitalOmega", 'Ω'),
+    ("Degree", '°'),
+    ("PlusMinus", '±'),
+    ("Times", '×'),
+    ("Divide", '÷'),
+    ("Infinity", '∞'),
+    ("Element", '∈'),
+    ("NotEqual", '≠'),
+    ("LessEqual", '≤'),
+    ("GreaterEqual", '≥'),
+    ("RightArrow", '→'),
+    ("Ellipsis", '…'),
+    ("Bullet", '•'),
+];
+
+fn character_name(c: char) -> Option<&'static str> {
+    NAMED_CHARACTERS
+        .iter()
+        .find(|(_, named)| *named == c)
+        .map(|(name, _)| *name)
+}
+
+fn named_character(name: &str) -> Option<char> {
+    NAMED_CHARACTERS
+        .iter()
+        .find(|(named, _)| *named == name)
+        .map(|(_, c)| *c)
+}
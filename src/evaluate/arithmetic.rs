@@ -0,0 +1,547 @@
+//! Built-in arithmetic and numeric comparison functions.
+//!
+//! Arithmetic on integers and `Rational[n, d]` expressions is exact, and arithmetic
+//! involving a machine real produces a machine real. Exact results which do not fit in
+//! 64-bit integers, and real results which are not finite, are left unevaluated.
+
+use std::cmp::Ordering;
+
+use crate::{
+    evaluate::{as_normal_with_head, BuiltinResult, Evaluator},
+    number::{Numeric, Rational},
+    Expr, ExprKind, Normal, Symbol,
+};
+
+pub(super) fn register(evaluator: &mut Evaluator) {
+    let builtins: &[(&str, super::Builtin)] = &[
+        ("System`Plus", plus),
+        ("System`Times", times),
+        ("System`Power", power),
+        ("System`Subtract", subtract),
+        ("System`Minus", minus),
+        ("System`Divide", divide),
+        ("System`Rational", rational),
+        ("System`Mod", mod_),
+        ("System`Quotient", quotient),
+        ("System`Equal", equal),
+        ("System`Unequal", unequal),
+        ("System`Less", less),
+        ("System`LessEqual", less_equal),
+        ("System`Greater", greater),
+        ("System`GreaterEqual", greater_equal),
+        ("System`Max", max),
+        ("System`Min", min),
+        ("System`D", d),
+    ];
+
+    for (name, builtin) in builtins {
+        evaluator.add_builtin(Symbol::new(name), *builtin);
+    }
+}
+
+//======================================
+// Arithmetic
+//======================================
+
+/// `Plus[x, y, ...]`: `x + y + ...`
+///
+/// Numbers are added together, and terms which differ only in their numeric
+/// coefficient are combined, e.g. `x + 2 x` becomes `3 x`.
+fn plus(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(add(normal.elements()))
+}
+
+/// Simplify `Plus[elements...]`, returning `None` if the sum overflows.
+pub(crate) fn add(elements: &[Expr]) -> Option<Expr> {
+    let mut sum = Numeric::ZERO;
+    // Each distinct term, and its total coefficient.
+    let mut terms: Vec<(Expr, Numeric)> = Vec::new();
+
+    for elem in elements {
+        if let Some(value) = Numeric::from_expr(elem) {
+            sum = sum.checked_add(value)?;
+            continue;
+        }
+
+        let (coeff, term) = split_coefficient(elem);
+        match terms.iter_mut().find(|(existing, _)| *existing == term) {
+            Some((_, total)) => *total = total.checked_add(coeff)?,
+            None => terms.push((term, coeff)),
+        }
+    }
+
+    let mut contents = Vec::with_capacity(terms.len() + 1);
+
+    for (term, coeff) in terms {
+        if coeff == Numeric::ZERO {
+            continue;
+        }
+        if coeff.is_zero() {
+            // An inexact zero coefficient leaves an inexact zero in the sum.
+            sum = sum.checked_add(coeff)?;
+            continue;
+        }
+        contents.push(with_coefficient(coeff, term));
+    }
+
+    if sum != Numeric::ZERO || contents.is_empty() {
+        contents.insert(0, sum.to_expr());
+    }
+
+    Some(collapse(symbol("System`Plus"), contents))
+}
+
+/// `Times[x, y, ...]`: `x * y * ...`
+///
+/// Numbers are multiplied together, and factors with the same base are combined, e.g.
+/// `x * x^2` becomes `x^3`.
+fn times(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(multiply(normal.elements()))
+}
+
+/// Simplify `Times[elements...]`, returning `None` if the product overflows.
+pub(crate) fn multiply(elements: &[Expr]) -> Option<Expr> {
+    let mut product = Numeric::ONE;
+    // Each distinct base, and the exponents it appears with.
+    let mut factors: Vec<(Expr, Vec<Expr>)> = Vec::new();
+
+    for elem in elements {
+        if let Some(value) = Numeric::from_expr(elem) {
+            product = product.checked_mul(value)?;
+            continue;
+        }
+
+        let (base, exp) = split_exponent(elem);
+        match factors.iter_mut().find(|(existing, _)| *existing == base) {
+            Some((_, exps)) => exps.push(exp),
+            None => factors.push((base, vec![exp])),
+        }
+    }
+
+    // 0 * x == 0
+    if product.is_zero() {
+        return Some(product.to_expr());
+    }
+
+    let mut contents = Vec::with_capacity(factors.len() + 1);
+
+    if !product.is_one() || factors.is_empty() {
+        contents.push(product.to_expr());
+    }
+
+    for (base, mut exps) in factors {
+        let exp = match exps.len() {
+            1 => exps.pop().unwrap(),
+            _ => Expr::normal(symbol("System`Plus"), exps),
+        };
+        contents.push(match Numeric::from_expr(&exp) {
+            Some(exp) if exp.is_one() => base,
+            _ => Expr::normal(symbol("System`Power"), vec![base, exp]),
+        });
+    }
+
+    Some(collapse(symbol("System`Times"), contents))
+}
+
+/// `Power[x, y]`: `x^y`
+fn power(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [base, exp] => raise(base, exp),
+        _ => None,
+    })
+}
+
+/// Simplify `base^exp`, returning `None` if no simplification applies.
+pub(crate) fn raise(base: &Expr, exp: &Expr) -> Option<Expr> {
+    match (Numeric::from_expr(base), Numeric::from_expr(exp)) {
+        (Some(base), Some(exp)) => {
+            if base.is_zero() && exp.is_zero() {
+                Some(symbol("System`Indeterminate"))
+            } else if base.is_zero() && exp.is_negative() {
+                Some(symbol("System`ComplexInfinity"))
+            } else {
+                base.checked_pow(exp).map(Numeric::to_expr)
+            }
+        },
+        // x^0 == 1
+        (_, Some(exp)) if exp.is_zero() => Some(match exp.is_real() {
+            true => Expr::real(1.0),
+            false => Expr::from(1),
+        }),
+        // x^1 == x
+        (_, Some(exp)) if exp.is_one() => Some(base.clone()),
+        // 1^x == 1
+        (Some(base), _) if base.is_one() => Some(Expr::from(1)),
+        // (x^a)^n == x^(a n) for integer n
+        (None, Some(exp)) if exp.as_integer().is_some() => {
+            match as_normal_with_head(base, "System`Power").map(Normal::elements) {
+                Some([inner, inner_exp]) => {
+                    let exp = Expr::normal(symbol("System`Times"), vec![
+                        inner_exp.clone(),
+                        exp.to_expr(),
+                    ]);
+                    Some(Expr::normal(symbol("System`Power"), vec![inner.clone(), exp]))
+                },
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// `Subtract[x, y]`: `x - y`, which is `x + (-1 * y)`.
+fn subtract(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [x, y] => Some(Expr::normal(symbol("System`Plus"), vec![
+            x.clone(),
+            negate(y),
+        ])),
+        _ => None,
+    })
+}
+
+/// `Minus[x]`: `-x`, which is `-1 * x`.
+fn minus(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [x] => Some(negate(x)),
+        _ => None,
+    })
+}
+
+/// `Divide[x, y]`: `x / y`, which is `x * y^-1`.
+fn divide(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [x, y] => Some(Expr::normal(symbol("System`Times"), vec![
+            x.clone(),
+            Expr::normal(symbol("System`Power"), vec![y.clone(), Expr::from(-1)]),
+        ])),
+        _ => None,
+    })
+}
+
+/// `Rational[n, d]`, which is reduced to lowest terms.
+fn rational(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (numer, denom) = match normal.elements() {
+        [numer, denom] => match (numer.kind(), denom.kind()) {
+            (ExprKind::Integer(numer), ExprKind::Integer(denom)) => (*numer, *denom),
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    Ok(match (numer, denom) {
+        (0, 0) => Some(symbol("System`Indeterminate")),
+        (_, 0) => Some(symbol("System`ComplexInfinity")),
+        _ => Rational::new(numer, denom).map(|value| Numeric::Exact(value).to_expr()),
+    })
+}
+
+/// `Mod[m, n]`: the remainder on dividing `m` by `n`, with the same sign as `n`.
+fn mod_(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (m, n) = match numeric_pair(normal) {
+        Some(pair) => pair,
+        None => return Ok(None),
+    };
+
+    if n.is_zero() {
+        return Ok(Some(symbol("System`Indeterminate")));
+    }
+
+    // m - n * Floor[m / n]
+    let result = floor_div(m, n)
+        .and_then(|quotient| n.checked_mul(Numeric::integer(quotient)))
+        .and_then(|multiple| m.checked_sub(multiple));
+
+    Ok(result.map(Numeric::to_expr))
+}
+
+/// `Quotient[m, n]`: the integer quotient of `m` and `n`, `Floor[m / n]`.
+fn quotient(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (m, n) = match numeric_pair(normal) {
+        Some(pair) => pair,
+        None => return Ok(None),
+    };
+
+    if n.is_zero() {
+        return Ok(Some(symbol("System`ComplexInfinity")));
+    }
+
+    Ok(floor_div(m, n).map(Expr::from))
+}
+
+//======================================
+// Comparison
+//======================================
+
+/// `Equal[x, y, ...]`: `x == y == ...`
+///
+/// Numbers are compared by value, and machine reals are considered equal if they
+/// differ only in their last 7 binary digits.
+fn equal(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let mut unknown = false;
+
+    for pair in normal.elements().windows(2) {
+        match equal_pair(&pair[0], &pair[1]) {
+            Some(true) => (),
+            Some(false) => return Ok(Some(Expr::from(false))),
+            None => unknown = true,
+        }
+    }
+
+    Ok(match unknown {
+        true => None,
+        false => Some(Expr::from(true)),
+    })
+}
+
+/// `Unequal[x, y, ...]`: `x != y != ...`, which is `True` if no two elements are
+/// equal.
+fn unequal(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let elements = normal.elements();
+    let mut unknown = false;
+
+    for (index, x) in elements.iter().enumerate() {
+        for y in &elements[index + 1..] {
+            match equal_pair(x, y) {
+                Some(true) => return Ok(Some(Expr::from(false))),
+                Some(false) => (),
+                None => unknown = true,
+            }
+        }
+    }
+
+    Ok(match unknown {
+        true => None,
+        false => Some(Expr::from(true)),
+    })
+}
+
+/// `Less[x, y, ...]`: `x < y < ...`
+fn less(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(compare(normal, |ordering| ordering == Ordering::Less))
+}
+
+/// `LessEqual[x, y, ...]`: `x <= y <= ...`
+fn less_equal(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(compare(normal, |ordering| ordering != Ordering::Greater))
+}
+
+/// `Greater[x, y, ...]`: `x > y > ...`
+fn greater(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(compare(normal, |ordering| ordering == Ordering::Greater))
+}
+
+/// `GreaterEqual[x, y, ...]`: `x >= y >= ...`
+fn greater_equal(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(compare(normal, |ordering| ordering != Ordering::Less))
+}
+
+/// Returns `True` if each adjacent pair of elements of `normal` is ordered as required
+/// by `accept`. Returns `None` if any element is not a number.
+fn compare(normal: &Normal, accept: fn(Ordering) -> bool) -> Option<Expr> {
+    let elements = normal.elements();
+
+    if elements.len() < 2 {
+        return Some(Expr::from(true));
+    }
+
+    let numbers = elements
+        .iter()
+        .map(Numeric::from_expr)
+        .collect::<Option<Vec<Numeric>>>()?;
+
+    let result = numbers
+        .windows(2)
+        .all(|pair| pair[0].partial_cmp(&pair[1]).is_some_and(accept));
+
+    Some(Expr::from(result))
+}
+
+/// `Max[x, y, ...]`
+fn max(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(extremum(normal, Ordering::Greater, -1))
+}
+
+/// `Min[x, y, ...]`
+fn min(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(extremum(normal, Ordering::Less, 1))
+}
+
+/// Find the largest (if `keep` is `Greater`) or smallest number among the elements of
+/// `normal`, and of any lists in its elements.
+///
+/// Non-numeric elements are kept. If there are no elements, the result is
+/// `DirectedInfinity[empty]`.
+fn extremum(normal: &Normal, keep: Ordering, empty: i64) -> Option<Expr> {
+    let mut best: Option<(Numeric, &Expr)> = None;
+    let mut others = Vec::new();
+
+    let mut stack: Vec<&Expr> = normal.elements().iter().rev().collect();
+
+    while let Some(elem) = stack.pop() {
+        if let Some(list) = as_normal_with_head(elem, "System`List") {
+            stack.extend(list.elements().iter().rev());
+            continue;
+        }
+
+        match Numeric::from_expr(elem) {
+            Some(value) => match best {
+                Some((current, _)) if value.partial_cmp(&current) != Some(keep) => (),
+                _ => best = Some((value, elem)),
+            },
+            None => others.push(elem.clone()),
+        }
+    }
+
+    if best.is_none() && others.is_empty() {
+        return Some(Expr::normal(symbol("System`DirectedInfinity"), vec![Expr::from(
+            empty,
+        )]));
+    }
+
+    let mut contents = Vec::with_capacity(others.len() + 1);
+    contents.extend(best.map(|(_, elem)| elem.clone()));
+    contents.extend(others);
+
+    Some(collapse(normal.head.clone(), contents))
+}
+
+//======================================
+// Calculus
+//======================================
+
+/// `D[f, x]`, `D[f, {x, n}]` or `D[f, x, y, ...]`: the partial derivative of `f`.
+///
+/// See [`Expr::derivative()`].
+fn d(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (expr, variables) = match normal.elements().split_first() {
+        Some(split) => split,
+        None => return Ok(None),
+    };
+
+    let mut result = expr.clone();
+
+    for variable in variables {
+        let (x, count) = match derivative_spec(variable) {
+            Some(spec) => spec,
+            None => return Ok(None),
+        };
+
+        for _ in 0..count {
+            result = result.derivative(x);
+        }
+    }
+
+    Ok(Some(result))
+}
+
+/// Parse the variable `x` or `{x, n}` of `D`.
+fn derivative_spec(variable: &Expr) -> Option<(&Symbol, i64)> {
+    if let ExprKind::Symbol(x) = variable.kind() {
+        return Some((x, 1));
+    }
+
+    match as_normal_with_head(variable, "System`List")?.elements() {
+        [x, count] => match (x.kind(), count.kind()) {
+            (ExprKind::Symbol(x), ExprKind::Integer(count)) if *count >= 0 => {
+                Some((x, *count))
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+//======================================
+// Helpers
+//======================================
+
+fn symbol(name: &str) -> Expr {
+    Expr::symbol(Symbol::new(name))
+}
+
+/// Construct `head[contents...]`, or just the element if there is only one.
+fn collapse(head: Expr, mut contents: Vec<Expr>) -> Expr {
+    match contents.len() {
+        1 => contents.pop().unwrap(),
+        _ => Expr::normal(head, contents),
+    }
+}
+
+/// Split a term of a sum into its numeric coefficient and the remaining factors, e.g.
+/// `2 x y` into `(2, x y)`.
+fn split_coefficient(term: &Expr) -> (Numeric, Expr) {
+    if let Some(product) = as_normal_with_head(term, "System`Times") {
+        if let Some((first, rest)) = product.elements().split_first() {
+            if let Some(coeff) = Numeric::from_expr(first) {
+                let rest = collapse(product.head.clone(), rest.to_vec());
+                return (coeff, rest);
+            }
+        }
+    }
+
+    (Numeric::ONE, term.clone())
+}
+
+/// The inverse of [`split_coefficient()`].
+fn with_coefficient(coeff: Numeric, term: Expr) -> Expr {
+    if coeff.is_one() {
+        return term;
+    }
+
+    let mut contents = vec![coeff.to_expr()];
+    match as_normal_with_head(&term, "System`Times") {
+        Some(product) => contents.extend(product.elements().iter().cloned()),
+        None => contents.push(term),
+    }
+
+    Expr::normal(symbol("System`Times"), contents)
+}
+
+/// Split a factor of a product into its base and exponent, e.g. `x^2` into `(x, 2)`.
+fn split_exponent(factor: &Expr) -> (Expr, Expr) {
+    match as_normal_with_head(factor, "System`Power").map(Normal::elements) {
+        Some([base, exp]) => (base.clone(), exp.clone()),
+        _ => (factor.clone(), Expr::from(1)),
+    }
+}
+
+/// `-1 * expr`
+fn negate(expr: &Expr) -> Expr {
+    Expr::normal(symbol("System`Times"), vec![Expr::from(-1), expr.clone()])
+}
+
+fn numeric_pair(normal: &Normal) -> Option<(Numeric, Numeric)> {
+    match normal.elements() {
+        [m, n] => Some((Numeric::from_expr(m)?, Numeric::from_expr(n)?)),
+        _ => None,
+    }
+}
+
+/// `Floor[m / n]`
+fn floor_div(m: Numeric, n: Numeric) -> Option<i64> {
+    m.checked_div(n)?.floor()
+}
+
+/// Returns `Some(true)` if `x` and `y` are known to be equal, `Some(false)` if they are
+/// known to be unequal, and `None` if it cannot be determined.
+fn equal_pair(x: &Expr, y: &Expr) -> Option<bool> {
+    if x == y {
+        return Some(true);
+    }
+
+    match (Numeric::from_expr(x), Numeric::from_expr(y)) {
+        (Some(Numeric::Exact(x)), Some(Numeric::Exact(y))) => return Some(x == y),
+        (Some(x), Some(y)) => {
+            let (x, y) = (x.to_f64(), y.to_f64());
+            let tolerance = x.abs().max(y.abs()) * f64::powi(2.0, -46);
+            return Some((x - y).abs() <= tolerance);
+        },
+        _ => (),
+    }
+
+    match (x.kind(), y.kind()) {
+        (ExprKind::String(_), ExprKind::String(_)) => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,426 @@
+//! Built-in assignment, control flow, scoping and logical functions.
+
+use crate::{
+    evaluate::{
+        as_normal_with_head, contains_symbol, is_symbol, substitute, BuiltinResult,
+        EvalError, Evaluator,
+    },
+    Expr, ExprKind, Normal, Symbol,
+};
+
+pub(super) fn register(evaluator: &mut Evaluator) {
+    let builtins: &[(&str, super::Builtin)] = &[
+        ("System`Set", set),
+        ("System`SetDelayed", set_delayed),
+        ("System`Clear", clear),
+        ("System`CompoundExpression", compound_expression),
+        ("System`If", if_),
+        ("System`Which", which),
+        ("System`Module", module),
+        ("System`Block", block),
+        ("System`With", with),
+        ("System`SameQ", same_q),
+        ("System`UnsameQ", unsame_q),
+        ("System`TrueQ", true_q),
+        ("System`Not", not),
+        ("System`And", and),
+        ("System`Or", or),
+    ];
+
+    for (name, builtin) in builtins {
+        evaluator.add_builtin(Symbol::new(name), *builtin);
+    }
+}
+
+//======================================
+// Assignment
+//======================================
+
+/// `Set[lhs, rhs]`: `lhs = rhs`
+fn set(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    match normal.elements() {
+        [lhs, rhs] => Ok(assign(evaluator, lhs, rhs)?.then(|| rhs.clone())),
+        _ => Ok(None),
+    }
+}
+
+/// `SetDelayed[lhs, rhs]`: `lhs := rhs`
+fn set_delayed(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    match normal.elements() {
+        [lhs, rhs] => Ok(assign(evaluator, lhs, rhs)?.then(Expr::null)),
+        _ => Ok(None),
+    }
+}
+
+/// Store the definition `lhs -> rhs`. Returns `false` if `lhs` cannot be assigned to.
+fn assign(evaluator: &mut Evaluator, lhs: &Expr, rhs: &Expr) -> Result<bool, EvalError> {
+    let normal = match lhs.kind() {
+        ExprKind::Symbol(symbol) => {
+            evaluator
+                .definitions
+                .set_own_value(symbol.clone(), rhs.clone());
+            return Ok(true);
+        },
+        ExprKind::Normal(normal) => normal,
+        _ => return Ok(false),
+    };
+
+    // {a, b} = {1, 2}
+    if is_symbol(&normal.head, "System`List") {
+        let values = match as_normal_with_head(rhs, "System`List") {
+            Some(values) if values.contents.len() == normal.contents.len() => values,
+            _ => return Ok(false),
+        };
+        for (lhs, rhs) in normal.contents.iter().zip(&values.contents) {
+            if !assign(evaluator, lhs, rhs)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+
+    // The elements of the left-hand side are evaluated, but not the head.
+    let (pattern, normal) = match as_normal_with_head(lhs, "System`HoldPattern") {
+        Some(held) => match held.elements() {
+            [pattern] => match pattern.try_as_normal() {
+                Some(normal) => (pattern.clone(), normal),
+                None => return Ok(false),
+            },
+            _ => return Ok(false),
+        },
+        None => {
+            let contents = normal
+                .contents
+                .iter()
+                .map(|elem| evaluator.evaluate(elem))
+                .collect::<Result<Vec<Expr>, EvalError>>()?;
+            let pattern = Expr::normal(normal.head.clone(), contents);
+            (pattern, normal)
+        },
+    };
+
+    let tag = match normal.head.kind() {
+        ExprKind::Symbol(tag) => tag.clone(),
+        _ => return Ok(false),
+    };
+
+    evaluator
+        .definitions
+        .add_down_value(tag, pattern, rhs.clone());
+
+    Ok(true)
+}
+
+/// `Clear[symbol, ...]`
+fn clear(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    for elem in normal.elements() {
+        match elem.try_as_symbol() {
+            Some(symbol) => evaluator.definitions.clear(symbol),
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(Expr::null()))
+}
+
+//======================================
+// Control flow
+//======================================
+
+/// `CompoundExpression[expr1, expr2, ...]`: `expr1; expr2; ...`
+fn compound_expression(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (last, init) = match normal.elements().split_last() {
+        Some(split) => split,
+        None => return Ok(Some(Expr::null())),
+    };
+
+    for expr in init {
+        evaluator.evaluate(expr)?;
+    }
+
+    Ok(Some(last.clone()))
+}
+
+/// `If[condition, then, else, otherwise]`
+fn if_(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (condition, branches) = match normal.elements().split_first() {
+        Some(split) if (1..=3).contains(&split.1.len()) => split,
+        _ => return Ok(None),
+    };
+
+    let index = match condition.try_as_bool() {
+        Some(true) => 0,
+        Some(false) => 1,
+        None => 2,
+    };
+
+    Ok(match branches.get(index) {
+        Some(branch) => Some(branch.clone()),
+        // If[False, x] evaluates to Null, but If[cond, x, y] with a non-boolean `cond`
+        // stays unevaluated.
+        None if index == 1 => Some(Expr::null()),
+        None => None,
+    })
+}
+
+/// `Which[test1, value1, test2, value2, ...]`
+fn which(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let elements = normal.elements();
+
+    if elements.len() % 2 != 0 {
+        return Ok(None);
+    }
+
+    for (index, pair) in elements.chunks(2).enumerate() {
+        let test = evaluator.evaluate(&pair[0])?;
+        match test.try_as_bool() {
+            Some(true) => return Ok(Some(pair[1].clone())),
+            Some(false) => continue,
+            None => {
+                // Leave the remaining cases unevaluated, starting with the evaluated
+                // test.
+                let mut contents = vec![test, pair[1].clone()];
+                contents.extend(elements[2 * index + 2..].iter().cloned());
+                return Ok(Some(Expr::normal(normal.head.clone(), contents)));
+            },
+        }
+    }
+
+    Ok(Some(Expr::null()))
+}
+
+//======================================
+// Scoping
+//======================================
+
+/// A local variable specification in `Module`, `Block` or `With`: `x` or `x = value`.
+struct LocalVariable<'e> {
+    symbol: &'e Symbol,
+    value: Option<&'e Expr>,
+}
+
+/// Parse the `{x, y = value, ...}` variable list of a scoping construct.
+fn local_variables(spec: &Expr) -> Option<Vec<LocalVariable<'_>>> {
+    as_normal_with_head(spec, "System`List")?
+        .elements()
+        .iter()
+        .map(|var| match var.kind() {
+            ExprKind::Symbol(symbol) => Some(LocalVariable {
+                symbol,
+                value: None,
+            }),
+            _ => {
+                let (symbol, value) = match as_normal_with_head(var, "System`Set")
+                    .or_else(|| as_normal_with_head(var, "System`SetDelayed"))?
+                    .elements()
+                {
+                    [symbol, value] => (symbol.try_as_symbol()?, value),
+                    _ => return None,
+                };
+                Some(LocalVariable {
+                    symbol,
+                    value: Some(value),
+                })
+            },
+        })
+        .collect()
+}
+
+/// `Module[{x, y = value, ...}, body]`
+///
+/// Each local variable is renamed to a unique symbol `x$n`. Like the `Temporary`
+/// symbols created by WL, the definitions of these symbols are cleared when `body` has
+/// been evaluated, unless the symbol appears in the result.
+fn module(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (vars, body) = match normal.elements() {
+        [vars, body] => match local_variables(vars) {
+            Some(vars) => (vars, body),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let number = evaluator.module_number;
+    evaluator.module_number += 1;
+
+    let mut bindings = Vec::with_capacity(vars.len());
+    let mut temporaries = Vec::with_capacity(vars.len());
+
+    for LocalVariable { symbol, value } in vars {
+        let unique = Symbol::new(&format!("{}${}", symbol, number));
+
+        if let Some(value) = value {
+            let value = match evaluator.evaluate(value) {
+                Ok(value) => value,
+                Err(err) => {
+                    clear_temporaries(evaluator, &temporaries, None);
+                    return Err(err);
+                },
+            };
+            evaluator.definitions.set_own_value(unique.clone(), value);
+        }
+
+        temporaries.push(unique.clone());
+        bindings.push((symbol.clone(), Expr::symbol(unique)));
+    }
+
+    let result = evaluator.evaluate(&substitute(body, &bindings));
+
+    clear_temporaries(evaluator, &temporaries, result.as_ref().ok());
+
+    result.map(Some)
+}
+
+/// Clear the definitions of the local symbols of a `Module` which do not escape in its
+/// `result`.
+fn clear_temporaries(
+    evaluator: &mut Evaluator,
+    symbols: &[Symbol],
+    result: Option<&Expr>,
+) {
+    for symbol in symbols {
+        if !result.is_some_and(|result| contains_symbol(result, symbol)) {
+            evaluator.definitions.clear(symbol);
+        }
+    }
+}
+
+/// `Block[{x, y = value, ...}, body]`
+///
+/// The values of the local variables are temporarily replaced while `body` is evaluated.
+fn block(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (vars, body) = match normal.elements() {
+        [vars, body] => match local_variables(vars) {
+            Some(vars) => (vars, body),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    // Evaluate the initial values before any variable is localized.
+    let mut values = Vec::with_capacity(vars.len());
+    for var in &vars {
+        values.push(match var.value {
+            Some(value) => Some(evaluator.evaluate(value)?),
+            None => None,
+        });
+    }
+
+    let mut saved = Vec::with_capacity(vars.len());
+    for (var, value) in vars.iter().zip(values) {
+        saved.push((var.symbol, evaluator.definitions.take_own_value(var.symbol)));
+        if let Some(value) = value {
+            evaluator.definitions.set_own_value(var.symbol.clone(), value);
+        }
+    }
+
+    let result = evaluator.evaluate(body);
+
+    for (symbol, value) in saved {
+        match value {
+            Some(value) => evaluator.definitions.set_own_value(symbol.clone(), value),
+            None => {
+                evaluator.definitions.take_own_value(symbol);
+            },
+        }
+    }
+
+    result.map(Some)
+}
+
+/// `With[{x = value, ...}, body]`
+///
+/// The values are substituted directly into `body`.
+fn with(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (vars, body) = match normal.elements() {
+        [vars, body] => match local_variables(vars) {
+            Some(vars) => (vars, body),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let mut bindings = Vec::with_capacity(vars.len());
+    for LocalVariable { symbol, value } in vars {
+        let value = match value {
+            Some(value) => evaluator.evaluate(value)?,
+            None => return Ok(None),
+        };
+        bindings.push((symbol.clone(), value));
+    }
+
+    Ok(Some(substitute(body, &bindings)))
+}
+
+//======================================
+// Logic
+//======================================
+
+/// `SameQ[expr1, expr2, ...]`: `expr1 === expr2 === ...`
+fn same_q(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let elements = normal.elements();
+    let same = elements.windows(2).all(|pair| pair[0] == pair[1]);
+    Ok(Some(Expr::from(same)))
+}
+
+/// `UnsameQ[expr1, expr2, ...]`: `expr1 =!= expr2 =!= ...`
+fn unsame_q(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let elements = normal.elements();
+    let unsame = elements
+        .iter()
+        .enumerate()
+        .all(|(index, elem)| !elements[index + 1..].contains(elem));
+    Ok(Some(Expr::from(unsame)))
+}
+
+/// `TrueQ[expr]`
+fn true_q(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    match normal.elements() {
+        [expr] => Ok(Some(Expr::from(expr.try_as_bool() == Some(true)))),
+        _ => Ok(None),
+    }
+}
+
+/// `Not[expr]`: `!expr`
+fn not(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    match normal.elements() {
+        [expr] => Ok(expr.try_as_bool().map(|value| Expr::from(!value))),
+        _ => Ok(None),
+    }
+}
+
+/// `And[expr1, expr2, ...]`: `expr1 && expr2 && ...`
+fn and(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    short_circuit(evaluator, normal, false)
+}
+
+/// `Or[expr1, expr2, ...]`: `expr1 || expr2 || ...`
+fn or(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    short_circuit(evaluator, normal, true)
+}
+
+/// Evaluate the elements of `And` or `Or` from left to right, stopping at the first
+/// element equal to `stop`. Elements equal to `!stop` are dropped, and other elements
+/// are left in place.
+fn short_circuit(
+    evaluator: &mut Evaluator,
+    normal: &Normal,
+    stop: bool,
+) -> BuiltinResult {
+    let mut remaining = Vec::new();
+
+    for elem in normal.elements() {
+        let value = evaluator.evaluate(elem)?;
+        match value.try_as_bool() {
+            Some(value) if value == stop => return Ok(Some(Expr::from(stop))),
+            Some(_) => (),
+            None => remaining.push(value),
+        }
+    }
+
+    Ok(Some(match remaining.len() {
+        0 => Expr::from(!stop),
+        1 => remaining.pop().unwrap(),
+        _ => Expr::normal(normal.head.clone(), remaining),
+    }))
+}
@@ -0,0 +1,466 @@
+//! Application of pure functions: `Function[body][args...]`.
+
+use std::fmt;
+
+use crate::{
+    association::lookup,
+    attributes::{Attribute, Attributes},
+    evaluate::{as_normal_with_head, contains_symbol, is_symbol, thread_listable},
+    system, Expr, ExprKind, Normal, Symbol,
+};
+
+/// Error returned by [`Expr::apply_function()`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionError {
+    /// `expr` is not a well-formed `Function[body]`, `Function[params, body]` or
+    /// `Function[params, body, attrs]` expression.
+    #[allow(missing_docs)]
+    NotFunction { expr: Expr },
+    /// The function has `expected` named parameters, but was applied to only `given`
+    /// arguments.
+    #[allow(missing_docs)]
+    TooFewArguments { expected: usize, given: usize },
+    /// The function is [`Listable`][Attribute::Listable], and was applied to lists of
+    /// different lengths.
+    ListLengthMismatch,
+}
+
+impl Expr {
+    /// Apply this pure function to `args`, as in
+    /// [`Function`](https://reference.wolfram.com/language/ref/Function.html)
+    /// <sub>WL</sub>, returning the body of the function with the arguments
+    /// substituted in.
+    ///
+    /// * In `Function[body]`, each `#n` (`Slot[n]`) is replaced with the `n`th
+    ///   argument, each `##n` (`SlotSequence[n]`) with the sequence of arguments from
+    ///   the `n`th onwards, `#0` with the function itself, and `#name` with the value of
+    ///   the key `"name"` in the first argument, if it is an `Association`. Slots in
+    ///   nested `Function[body]` expressions belong to the inner function.
+    /// * In `Function[x, body]` or `Function[{x, y, ...}, body]`, each free occurrence
+    ///   of a parameter in `body` is replaced with the corresponding argument.
+    ///   Parameters of nested `Function`, `Module` and `With` expressions shadow those
+    ///   of the outer function, and are renamed to `x$` if they would otherwise capture
+    ///   a symbol in one of the arguments.
+    ///
+    /// The result is not evaluated.
+    ///
+    /// In `Function[params, body, attrs]`, the attributes
+    /// [`Listable`][Attribute::Listable], which threads the function over lists in
+    /// `args`, and [`Orderless`][Attribute::Orderless], which sorts `args`, are applied.
+    /// Other attributes only affect evaluation. `Function[Null, body, attrs]` uses
+    /// slots.
+    ///
+    /// # Example
+    ///
+    /// Applying `Function[{x}, Function[{y}, x + y]]` to `y` renames the inner
+    /// parameter:
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, Symbol};
+    ///
+    /// let call = |head: &str, elems| Expr::function(Symbol::new(head), elems);
+    /// let sym = |name: &str| Expr::symbol(Symbol::new(name));
+    /// let (x, y) = (sym("Global`x"), sym("Global`y"));
+    ///
+    /// let function = call("System`Function", vec![
+    ///     Expr::list(vec![x.clone()]),
+    ///     call("System`Function", vec![
+    ///         Expr::list(vec![y.clone()]),
+    ///         call("System`Plus", vec![x, y.clone()]),
+    ///     ]),
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     function.apply_function(&[y.clone()]),
+    ///     Ok(call("System`Function", vec![
+    ///         Expr::list(vec![sym("Global`y$")]),
+    ///         call("System`Plus", vec![y, sym("Global`y$")]),
+    ///     ]))
+    /// );
+    /// ```
+    pub fn apply_function(&self, args: &[Expr]) -> Result<Expr, FunctionError> {
+        let not_function = || FunctionError::NotFunction { expr: self.clone() };
+
+        let function =
+            as_normal_with_head(self, "System`Function").ok_or_else(not_function)?;
+        let attrs = function_attributes(function).ok_or_else(not_function)?;
+
+        if attrs.contains(Attribute::Listable)
+            && args.iter().any(|arg| as_normal_with_head(arg, "System`List").is_some())
+        {
+            let threaded = thread_listable(&Normal::new(self.clone(), args.to_vec()))
+                .ok_or(FunctionError::ListLengthMismatch)?;
+
+            let results = threaded
+                .try_as_normal()
+                .unwrap()
+                .elements()
+                .iter()
+                .map(|call| self.apply_function(call.try_as_normal().unwrap().elements()))
+                .collect::<Result<_, _>>()?;
+
+            return Ok(Expr::list(results));
+        }
+
+        let mut sorted;
+        let args = match attrs.contains(Attribute::Orderless) {
+            true => {
+                sorted = args.to_vec();
+                sorted.sort();
+                &sorted
+            },
+            false => args,
+        };
+
+        match function.elements() {
+            [body] => Ok(replace_slots(body, self, args)),
+            [params, body, _] if is_symbol(params, "System`Null") => {
+                Ok(replace_slots(body, self, args))
+            },
+            [params, body] | [params, body, _] => {
+                let params = parameters(params).ok_or_else(not_function)?;
+
+                if params.len() > args.len() {
+                    return Err(FunctionError::TooFewArguments {
+                        expected: params.len(),
+                        given: args.len(),
+                    });
+                }
+
+                let bindings: Vec<(Symbol, Expr)> =
+                    params.into_iter().zip(args.iter().cloned()).collect();
+
+                Ok(substitute_hygienic(body, &bindings))
+            },
+            _ => Err(not_function()),
+        }
+    }
+}
+
+/// The attributes given as the third element of `Function[params, body, attrs]`.
+///
+/// Returns `None` if `attrs` is not an attribute or list of attributes.
+pub(super) fn function_attributes(function: &Normal) -> Option<Attributes> {
+    let attrs = match function.elements() {
+        [_, _, attrs] => attrs,
+        _ => return Some(Attributes::empty()),
+    };
+
+    let attribute = |attr: &Expr| Attribute::from_symbol(attr.try_as_symbol()?);
+
+    match as_normal_with_head(attrs, "System`List") {
+        Some(list) => list.elements().iter().map(attribute).collect(),
+        None => attribute(attrs).map(Attributes::from),
+    }
+}
+
+/// Parse the `x` or `{x, y, ...}` parameters of a `Function`.
+fn parameters(params: &Expr) -> Option<Vec<Symbol>> {
+    match params.kind() {
+        ExprKind::Symbol(param) => Some(vec![param.clone()]),
+        _ => as_normal_with_head(params, "System`List")?
+            .elements()
+            .iter()
+            .map(|param| param.try_as_symbol().cloned())
+            .collect(),
+    }
+}
+
+//======================================
+// Slots
+//======================================
+
+/// Replace `#n` and `##n` in `body` with the corresponding `args`, and `#0` with
+/// `function`.
+///
+/// Slots inside nested `Function[body]` expressions belong to that function, and are
+/// left unchanged.
+fn replace_slots(body: &Expr, function: &Expr, args: &[Expr]) -> Expr {
+    let normal = match body.kind() {
+        ExprKind::Normal(normal) => normal,
+        ExprKind::Association(assoc) => {
+            return Expr::from(assoc.map_rules(|elem| replace_slots(elem, function, args)))
+        },
+        _ => return body.clone(),
+    };
+
+    if let Some(slot) = as_normal_with_head(body, "System`Slot") {
+        return match slot.elements() {
+            [key] if key.try_as_str().is_some() => match args.first() {
+                Some(assoc) => lookup(assoc, key).unwrap_or(body).clone(),
+                None => body.clone(),
+            },
+            _ => match slot_index(slot) {
+                Some(0) => function.clone(),
+                Some(index) if index <= args.len() => args[index - 1].clone(),
+                _ => body.clone(),
+            },
+        };
+    }
+
+    // A body which is exactly `##n` is replaced by `Sequence[args...]`.
+    if let Some(slot) = as_normal_with_head(body, "System`SlotSequence") {
+        return match slot_index(slot) {
+            Some(index) if index >= 1 && index <= args.len() + 1 => {
+                Expr::normal(system::Sequence.into(), args[index - 1..].to_vec())
+            },
+            _ => body.clone(),
+        };
+    }
+
+    if let Some(inner) = as_normal_with_head(body, "System`Function") {
+        match inner.elements() {
+            [_] => return body.clone(),
+            [params, _, _] if is_symbol(params, "System`Null") => return body.clone(),
+            _ => (),
+        }
+    }
+
+    // Rename the parameters of nested named functions and other scoping constructs
+    // which would capture a symbol in `args`.
+    let renamed;
+    let normal = match scoped_symbols(normal) {
+        Some(locals) => {
+            renamed = avoid_capture(normal, &locals, args.iter());
+            &renamed
+        },
+        None => normal,
+    };
+
+    let head = replace_slots(&normal.head, function, args);
+
+    let mut contents = Vec::with_capacity(normal.contents.len());
+    for elem in &normal.contents {
+        match as_normal_with_head(elem, "System`SlotSequence") {
+            Some(slot) => match slot_index(slot) {
+                Some(index) if index >= 1 && index <= args.len() + 1 => {
+                    contents.extend(args[index - 1..].iter().cloned())
+                },
+                _ => contents.push(elem.clone()),
+            },
+            None => contents.push(replace_slots(elem, function, args)),
+        }
+    }
+
+    Expr::normal(head, contents)
+}
+
+/// Get the index of `Slot[n]` or `SlotSequence[n]`. `#` and `##` are equivalent to
+/// `#1` and `##1`.
+fn slot_index(slot: &Normal) -> Option<usize> {
+    match slot.elements() {
+        [] => Some(1),
+        [index] => match index.kind() {
+            ExprKind::Integer(index) if *index >= 0 => Some(*index as usize),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+//======================================
+// Hygienic substitution
+//======================================
+
+/// Replace each free occurrence of a bound symbol in `expr` with its value.
+///
+/// Symbols which are local to a nested `Function`, `Module` or `With` are not free in
+/// its body. Local symbols which also appear in one of the values are renamed, so that
+/// they do not capture the substituted value.
+///
+/// A symbol bound to `Sequence[...]` which appears as an element of a normal expression
+/// is replaced by the elements of the sequence.
+fn substitute_hygienic(expr: &Expr, bindings: &[(Symbol, Expr)]) -> Expr {
+    if bindings.is_empty() {
+        return expr.clone();
+    }
+
+    let lookup = |expr: &Expr| -> Option<&Expr> {
+        let symbol = expr.try_as_symbol()?;
+        bindings
+            .iter()
+            .find(|(name, _)| name == symbol)
+            .map(|(_, value)| value)
+    };
+
+    let normal = match expr.kind() {
+        ExprKind::Normal(normal) => normal,
+        ExprKind::Symbol(_) => return lookup(expr).unwrap_or(expr).clone(),
+        ExprKind::Association(assoc) => {
+            return Expr::from(assoc.map_rules(|elem| substitute_hygienic(elem, bindings)))
+        },
+        _ => return expr.clone(),
+    };
+
+    if let Some(locals) = scoped_symbols(normal) {
+        return substitute_scoped(normal, &locals, bindings);
+    }
+
+    let head = substitute_hygienic(&normal.head, bindings);
+
+    let mut contents = Vec::with_capacity(normal.contents.len());
+    for elem in &normal.contents {
+        match lookup(elem) {
+            Some(value) => match as_normal_with_head(value, "System`Sequence") {
+                Some(seq) => contents.extend(seq.contents.iter().cloned()),
+                None => contents.push(value.clone()),
+            },
+            None => contents.push(substitute_hygienic(elem, bindings)),
+        }
+    }
+
+    Expr::normal(head, contents)
+}
+
+/// Substitute `bindings` into the scoping construct `normal`, whose local symbols are
+/// `locals`.
+fn substitute_scoped(
+    normal: &Normal,
+    locals: &[Symbol],
+    bindings: &[(Symbol, Expr)],
+) -> Expr {
+    let inner: Vec<(Symbol, Expr)> = bindings
+        .iter()
+        .filter(|(symbol, _)| !locals.contains(symbol))
+        .cloned()
+        .collect();
+
+    let normal = avoid_capture(normal, locals, inner.iter().map(|(_, value)| value));
+
+    let mut contents = normal.contents.iter();
+    // Initial values of local variables are in the enclosing scope.
+    let spec = map_spec(
+        contents.next().unwrap(),
+        &|symbol| Expr::from(symbol.clone()),
+        &|value| substitute_hygienic(value, bindings),
+    );
+
+    let mut new_contents = vec![spec];
+    new_contents.extend(contents.map(|elem| substitute_hygienic(elem, &inner)));
+
+    Expr::normal(normal.head.clone(), new_contents)
+}
+
+/// If `normal` is a scoping construct `Function[params, body]`,
+/// `Function[params, body, attrs]`, `Module[{x, y = value, ...}, body]` or
+/// `With[{x = value, ...}, body]`, return its local symbols.
+fn scoped_symbols(normal: &Normal) -> Option<Vec<Symbol>> {
+    let name = normal.head.try_as_symbol()?.as_str();
+
+    let spec = match (name, normal.elements()) {
+        ("System`Function", [params, _]) | ("System`Function", [params, _, _]) => {
+            if is_symbol(params, "System`Null") {
+                return None;
+            }
+            return parameters(params);
+        },
+        ("System`Module", [spec, _]) | ("System`With", [spec, _]) => spec,
+        _ => return None,
+    };
+
+    as_normal_with_head(spec, "System`List")?
+        .elements()
+        .iter()
+        .map(|var| match var.kind() {
+            ExprKind::Symbol(symbol) => Some(symbol.clone()),
+            _ => {
+                let assignment = as_normal_with_head(var, "System`Set")
+                    .or_else(|| as_normal_with_head(var, "System`SetDelayed"))?;
+                match assignment.elements() {
+                    [symbol, _] => symbol.try_as_symbol().cloned(),
+                    _ => None,
+                }
+            },
+        })
+        .collect()
+}
+
+/// Rename each of the `locals` of the scoping construct `normal` which appears in one
+/// of `values` to a fresh symbol `x$`.
+fn avoid_capture<'e>(
+    normal: &Normal,
+    locals: &[Symbol],
+    values: impl Iterator<Item = &'e Expr> + Clone,
+) -> Normal {
+    let renames: Vec<(Symbol, Expr)> = locals
+        .iter()
+        .filter(|local| values.clone().any(|value| contains_symbol(value, local)))
+        .map(|local| {
+            let mut fresh = Symbol::new(&format!("{}$", local));
+            while values.clone().any(|value| contains_symbol(value, &fresh))
+                || normal.contents.iter().any(|elem| contains_symbol(elem, &fresh))
+            {
+                fresh = Symbol::new(&format!("{}$", fresh));
+            }
+            (local.clone(), Expr::from(fresh))
+        })
+        .collect();
+
+    if renames.is_empty() {
+        return normal.clone();
+    }
+
+    let rename = |symbol: &Symbol| {
+        renames
+            .iter()
+            .find(|(local, _)| local == symbol)
+            .map_or_else(|| Expr::from(symbol.clone()), |(_, fresh)| fresh.clone())
+    };
+
+    let mut contents = normal.contents.iter();
+    let spec = map_spec(contents.next().unwrap(), &rename, &Expr::clone);
+
+    let mut new_contents = vec![spec];
+    new_contents.extend(contents.map(|elem| substitute_hygienic(elem, &renames)));
+
+    Normal::new(normal.head.clone(), new_contents)
+}
+
+/// Rebuild the parameter or local variable specification of a scoping construct,
+/// transforming each local symbol with `symbol` and each initial value with `value`.
+fn map_spec(
+    spec: &Expr,
+    symbol: &dyn Fn(&Symbol) -> Expr,
+    value: &dyn Fn(&Expr) -> Expr,
+) -> Expr {
+    let normal = match spec.kind() {
+        ExprKind::Symbol(local) => return symbol(local),
+        ExprKind::Normal(normal) => normal,
+        _ => return spec.clone(),
+    };
+
+    let is_assignment = is_symbol(&normal.head, "System`Set")
+        || is_symbol(&normal.head, "System`SetDelayed");
+
+    let contents = match (is_assignment, normal.elements()) {
+        (true, [local, init]) => vec![map_spec(local, symbol, value), value(init)],
+        _ => normal
+            .elements()
+            .iter()
+            .map(|elem| map_spec(elem, symbol, value))
+            .collect(),
+    };
+
+    Expr::normal(normal.head.clone(), contents)
+}
+
+impl fmt::Display for FunctionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FunctionError::NotFunction { expr } => {
+                write!(f, "{} is not a valid pure function.", expr)
+            },
+            FunctionError::TooFewArguments { expected, given } => write!(
+                f,
+                "Function with {} parameters applied to {} arguments.",
+                expected, given
+            ),
+            FunctionError::ListLengthMismatch => write!(
+                f,
+                "Listable function applied to lists of unequal length."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FunctionError {}
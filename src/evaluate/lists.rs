@@ -0,0 +1,563 @@
+//! Built-in functions for constructing and manipulating lists.
+
+use std::convert::TryFrom;
+
+use crate::{
+    evaluate::{as_normal_with_head, is_symbol, BuiltinResult, EvalError, Evaluator},
+    number::Numeric,
+    part::span_positions,
+    system, Association, Expr, ExprKind, Normal, PartSpec, Symbol,
+};
+
+pub(super) fn register(evaluator: &mut Evaluator) {
+    let builtins: &[(&str, super::Builtin)] = &[
+        ("System`Length", length),
+        ("System`Part", part),
+        ("System`Take", take),
+        ("System`Drop", drop),
+        ("System`Join", join),
+        ("System`Range", range),
+        ("System`Table", table),
+        ("System`Map", map),
+        ("System`Select", select),
+        ("System`Total", total),
+        ("System`Sort", sort),
+        ("System`Association", association),
+        ("System`Keys", keys),
+        ("System`Values", values),
+        ("System`Lookup", lookup),
+    ];
+
+    for (name, builtin) in builtins {
+        evaluator.add_builtin(Symbol::new(name), *builtin);
+    }
+}
+
+//======================================
+// Parts
+//======================================
+
+/// `Length[expr]`
+///
+/// The length of an atomic expression, including `Rational[n, d]`, is `0`.
+fn length(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let expr = match normal.elements() {
+        [expr] => expr,
+        _ => return Ok(None),
+    };
+
+    let length = match expr.kind() {
+        ExprKind::Normal(_) if Numeric::from_expr(expr).is_some() => 0,
+        ExprKind::Normal(normal) => normal.elements().len(),
+        ExprKind::Association(assoc) => assoc.len(),
+        _ => 0,
+    };
+
+    Ok(Some(Expr::from(length as i64)))
+}
+
+/// `Part[expr, i, j, ...]`: `expr[[i, j, ...]]`
+///
+/// Each part specification can be an integer, `All`, or `Span[start, end]` /
+/// `Span[start, end, step]`. Invalid part specifications leave the expression
+/// unevaluated.
+fn part(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (expr, specs) = match normal.elements().split_first() {
+        Some(split) => split,
+        None => return Ok(None),
+    };
+
+    let specs = match specs.iter().map(part_spec).collect::<Option<Vec<PartSpec>>>() {
+        Some(specs) => specs,
+        None => return Ok(None),
+    };
+
+    Ok(expr.part(&specs).ok())
+}
+
+/// `Take[list, n]`, `Take[list, -n]`, `Take[list, {m, n}]` or `Take[list, {m, n, s}]`
+fn take(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(take_or_drop(normal, true))
+}
+
+/// `Drop[list, n]`, `Drop[list, -n]`, `Drop[list, {m, n}]` or `Drop[list, {m, n, s}]`
+fn drop(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(take_or_drop(normal, false))
+}
+
+fn take_or_drop(normal: &Normal, take: bool) -> Option<Expr> {
+    let (list, spec) = match normal.elements() {
+        [list, spec] => (list.try_as_normal()?, spec),
+        _ => return None,
+    };
+
+    let (start, end, step) = match spec.kind() {
+        ExprKind::Integer(n) if *n >= 0 => (1, *n, 1),
+        ExprKind::Integer(n) => (*n, -1, 1),
+        _ if is_symbol(spec, "System`All") => (1, -1, 1),
+        _ => {
+            let spec = as_normal_with_head(spec, "System`List")?;
+            let bounds: Vec<i64> =
+                spec.elements().iter().map(integer).collect::<Option<_>>()?;
+            match bounds[..] {
+                [n] => (n, n, 1),
+                [start, end] => (start, end, 1),
+                [start, end, step] if step != 0 => (start, end, step),
+                _ => return None,
+            }
+        },
+    };
+
+    let positions = span_positions(start, end, step, list.elements().len())?;
+
+    // Position `0` is the head, which cannot be taken or dropped.
+    if positions.contains(&0) {
+        return None;
+    }
+
+    let contents = if take {
+        positions
+            .into_iter()
+            .map(|position| list.elements()[position - 1].clone())
+            .collect()
+    } else {
+        list.elements()
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !positions.contains(&(index + 1)))
+            .map(|(_, elem)| elem.clone())
+            .collect()
+    };
+
+    Some(Expr::normal(list.head.clone(), contents))
+}
+
+/// `Join[list1, list2, ...]`
+///
+/// Each element must be a normal expression with the same head.
+fn join(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let lists = match normal
+        .elements()
+        .iter()
+        .map(Expr::try_as_normal)
+        .collect::<Option<Vec<&Normal>>>()
+    {
+        Some(lists) => lists,
+        None => return Ok(None),
+    };
+
+    let head = match lists.first() {
+        Some(first) => first.head.clone(),
+        None => return Ok(Some(Expr::list(vec![]))),
+    };
+
+    if lists.iter().any(|list| list.head != head) {
+        return Ok(None);
+    }
+
+    let contents = lists
+        .into_iter()
+        .flat_map(|list| list.elements().iter().cloned())
+        .collect();
+
+    Ok(Some(Expr::normal(head, contents)))
+}
+
+//======================================
+// Construction
+//======================================
+
+/// `Range[n]`, `Range[m, n]` or `Range[m, n, step]`
+fn range(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let numbers = match normal
+        .elements()
+        .iter()
+        .map(Numeric::from_expr)
+        .collect::<Option<Vec<Numeric>>>()
+    {
+        Some(numbers) => numbers,
+        None => return Ok(None),
+    };
+
+    let values = match numbers[..] {
+        [end] => range_values(Numeric::ONE, end, Numeric::ONE),
+        [start, end] => range_values(start, end, Numeric::ONE),
+        [start, end, step] => range_values(start, end, step),
+        _ => None,
+    };
+
+    Ok(values.map(Expr::list))
+}
+
+/// The values `start, start + step, ...` up to and including `end`.
+///
+/// Returns `None` if `step` is zero or the values cannot be represented.
+fn range_values(start: Numeric, end: Numeric, step: Numeric) -> Option<Vec<Expr>> {
+    if step.is_zero() {
+        return None;
+    }
+
+    // A real step makes every value real.
+    let start = match step {
+        Numeric::Real(_) => Numeric::Real(start.to_f64()),
+        Numeric::Exact(_) => start,
+    };
+
+    let count = end.checked_sub(start)?.checked_div(step)?.floor()?;
+    if count < 0 {
+        return Some(vec![]);
+    }
+
+    let mut values = Vec::with_capacity(usize::try_from(count).ok()?.saturating_add(1));
+    let mut value = start;
+    for _ in 0..=count {
+        values.push(value.to_expr());
+        value = value.checked_add(step)?;
+    }
+
+    Some(values)
+}
+
+/// `Table[expr, spec1, spec2, ...]`
+///
+/// Each iterator specification can be `n`, `{n}`, `{i, n}`, `{i, m, n}`,
+/// `{i, m, n, step}` or `{i, {values...}}`. The iterator variable `i` is localized as
+/// if by `Block`.
+fn table(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    match normal.elements().split_first() {
+        Some((body, specs)) if !specs.is_empty() => table_level(evaluator, body, specs),
+        _ => Ok(None),
+    }
+}
+
+fn table_level(evaluator: &mut Evaluator, body: &Expr, specs: &[Expr]) -> BuiltinResult {
+    let (spec, rest) = match specs.split_first() {
+        Some(split) => split,
+        None => return evaluator.evaluate(body).map(Some),
+    };
+
+    let TableIterator { variable, values } = match table_iterator(evaluator, spec)? {
+        Some(iterator) => iterator,
+        None => return Ok(None),
+    };
+
+    let mut contents = Vec::with_capacity(values.len());
+
+    for value in values {
+        let saved = variable.map(|variable| {
+            let saved = evaluator.definitions.take_own_value(variable);
+            evaluator.definitions.set_own_value(variable.clone(), value);
+            (variable, saved)
+        });
+
+        let result = table_level(evaluator, body, rest);
+
+        if let Some((variable, saved)) = saved {
+            evaluator.definitions.take_own_value(variable);
+            if let Some(saved) = saved {
+                evaluator.definitions.set_own_value(variable.clone(), saved);
+            }
+        }
+
+        match result? {
+            Some(elem) => contents.push(elem),
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(Expr::list(contents)))
+}
+
+/// An iterator in `Table`: the iterator variable (if any) and the values it takes.
+struct TableIterator<'e> {
+    variable: Option<&'e Symbol>,
+    values: Vec<Expr>,
+}
+
+/// Parse and evaluate a `Table` iterator specification.
+fn table_iterator<'e>(
+    evaluator: &mut Evaluator,
+    spec: &'e Expr,
+) -> Result<Option<TableIterator<'e>>, EvalError> {
+    let (variable, bounds) = match as_normal_with_head(spec, "System`List") {
+        Some(list) => match list.elements().split_first() {
+            Some((variable, bounds)) if !bounds.is_empty() => {
+                match variable.try_as_symbol() {
+                    Some(variable) => (Some(variable), bounds),
+                    None => return Ok(None),
+                }
+            },
+            _ => (None, list.elements()),
+        },
+        None => (None, std::slice::from_ref(spec)),
+    };
+
+    let bounds = bounds
+        .iter()
+        .map(|bound| evaluator.evaluate(bound))
+        .collect::<Result<Vec<Expr>, EvalError>>()?;
+
+    // {i, {values...}}
+    if let [values] = &bounds[..] {
+        if let Some(values) = as_normal_with_head(values, "System`List") {
+            return Ok(variable.map(|variable| TableIterator {
+                variable: Some(variable),
+                values: values.elements().to_vec(),
+            }));
+        }
+    }
+
+    let numbers = match bounds
+        .iter()
+        .map(Numeric::from_expr)
+        .collect::<Option<Vec<Numeric>>>()
+    {
+        Some(numbers) => numbers,
+        None => return Ok(None),
+    };
+
+    let values = match numbers[..] {
+        [end] => match variable {
+            Some(_) => range_values(Numeric::ONE, end, Numeric::ONE),
+            // Table[expr, n] evaluates `expr` `n` times.
+            None => end
+                .floor()
+                .map(|count| vec![Expr::null(); usize::try_from(count).unwrap_or(0)]),
+        },
+        [start, end] => range_values(start, end, Numeric::ONE),
+        [start, end, step] => range_values(start, end, step),
+        _ => None,
+    };
+
+    Ok(values.map(|values| TableIterator { variable, values }))
+}
+
+//======================================
+// Functional operations
+//======================================
+
+/// `Map[f, expr]`: `f /@ expr`
+fn map(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (function, expr) = match normal.elements() {
+        [function, expr] => (function, expr),
+        _ => return Ok(None),
+    };
+
+    let target = match expr.kind() {
+        ExprKind::Normal(_) if Numeric::from_expr(expr).is_some() => {
+            return Ok(Some(expr.clone()))
+        },
+        ExprKind::Normal(target) => target,
+        _ => return Ok(Some(expr.clone())),
+    };
+
+    let contents = target
+        .elements()
+        .iter()
+        .map(|elem| Expr::normal(function.clone(), vec![elem.clone()]))
+        .collect();
+
+    Ok(Some(Expr::normal(target.head.clone(), contents)))
+}
+
+/// `Select[list, crit]` or `Select[list, crit, n]`
+///
+/// Keeps the elements `e` of `list` for which `crit[e]` evaluates to `True`, up to a
+/// maximum of `n` elements.
+fn select(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (list, criterion, limit) = match normal.elements() {
+        [list, criterion] => (list, criterion, usize::MAX),
+        [list, criterion, limit] => match integer(limit) {
+            Some(limit) if limit >= 0 => (list, criterion, limit as usize),
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let list = match list.try_as_normal() {
+        Some(list) => list,
+        None => return Ok(None),
+    };
+
+    let mut contents = Vec::new();
+
+    for elem in list.elements() {
+        if contents.len() >= limit {
+            break;
+        }
+
+        let test = Expr::normal(criterion.clone(), vec![elem.clone()]);
+        if evaluator.evaluate(&test)?.try_as_bool() == Some(true) {
+            contents.push(elem.clone());
+        }
+    }
+
+    Ok(Some(Expr::normal(list.head.clone(), contents)))
+}
+
+/// `Total[list]`: the sum of the elements of `list`.
+fn total(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [list] => list.try_as_normal().map(|list| {
+            Expr::function(system::Plus, list.elements().to_vec())
+        }),
+        _ => None,
+    })
+}
+
+/// `Sort[list]` or `Sort[list, p]`
+///
+/// Without an ordering function, the elements are sorted into canonical order.
+/// Otherwise, `p[a, b]` is evaluated to determine whether `a` should come before `b`.
+/// The sort is stable.
+fn sort(evaluator: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    match normal.elements() {
+        [list] if list.try_as_normal().is_some() => Ok(list.sort()),
+        [list, order] => {
+            let list = match list.try_as_normal() {
+                Some(list) => list,
+                None => return Ok(None),
+            };
+            let contents = merge_sort(evaluator, list.elements(), order)?;
+            Ok(Some(Expr::normal(list.head.clone(), contents)))
+        },
+        _ => Ok(None),
+    }
+}
+
+fn merge_sort(
+    evaluator: &mut Evaluator,
+    elements: &[Expr],
+    order: &Expr,
+) -> Result<Vec<Expr>, EvalError> {
+    if elements.len() <= 1 {
+        return Ok(elements.to_vec());
+    }
+
+    let (left, right) = elements.split_at(elements.len() / 2);
+    let left = merge_sort(evaluator, left, order)?;
+    let right = merge_sort(evaluator, right, order)?;
+
+    let mut merged = Vec::with_capacity(elements.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        // Only take from the right if it must come first, so that the sort is stable.
+        let right_first = is_ordered(evaluator, order, r, l)?
+            && !is_ordered(evaluator, order, l, r)?;
+
+        merged.extend(match right_first {
+            true => right.next(),
+            false => left.next(),
+        });
+    }
+
+    merged.extend(left);
+    merged.extend(right);
+
+    Ok(merged)
+}
+
+//======================================
+// Helpers
+//======================================
+
+/// Returns `true` if `order[a, b]` evaluates to `True`.
+fn is_ordered(
+    evaluator: &mut Evaluator,
+    order: &Expr,
+    a: &Expr,
+    b: &Expr,
+) -> Result<bool, EvalError> {
+    let test = Expr::normal(order.clone(), vec![a.clone(), b.clone()]);
+    Ok(evaluator.evaluate(&test)?.try_as_bool() == Some(true))
+}
+
+fn integer(expr: &Expr) -> Option<i64> {
+    match expr.kind() {
+        ExprKind::Integer(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Convert an evaluated `Part` specification into a [`PartSpec`].
+fn part_spec(spec: &Expr) -> Option<PartSpec> {
+    if let ExprKind::Integer(index) = spec.kind() {
+        return Some(PartSpec::Index(*index));
+    }
+
+    if is_symbol(spec, "System`All") {
+        return Some(PartSpec::All);
+    }
+
+    // In a span, `All` as the start or end means the first or last element.
+    let bound = |expr: &Expr, all: i64| match expr.kind() {
+        ExprKind::Integer(index) => Some(*index),
+        _ if is_symbol(expr, "System`All") => Some(all),
+        _ => None,
+    };
+
+    match as_normal_with_head(spec, "System`Span")?.elements() {
+        [start, end] => Some(PartSpec::span(bound(start, 1)?, bound(end, -1)?)),
+        [start, end, step] => Some(PartSpec::Span {
+            start: bound(start, 1)?,
+            end: bound(end, -1)?,
+            step: integer(step)?,
+        }),
+        _ => None,
+    }
+}
+
+//======================================
+// Associations
+//======================================
+
+/// `Association[rules...]`: `<|rules...|>`
+///
+/// The elements may be rules, lists of rules, or associations. Any other element leaves
+/// the expression unevaluated.
+fn association(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let expr = Expr::from(normal.clone());
+    Ok(Association::try_from(&expr).ok().map(Expr::from))
+}
+
+/// `Keys[assoc]`
+fn keys(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [expr] => expr
+            .try_as_association()
+            .map(|assoc| Expr::list(assoc.keys().cloned().collect())),
+        _ => None,
+    })
+}
+
+/// `Values[assoc]`
+fn values(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [expr] => expr.try_as_association().map(|assoc| {
+            Expr::list(assoc.rules().map(|(_, _, value)| value.clone()).collect())
+        }),
+        _ => None,
+    })
+}
+
+/// `Lookup[assoc, key]` or `Lookup[assoc, key, default]`
+fn lookup(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (assoc, key, default) = match normal.elements() {
+        [assoc, key] => (assoc, key, None),
+        [assoc, key, default] => (assoc, key, Some(default)),
+        _ => return Ok(None),
+    };
+
+    let assoc = match assoc.try_as_association() {
+        Some(assoc) => assoc,
+        None => return Ok(None),
+    };
+
+    Ok(Some(match (assoc.get_rule(key), default) {
+        (Some((_, value)), _) => value.clone(),
+        (None, Some(default)) => default.clone(),
+        (None, None) => assoc.get_or_missing(key),
+    }))
+}
@@ -0,0 +1,594 @@
+//! Rule-based evaluation of expressions, without a Wolfram kernel.
+//!
+//! [`Evaluator`] implements a subset of the Wolfram Language
+//! [standard evaluation procedure][ref/Evaluation]. It stores user definitions made with
+//! [`Set`](https://reference.wolfram.com/language/ref/Set.html) <sub>WL</sub> and
+//! [`SetDelayed`](https://reference.wolfram.com/language/ref/SetDelayed.html)
+//! <sub>WL</sub> in [`Definitions`], and understands a small set of built-in control
+//! flow, scoping, arithmetic, list and string functions.
+//!
+//! [ref/Evaluation]: https://reference.wolfram.com/language/tutorial/EvaluationOfExpressions.html
+
+mod arithmetic;
+mod builtins;
+mod function;
+mod lists;
+mod pattern;
+mod strings;
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{
+    attributes::{flatten_and_sort, Attribute, AttributeTable, Attributes},
+    Association, Expr, ExprKind, Normal, RuleKind, Symbol,
+};
+
+pub(crate) use self::arithmetic::{add, multiply, raise};
+pub use self::function::FunctionError;
+use self::function::function_attributes;
+pub(crate) use self::pattern::{is_pattern_free, substitute, Bindings};
+
+/// Implementation of a built-in function.
+///
+/// A builtin is called with a normal expression whose head is the symbol it was
+/// registered for, and whose elements have already been evaluated according to the
+/// attributes of that symbol. It returns `Ok(None)` if it does not apply to the given
+/// expression, which is then left unevaluated.
+///
+/// The returned expression will be evaluated further by the evaluator.
+pub type Builtin = fn(&mut Evaluator, &Normal) -> Result<Option<Expr>, EvalError>;
+
+type BuiltinResult = Result<Option<Expr>, EvalError>;
+
+/// Evaluates expressions using stored definitions and built-in functions.
+///
+/// # Example
+///
+/// Evaluate `f[x_] := g[x, x]; f[1]`:
+///
+/// ```
+/// use wolfram_expr::{Evaluator, Expr, Symbol};
+///
+/// let call = |head: &str, elems| Expr::function(Symbol::new(head), elems);
+/// let x = Expr::symbol(Symbol::new("Global`x"));
+///
+/// let mut evaluator = Evaluator::new();
+///
+/// let program = call("System`CompoundExpression", vec![
+///     call("System`SetDelayed", vec![
+///         call("Global`f", vec![call("System`Pattern", vec![
+///             x.clone(),
+///             call("System`Blank", vec![]),
+///         ])]),
+///         call("Global`g", vec![x.clone(), x]),
+///     ]),
+///     call("Global`f", vec![Expr::from(1)]),
+/// ]);
+///
+/// assert_eq!(
+///     evaluator.evaluate(&program).unwrap(),
+///     call("Global`g", vec![Expr::from(1), Expr::from(1)])
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    definitions: Definitions,
+    attributes: AttributeTable,
+    builtins: HashMap<Symbol, Builtin>,
+
+    /// `$IterationLimit`
+    iteration_limit: usize,
+    /// `$RecursionLimit`
+    recursion_limit: usize,
+
+    /// Current evaluation depth, compared against `recursion_limit`.
+    depth: usize,
+    /// `$ModuleNumber`, used to generate unique names for `Module` variables.
+    module_number: u64,
+}
+
+/// Definitions associated with symbols.
+///
+/// * *OwnValues* are made by assignments to a symbol, e.g. `x = 5`.
+/// * *DownValues* are made by assignments to a normal expression whose head is a symbol,
+///   e.g. `f[x_] := x^2`.
+#[derive(Debug, Clone, Default)]
+pub struct Definitions {
+    own_values: HashMap<Symbol, Expr>,
+    down_values: HashMap<Symbol, Vec<Definition>>,
+}
+
+/// A single definition: `lhs :> rhs`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Definition {
+    lhs: Expr,
+    rhs: Expr,
+}
+
+/// Error that aborts evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    /// `$IterationLimit::itlim`: an expression was rewritten more than `limit` times
+    /// without reaching a fixed point.
+    #[allow(missing_docs)]
+    IterationLimit { limit: usize, expr: Expr },
+    /// `$RecursionLimit::reclim`: evaluation nested deeper than `limit`.
+    #[allow(missing_docs)]
+    RecursionLimit { limit: usize, expr: Expr },
+}
+
+/// Result of a single round of evaluation of a normal expression.
+enum Step {
+    /// No further rules apply to the expression.
+    Done(Expr),
+    /// A rule was applied, and the result must be evaluated again.
+    Continue(Expr),
+}
+
+//======================================
+// Evaluator
+//======================================
+
+impl Evaluator {
+    /// Default value of `$IterationLimit`.
+    pub const DEFAULT_ITERATION_LIMIT: usize = 4096;
+
+    /// Default value of `$RecursionLimit`.
+    pub const DEFAULT_RECURSION_LIMIT: usize = 1024;
+
+    /// Construct a new evaluator with no user definitions, the attributes from
+    /// [`AttributeTable::system()`], and the built-in functions.
+    pub fn new() -> Self {
+        let mut evaluator = Evaluator {
+            definitions: Definitions::default(),
+            attributes: AttributeTable::system().clone(),
+            builtins: HashMap::new(),
+            iteration_limit: Evaluator::DEFAULT_ITERATION_LIMIT,
+            recursion_limit: Evaluator::DEFAULT_RECURSION_LIMIT,
+            depth: 0,
+            module_number: 1,
+        };
+
+        builtins::register(&mut evaluator);
+        arithmetic::register(&mut evaluator);
+        lists::register(&mut evaluator);
+        strings::register(&mut evaluator);
+
+        evaluator
+    }
+
+    /// Evaluate `expr`.
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Expr, EvalError> {
+        if self.depth >= self.recursion_limit {
+            return Err(EvalError::RecursionLimit {
+                limit: self.recursion_limit,
+                expr: expr.clone(),
+            });
+        }
+
+        self.depth += 1;
+        let result = self.evaluate_to_fixed_point(expr);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn evaluate_to_fixed_point(&mut self, expr: &Expr) -> Result<Expr, EvalError> {
+        let mut current = expr.clone();
+
+        for _ in 0..=self.iteration_limit {
+            let next = match current.kind() {
+                ExprKind::Symbol(symbol) => {
+                    match self.definitions.own_values.get(symbol) {
+                        Some(value) if *value != current => Step::Continue(value.clone()),
+                        _ => Step::Done(current),
+                    }
+                },
+                ExprKind::Normal(normal) => self.evaluate_normal(normal)?,
+                ExprKind::Association(assoc) => {
+                    Step::Done(self.evaluate_association(&current, assoc)?)
+                },
+                ExprKind::Integer(_) | ExprKind::Real(_) | ExprKind::String(_) => {
+                    Step::Done(current)
+                },
+            };
+
+            current = match next {
+                Step::Done(expr) => return Ok(expr),
+                Step::Continue(expr) => expr,
+            };
+        }
+
+        Err(EvalError::IterationLimit {
+            limit: self.iteration_limit,
+            expr: current,
+        })
+    }
+
+    /// Evaluate the keys of `assoc`, and the values of its `Rule`s. The values of
+    /// `RuleDelayed`s are held.
+    fn evaluate_association(
+        &mut self,
+        expr: &Expr,
+        assoc: &Association,
+    ) -> Result<Expr, EvalError> {
+        let mut evaluated = Association::new();
+        for (key, kind, value) in assoc.rules() {
+            let value = match kind {
+                RuleKind::Rule => self.evaluate(value)?,
+                RuleKind::RuleDelayed => value.clone(),
+            };
+            evaluated.insert_rule(self.evaluate(key)?, kind, value);
+        }
+
+        Ok(match evaluated == *assoc {
+            true => expr.clone(),
+            false => Expr::from(evaluated),
+        })
+    }
+
+    fn evaluate_normal(&mut self, normal: &Normal) -> Result<Step, EvalError> {
+        let head = self.evaluate(&normal.head)?;
+
+        let attrs = self.head_attributes(&head);
+
+        //
+        // Evaluate the elements
+        //
+
+        let mut contents = Vec::with_capacity(normal.contents.len());
+
+        for (index, elem) in normal.contents.iter().enumerate() {
+            let elem = if attrs.holds(index) {
+                match single_element(elem, "System`Evaluate") {
+                    Some(inner) => self.evaluate(inner)?,
+                    None => elem.clone(),
+                }
+            } else {
+                match single_element(elem, "System`Unevaluated") {
+                    Some(inner) => inner.clone(),
+                    None => self.evaluate(elem)?,
+                }
+            };
+
+            // Splice `Sequence[...]` into the enclosing expression.
+            match elem.kind() {
+                ExprKind::Normal(seq) if is_symbol(&seq.head, "System`Sequence") => {
+                    contents.extend(seq.contents.iter().cloned())
+                },
+                _ => contents.push(elem),
+            }
+        }
+
+        let contents = flatten_and_sort(&head, contents, attrs);
+        let normal = Normal::new(head, contents);
+
+        //
+        // Apply rules
+        //
+
+        if attrs.contains(Attribute::Listable) {
+            if let Some(threaded) = thread_listable(&normal) {
+                return Ok(Step::Continue(threaded));
+            }
+        }
+
+        // <|key -> value, ...|>[key]
+        if let Some(assoc) = normal.head.try_as_association() {
+            if let [key] = normal.elements() {
+                return Ok(Step::Done(assoc.get_or_missing(key)));
+            }
+        }
+
+        if as_normal_with_head(&normal.head, "System`Function").is_some() {
+            return Ok(match normal.head.apply_function(&normal.contents) {
+                Ok(result) => Step::Continue(result),
+                Err(_) => Step::Done(Expr::from(normal)),
+            });
+        }
+
+        let tag = match normal.head.kind() {
+            ExprKind::Symbol(tag) => tag.clone(),
+            _ => return Ok(Step::Done(Expr::from(normal))),
+        };
+
+        let expr = Expr::from(normal);
+
+        if let Some(result) = self.apply_down_values(&tag, &expr)? {
+            return Ok(Step::Continue(result));
+        }
+
+        if let Some(builtin) = self.builtins.get(&tag).copied() {
+            let normal = expr.try_as_normal().unwrap();
+            if let Some(result) = builtin(self, normal)? {
+                return Ok(match result == expr {
+                    true => Step::Done(result),
+                    false => Step::Continue(result),
+                });
+            }
+        }
+
+        Ok(Step::Done(expr))
+    }
+
+    fn apply_down_values(
+        &mut self,
+        tag: &Symbol,
+        expr: &Expr,
+    ) -> Result<Option<Expr>, EvalError> {
+        let rules = match self.definitions.down_values.get(tag) {
+            Some(rules) => rules.clone(),
+            None => return Ok(None),
+        };
+
+        for Definition { lhs, rhs } in rules {
+            let mut bindings = Bindings::new();
+            if !self.match_pattern(&lhs, expr, &mut bindings)? {
+                continue;
+            }
+
+            let rhs = substitute(&rhs, &bindings);
+
+            // `lhs := rhs /; test` only applies if `test` evaluates to `True`.
+            if let Some(condition) = as_normal_with_head(&rhs, "System`Condition") {
+                if let [value, test] = condition.elements() {
+                    if self.evaluate(test)?.try_as_bool() == Some(true) {
+                        return Ok(Some(value.clone()));
+                    }
+                    continue;
+                }
+            }
+
+            return Ok(Some(rhs));
+        }
+
+        Ok(None)
+    }
+
+    /// Get the attributes of `head`, if it is a symbol.
+    fn head_attributes(&self, head: &Expr) -> Attributes {
+        match head.kind() {
+            ExprKind::Symbol(symbol) => self.attributes.get(symbol),
+            // Function[params, body, attrs]
+            _ => match as_normal_with_head(head, "System`Function") {
+                Some(function) => function_attributes(function).unwrap_or_default(),
+                None => Attributes::empty(),
+            },
+        }
+    }
+
+    //==================================
+    // Accessors
+    //==================================
+
+    /// The definitions made in this evaluator.
+    pub fn definitions(&self) -> &Definitions {
+        &self.definitions
+    }
+
+    /// Mutable access to the definitions made in this evaluator.
+    pub fn definitions_mut(&mut self) -> &mut Definitions {
+        &mut self.definitions
+    }
+
+    /// The symbol attributes used by this evaluator.
+    pub fn attributes(&self) -> &AttributeTable {
+        &self.attributes
+    }
+
+    /// Mutable access to the symbol attributes used by this evaluator.
+    pub fn attributes_mut(&mut self) -> &mut AttributeTable {
+        &mut self.attributes
+    }
+
+    /// Register `builtin` as the implementation of `symbol`, replacing any previous
+    /// builtin for that symbol.
+    ///
+    /// Builtins are applied after any user definitions for `symbol`.
+    pub fn add_builtin(&mut self, symbol: Symbol, builtin: Builtin) {
+        self.builtins.insert(symbol, builtin);
+    }
+
+    /// The maximum number of times an expression will be rewritten while evaluating it
+    /// (`$IterationLimit`).
+    pub fn iteration_limit(&self) -> usize {
+        self.iteration_limit
+    }
+
+    /// Set the maximum number of times an expression will be rewritten while evaluating
+    /// it (`$IterationLimit`).
+    pub fn set_iteration_limit(&mut self, limit: usize) {
+        self.iteration_limit = limit;
+    }
+
+    /// The maximum depth of nested evaluations (`$RecursionLimit`).
+    pub fn recursion_limit(&self) -> usize {
+        self.recursion_limit
+    }
+
+    /// Set the maximum depth of nested evaluations (`$RecursionLimit`).
+    ///
+    /// Each level of evaluation uses space on the Rust call stack, so large limits may
+    /// require running the evaluator on a thread with a larger stack.
+    pub fn set_recursion_limit(&mut self, limit: usize) {
+        self.recursion_limit = limit;
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Evaluator::new()
+    }
+}
+
+//======================================
+// Definitions
+//======================================
+
+impl Definitions {
+    /// Get the OwnValue of `symbol`.
+    pub fn own_value(&self, symbol: &Symbol) -> Option<&Expr> {
+        self.own_values.get(symbol)
+    }
+
+    /// Set the OwnValue of `symbol`, as in `symbol = value`.
+    pub fn set_own_value(&mut self, symbol: Symbol, value: Expr) {
+        self.own_values.insert(symbol, value);
+    }
+
+    /// Get the DownValues of `symbol`, in the order they are tried.
+    pub fn down_values(&self, symbol: &Symbol) -> &[Definition] {
+        match self.down_values.get(symbol) {
+            Some(rules) => rules,
+            None => &[],
+        }
+    }
+
+    /// Add a DownValue for `tag`, as in `lhs := rhs`.
+    ///
+    /// A definition whose `lhs` is identical to an existing definition replaces it.
+    /// Otherwise, definitions whose `lhs` contains no patterns are tried before
+    /// definitions containing patterns, and definitions are otherwise tried in the
+    /// order they were added.
+    pub fn add_down_value(&mut self, tag: Symbol, lhs: Expr, rhs: Expr) {
+        let rules = self.down_values.entry(tag).or_default();
+
+        if let Some(existing) = rules.iter_mut().find(|rule| rule.lhs == lhs) {
+            existing.rhs = rhs;
+            return;
+        }
+
+        let position = if is_pattern_free(&lhs) {
+            rules
+                .iter()
+                .position(|rule| !is_pattern_free(&rule.lhs))
+                .unwrap_or(rules.len())
+        } else {
+            rules.len()
+        };
+
+        rules.insert(position, Definition { lhs, rhs });
+    }
+
+    /// Remove all OwnValues and DownValues of `symbol`, as in `Clear[symbol]`.
+    pub fn clear(&mut self, symbol: &Symbol) {
+        self.own_values.remove(symbol);
+        self.down_values.remove(symbol);
+    }
+
+    /// Remove the OwnValue of `symbol`, returning it.
+    pub(crate) fn take_own_value(&mut self, symbol: &Symbol) -> Option<Expr> {
+        self.own_values.remove(symbol)
+    }
+}
+
+impl Definition {
+    /// The left-hand side of this definition.
+    pub fn lhs(&self) -> &Expr {
+        &self.lhs
+    }
+
+    /// The right-hand side of this definition.
+    pub fn rhs(&self) -> &Expr {
+        &self.rhs
+    }
+}
+
+//======================================
+// Helpers
+//======================================
+
+/// Returns `true` if `expr` is the symbol named `name`.
+pub(crate) fn is_symbol(expr: &Expr, name: &str) -> bool {
+    match expr.kind() {
+        ExprKind::Symbol(symbol) => symbol.as_str() == name,
+        _ => false,
+    }
+}
+
+/// If `expr` is a normal expression whose head is the symbol named `name`, return it.
+pub(crate) fn as_normal_with_head<'e>(expr: &'e Expr, name: &str) -> Option<&'e Normal> {
+    match expr.kind() {
+        ExprKind::Normal(normal) if is_symbol(&normal.head, name) => Some(normal),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `symbol` appears anywhere in `expr`.
+fn contains_symbol(expr: &Expr, symbol: &Symbol) -> bool {
+    match expr.kind() {
+        ExprKind::Symbol(other) => other == symbol,
+        ExprKind::Normal(normal) => {
+            contains_symbol(&normal.head, symbol)
+                || normal.elements().iter().any(|elem| contains_symbol(elem, symbol))
+        },
+        ExprKind::Association(assoc) => assoc.rules().any(|(key, _, value)| {
+            contains_symbol(key, symbol) || contains_symbol(value, symbol)
+        }),
+        ExprKind::Integer(_) | ExprKind::Real(_) | ExprKind::String(_) => false,
+    }
+}
+
+/// If `expr` has the form `name[elem]`, return `elem`.
+fn single_element<'e>(expr: &'e Expr, name: &str) -> Option<&'e Expr> {
+    match as_normal_with_head(expr, name)?.elements() {
+        [elem] => Some(elem),
+        _ => None,
+    }
+}
+
+/// Thread a normal expression over any lists in its elements, e.g.
+/// `f[{1, 2}, x]` becomes `{f[1, x], f[2, x]}`.
+///
+/// Returns `None` if no element is a list, or if the lists have different lengths.
+fn thread_listable(normal: &Normal) -> Option<Expr> {
+    let mut length = None;
+
+    for elem in &normal.contents {
+        if let Some(list) = as_normal_with_head(elem, "System`List") {
+            match length {
+                None => length = Some(list.contents.len()),
+                Some(len) if len == list.contents.len() => (),
+                Some(_) => return None,
+            }
+        }
+    }
+
+    let length = length?;
+
+    let threaded = (0..length)
+        .map(|index| {
+            let contents = normal
+                .contents
+                .iter()
+                .map(|elem| match as_normal_with_head(elem, "System`List") {
+                    Some(list) => list.contents[index].clone(),
+                    None => elem.clone(),
+                })
+                .collect();
+            Expr::normal(normal.head.clone(), contents)
+        })
+        .collect();
+
+    Some(Expr::list(threaded))
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::IterationLimit { limit, expr } => write!(
+                f,
+                "Iteration limit of {} exceeded while evaluating {}.",
+                limit, expr
+            ),
+            EvalError::RecursionLimit { limit, expr } => write!(
+                f,
+                "Recursion depth of {} exceeded during evaluation of {}.",
+                limit, expr
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
@@ -0,0 +1,393 @@
+//! Pattern matching and substitution of pattern variables.
+
+use crate::{
+    attributes::Attribute,
+    evaluate::{as_normal_with_head, is_symbol, EvalError, Evaluator},
+    system, Expr, ExprKind, Normal, Symbol,
+};
+
+/// Values of the named pattern variables bound by a successful match.
+///
+/// Variables bound to a sequence of elements (by `__` or `___`) have a value of the form
+/// `Sequence[...]`.
+pub(crate) type Bindings = Vec<(Symbol, Expr)>;
+
+/// A `__` or `___` pattern, possibly named.
+struct SequencePattern<'p> {
+    name: Option<&'p Symbol>,
+    /// The minimum number of elements this pattern matches.
+    min: usize,
+    /// The head each matched element must have, if any.
+    head: Option<&'p Expr>,
+}
+
+impl Evaluator {
+    /// Attempt to match `expr` against `pattern`, recording the values of any named
+    /// pattern variables in `bindings`.
+    ///
+    /// If the match fails, `bindings` is left unchanged.
+    ///
+    /// The attributes of symbols in this evaluator are respected: the elements of an
+    /// expression with an [`Orderless`][Attribute::Orderless] head may match in any
+    /// order, and a single pattern inside a [`Flat`][Attribute::Flat] head may match a
+    /// run of elements.
+    pub(crate) fn match_pattern(
+        &mut self,
+        pattern: &Expr,
+        expr: &Expr,
+        bindings: &mut Bindings,
+    ) -> Result<bool, EvalError> {
+        let mark = bindings.len();
+
+        let matched = self.match_pattern_inner(pattern, expr, bindings)?;
+
+        if !matched {
+            bindings.truncate(mark);
+        }
+
+        Ok(matched)
+    }
+
+    fn match_pattern_inner(
+        &mut self,
+        pattern: &Expr,
+        expr: &Expr,
+        bindings: &mut Bindings,
+    ) -> Result<bool, EvalError> {
+        let pnormal = match pattern.kind() {
+            ExprKind::Normal(pnormal) => pnormal,
+            _ => return Ok(pattern == expr),
+        };
+
+        let pname = match pnormal.head.kind() {
+            ExprKind::Symbol(symbol) => symbol.as_str(),
+            _ => "",
+        };
+
+        match (pname, pnormal.elements()) {
+            ("System`Blank", [])
+            | ("System`BlankSequence", [])
+            | ("System`BlankNullSequence", []) => Ok(true),
+            ("System`Blank", [head])
+            | ("System`BlankSequence", [head])
+            | ("System`BlankNullSequence", [head]) => Ok(expr.head() == *head),
+            ("System`Pattern", [name, pattern]) => {
+                let name = match name.try_as_symbol() {
+                    Some(name) => name,
+                    None => return Ok(false),
+                };
+                Ok(self.match_pattern(pattern, expr, bindings)?
+                    && bind(name, expr.clone(), bindings))
+            },
+            ("System`Condition", [pattern, test]) => {
+                if !self.match_pattern(pattern, expr, bindings)? {
+                    return Ok(false);
+                }
+                let test = substitute(test, bindings);
+                Ok(self.evaluate(&test)?.try_as_bool() == Some(true))
+            },
+            ("System`PatternTest", [pattern, test]) => {
+                if !self.match_pattern(pattern, expr, bindings)? {
+                    return Ok(false);
+                }
+                let test = Expr::normal(test.clone(), vec![expr.clone()]);
+                Ok(self.evaluate(&test)?.try_as_bool() == Some(true))
+            },
+            ("System`Alternatives", alternatives) => {
+                for alternative in alternatives {
+                    if self.match_pattern(alternative, expr, bindings)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            },
+            ("System`HoldPattern", [pattern]) => {
+                self.match_pattern(pattern, expr, bindings)
+            },
+            _ => {
+                let normal = match expr.kind() {
+                    ExprKind::Normal(normal) => normal,
+                    _ => return Ok(false),
+                };
+                self.match_normal(pnormal, normal, bindings)
+            },
+        }
+    }
+
+    fn match_normal(
+        &mut self,
+        pattern: &Normal,
+        normal: &Normal,
+        bindings: &mut Bindings,
+    ) -> Result<bool, EvalError> {
+        if !self.match_pattern(&pattern.head, &normal.head, bindings)? {
+            return Ok(false);
+        }
+
+        let attrs = self.head_attributes(&normal.head);
+        let flat_head = match attrs.contains(Attribute::Flat) {
+            true => Some(&normal.head),
+            false => None,
+        };
+
+        if attrs.contains(Attribute::Orderless) {
+            let exprs: Vec<&Expr> = normal.contents.iter().collect();
+            self.match_orderless(&pattern.contents, exprs, flat_head, bindings)
+        } else {
+            self.match_ordered(&pattern.contents, &normal.contents, flat_head, bindings)
+        }
+    }
+
+    /// Match a sequence of elements against a sequence of patterns, in order.
+    fn match_ordered(
+        &mut self,
+        patterns: &[Expr],
+        exprs: &[Expr],
+        flat_head: Option<&Expr>,
+        bindings: &mut Bindings,
+    ) -> Result<bool, EvalError> {
+        let (pattern, rest) = match patterns.split_first() {
+            Some(split) => split,
+            None => return Ok(exprs.is_empty()),
+        };
+
+        let mark = bindings.len();
+
+        if let Some(seq) = sequence_pattern(pattern) {
+            for count in seq.min..=exprs.len() {
+                if count > 0 && !seq.matches_element(&exprs[count - 1]) {
+                    break;
+                }
+
+                let value = Expr::function(sequence_symbol(), exprs[..count].to_vec());
+                if seq.bind(value, bindings)
+                    && self.match_ordered(rest, &exprs[count..], flat_head, bindings)?
+                {
+                    return Ok(true);
+                }
+                bindings.truncate(mark);
+            }
+
+            return Ok(false);
+        }
+
+        if exprs.is_empty() {
+            return Ok(false);
+        }
+
+        if self.match_pattern(pattern, &exprs[0], bindings)?
+            && self.match_ordered(rest, &exprs[1..], flat_head, bindings)?
+        {
+            return Ok(true);
+        }
+        bindings.truncate(mark);
+
+        // Inside a `Flat` head, a single pattern can match a run of elements wrapped in
+        // that head.
+        if let Some(head) = flat_head {
+            for count in 2..=exprs.len() {
+                let wrapped = Expr::normal(head.clone(), exprs[..count].to_vec());
+                if self.match_pattern(pattern, &wrapped, bindings)?
+                    && self.match_ordered(rest, &exprs[count..], flat_head, bindings)?
+                {
+                    return Ok(true);
+                }
+                bindings.truncate(mark);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Match a sequence of elements against a sequence of patterns, where any element may
+    /// match any pattern.
+    ///
+    /// Each single-element pattern is tried against every unmatched element. Any
+    /// remaining elements are then matched, in order, against the sequence patterns.
+    fn match_orderless(
+        &mut self,
+        patterns: &[Expr],
+        exprs: Vec<&Expr>,
+        flat_head: Option<&Expr>,
+        bindings: &mut Bindings,
+    ) -> Result<bool, EvalError> {
+        let (singles, sequences): (Vec<&Expr>, Vec<&Expr>) = patterns
+            .iter()
+            .partition(|pattern| sequence_pattern(pattern).is_none());
+
+        let sequences: Vec<Expr> = sequences.into_iter().cloned().collect();
+
+        self.match_orderless_singles(&singles, exprs, &sequences, flat_head, bindings)
+    }
+
+    fn match_orderless_singles(
+        &mut self,
+        singles: &[&Expr],
+        exprs: Vec<&Expr>,
+        sequences: &[Expr],
+        flat_head: Option<&Expr>,
+        bindings: &mut Bindings,
+    ) -> Result<bool, EvalError> {
+        let (pattern, rest) = match singles.split_first() {
+            Some(split) => split,
+            None => {
+                let exprs: Vec<Expr> = exprs.into_iter().cloned().collect();
+                return self.match_ordered(sequences, &exprs, None, bindings);
+            },
+        };
+
+        // Inside a `Flat` head, the last single pattern can absorb all remaining elements
+        // if there are no sequence patterns to match them.
+        if rest.is_empty() && sequences.is_empty() && exprs.len() > 1 {
+            return match flat_head {
+                Some(head) => {
+                    let contents = exprs.into_iter().cloned().collect();
+                    let wrapped = Expr::normal(head.clone(), contents);
+                    self.match_pattern(pattern, &wrapped, bindings)
+                },
+                None => Ok(false),
+            };
+        }
+
+        let mark = bindings.len();
+
+        for index in 0..exprs.len() {
+            if self.match_pattern(pattern, exprs[index], bindings)? {
+                let mut remaining = exprs.clone();
+                remaining.remove(index);
+
+                if self.match_orderless_singles(
+                    rest, remaining, sequences, flat_head, bindings,
+                )? {
+                    return Ok(true);
+                }
+                bindings.truncate(mark);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl<'p> SequencePattern<'p> {
+    fn matches_element(&self, elem: &Expr) -> bool {
+        match self.head {
+            Some(head) => elem.head() == *head,
+            None => true,
+        }
+    }
+
+    fn bind(&self, value: Expr, bindings: &mut Bindings) -> bool {
+        match self.name {
+            Some(name) => bind(name, value, bindings),
+            None => true,
+        }
+    }
+}
+
+/// If `pattern` is `__`, `___`, or a named form of one of them, describe it.
+fn sequence_pattern(pattern: &Expr) -> Option<SequencePattern<'_>> {
+    let (name, blank) = match as_normal_with_head(pattern, "System`Pattern") {
+        Some(named) => match named.elements() {
+            [name, blank] => (Some(name.try_as_symbol()?), blank),
+            _ => return None,
+        },
+        None => (None, pattern),
+    };
+
+    let blank = blank.try_as_normal()?;
+
+    let min = if is_symbol(&blank.head, "System`BlankSequence") {
+        1
+    } else if is_symbol(&blank.head, "System`BlankNullSequence") {
+        0
+    } else {
+        return None;
+    };
+
+    let head = match blank.elements() {
+        [] => None,
+        [head] => Some(head),
+        _ => return None,
+    };
+
+    Some(SequencePattern { name, min, head })
+}
+
+/// Bind `name` to `value`, or check that `value` is equal to the existing binding.
+fn bind(name: &Symbol, value: Expr, bindings: &mut Bindings) -> bool {
+    match bindings.iter().find(|(bound, _)| bound == name) {
+        Some((_, existing)) => *existing == value,
+        None => {
+            bindings.push((name.clone(), value));
+            true
+        },
+    }
+}
+
+fn sequence_symbol() -> Symbol {
+    Symbol::from(system::Sequence)
+}
+
+/// Replace each occurrence of a bound symbol in `expr` with its value.
+///
+/// A symbol bound to `Sequence[...]` which appears as an element of a normal expression
+/// is replaced by the elements of the sequence.
+pub(crate) fn substitute(expr: &Expr, bindings: &[(Symbol, Expr)]) -> Expr {
+    if bindings.is_empty() {
+        return expr.clone();
+    }
+
+    let lookup = |expr: &Expr| -> Option<&Expr> {
+        let symbol = expr.try_as_symbol()?;
+        bindings
+            .iter()
+            .find(|(name, _)| name == symbol)
+            .map(|(_, value)| value)
+    };
+
+    let normal = match expr.kind() {
+        ExprKind::Normal(normal) => normal,
+        ExprKind::Symbol(_) => return lookup(expr).unwrap_or(expr).clone(),
+        _ => return expr.clone(),
+    };
+
+    let head = substitute(&normal.head, bindings);
+
+    let mut contents = Vec::with_capacity(normal.contents.len());
+    for elem in &normal.contents {
+        match lookup(elem) {
+            Some(value) => match as_normal_with_head(value, "System`Sequence") {
+                Some(seq) => contents.extend(seq.contents.iter().cloned()),
+                None => contents.push(value.clone()),
+            },
+            None => contents.push(substitute(elem, bindings)),
+        }
+    }
+
+    Expr::normal(head, contents)
+}
+
+/// Returns `true` if `expr` does not contain any pattern objects (`_`, `x_`, `p /; c`,
+/// etc.).
+pub(crate) fn is_pattern_free(expr: &Expr) -> bool {
+    const PATTERN_HEADS: &[&str] = &[
+        "System`Blank",
+        "System`BlankSequence",
+        "System`BlankNullSequence",
+        "System`Pattern",
+        "System`Condition",
+        "System`PatternTest",
+        "System`Alternatives",
+        "System`Optional",
+    ];
+
+    match expr.kind() {
+        ExprKind::Normal(normal) => {
+            !PATTERN_HEADS.iter().any(|name| is_symbol(&normal.head, name))
+                && is_pattern_free(&normal.head)
+                && normal.contents.iter().all(is_pattern_free)
+        },
+        _ => true,
+    }
+}
@@ -0,0 +1,140 @@
+//! Built-in string functions.
+
+use crate::{
+    evaluate::{as_normal_with_head, BuiltinResult, Evaluator},
+    Expr, ExprKind, Normal, Symbol,
+};
+
+pub(super) fn register(evaluator: &mut Evaluator) {
+    let builtins: &[(&str, super::Builtin)] = &[
+        ("System`StringJoin", string_join),
+        ("System`StringLength", string_length),
+        ("System`StringSplit", string_split),
+        ("System`ToUpperCase", to_upper_case),
+        ("System`ToLowerCase", to_lower_case),
+    ];
+
+    for (name, builtin) in builtins {
+        evaluator.add_builtin(Symbol::new(name), *builtin);
+    }
+}
+
+/// `StringJoin[s1, s2, ...]`: `s1 <> s2 <> ...`
+///
+/// Lists of strings are joined as if their elements were given directly.
+fn string_join(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let mut joined = String::new();
+    let mut stack: Vec<&Expr> = normal.elements().iter().rev().collect();
+
+    while let Some(elem) = stack.pop() {
+        match elem.kind() {
+            ExprKind::String(string) => joined.push_str(string),
+            _ => match as_normal_with_head(elem, "System`List") {
+                Some(list) => stack.extend(list.elements().iter().rev()),
+                None => return Ok(None),
+            },
+        }
+    }
+
+    Ok(Some(Expr::string(joined)))
+}
+
+/// `StringLength[string]`: the number of characters in `string`.
+fn string_length(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [string] => string
+            .try_as_str()
+            .map(|string| Expr::from(string.chars().count() as i64)),
+        _ => None,
+    })
+}
+
+/// `StringSplit[string]` or `StringSplit[string, sep]`
+///
+/// Without a separator, `string` is split at runs of whitespace. Otherwise it is split
+/// at each occurrence of `sep`, which may be a string or a list of strings. Empty
+/// substrings at the beginning and end of the result are dropped.
+fn string_split(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    let (string, separators) = match normal.elements() {
+        [string] => match string.try_as_str() {
+            Some(string) => {
+                let pieces = string.split_whitespace().map(Expr::string).collect();
+                return Ok(Some(Expr::list(pieces)));
+            },
+            None => return Ok(None),
+        },
+        [string, separators] => (string, separators),
+        _ => return Ok(None),
+    };
+
+    let string = match string.try_as_str() {
+        Some(string) => string,
+        None => return Ok(None),
+    };
+
+    let separators: Vec<&str> = match separators.kind() {
+        ExprKind::String(separator) => vec![separator.as_str()],
+        _ => match as_normal_with_head(separators, "System`List")
+            .and_then(|list| list.elements().iter().map(Expr::try_as_str).collect())
+        {
+            Some(separators) => separators,
+            None => return Ok(None),
+        },
+    };
+
+    if separators.iter().any(|separator| separator.is_empty()) {
+        return Ok(None);
+    }
+
+    let mut pieces = split_at_any(string, &separators);
+
+    if pieces.last() == Some(&"") {
+        pieces.pop();
+    }
+    if pieces.first() == Some(&"") {
+        pieces.remove(0);
+    }
+
+    Ok(Some(Expr::list(pieces.into_iter().map(Expr::string).collect())))
+}
+
+/// `ToUpperCase[string]`
+fn to_upper_case(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [string] => string.try_as_str().map(|s| Expr::string(s.to_uppercase())),
+        _ => None,
+    })
+}
+
+/// `ToLowerCase[string]`
+fn to_lower_case(_: &mut Evaluator, normal: &Normal) -> BuiltinResult {
+    Ok(match normal.elements() {
+        [string] => string.try_as_str().map(|s| Expr::string(s.to_lowercase())),
+        _ => None,
+    })
+}
+
+/// Split `string` at each occurrence of any of `separators`, trying them in order at
+/// each position. None of `separators` may be empty.
+fn split_at_any<'s>(string: &'s str, separators: &[&str]) -> Vec<&'s str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut position = 0;
+
+    while position < string.len() {
+        let rest = &string[position..];
+        match separators.iter().find(|separator| rest.starts_with(**separator)) {
+            Some(separator) => {
+                pieces.push(&string[start..position]);
+                position += separator.len();
+                start = position;
+            },
+            None => {
+                position += rest.chars().next().map_or(1, char::len_utf8);
+            },
+        }
+    }
+
+    pieces.push(&string[start..]);
+    pieces
+}
@@ -0,0 +1,346 @@
+//! The operator precedence grammar of the Wolfram Language syntax accepted by
+//! [`ExprTemplate::parse()`] and the `wl!` macro of the `wolfram-expr-macros` crate.
+//!
+//! The grammar is independent of how the input is tokenized, and of how expressions
+//! are represented: each parser implements [`Syntax`] to supply operators, operands
+//! and expression constructors, and calls [`compound()`] to parse an expression.
+//!
+//! This module is not part of the stable API of this crate.
+//!
+//! [`ExprTemplate::parse()`]: crate::ExprTemplate::parse
+
+use crate::{system, SystemSymbol};
+
+/// The operators and brackets of the syntax, ordered so that each operator comes
+/// before its prefixes.
+pub const OPERATORS: &[&str] = &[
+    "===", "=!=", "//.", "->", ":>", ":=", "/.", "//", "/@", "==", "!=", "<=", ">=",
+    "&&", "||", "<|", "|>", "@@", "@", "&", ";", ",", "+", "-", "*", "/", "^", "!", "<",
+    ">", "=", "(", ")", "{", "}", "[", "]",
+];
+
+/// The tokens and expression representation of a parser which uses this grammar.
+pub trait Syntax {
+    /// A parsed expression.
+    type Node;
+    /// A syntax error.
+    type Error;
+
+    /// The operator at the current position, if any: the longest of [`OPERATORS`]
+    /// which the next tokens spell.
+    fn peek_operator(&self) -> Option<&'static str>;
+
+    /// Consume the operator returned by [`peek_operator()`][Syntax::peek_operator].
+    fn skip_operator(&mut self);
+
+    /// Returns `true` at the end of the input, or at a token which ends an element of a
+    /// list or compound expression.
+    fn at_element_end(&self) -> bool;
+
+    /// Returns `true` if the next token starts an operand, which is implicitly
+    /// multiplied with the previous factor, as in `2 x`.
+    fn at_operand(&self) -> bool;
+
+    /// Parse an operand of the operators: an atom, a group like `(x)`, `{x}` or
+    /// `<|x -> y|>`, or a function application or part like `f[x]` or `x[[i]]`.
+    fn operand(&mut self) -> Result<Self::Node, Self::Error>;
+
+    /// An error at the current position.
+    fn error(&self, message: &str) -> Self::Error;
+
+    /// The symbol `symbol`.
+    fn symbol(&self, symbol: SystemSymbol) -> Self::Node;
+
+    /// `head[elements...]`
+    fn apply(&self, head: Self::Node, elements: Vec<Self::Node>) -> Self::Node;
+
+    /// The integer `value`.
+    fn integer(&self, value: i64) -> Self::Node;
+
+    /// `-node`, if `node` is a number.
+    fn negate_number(&self, node: &Self::Node) -> Option<Self::Node>;
+}
+
+//======================================
+// Operators, from lowest to highest precedence
+//======================================
+
+/// `x; y; ...`
+pub fn compound<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let first = set(s)?;
+    if !at(s, ";") {
+        return Ok(first);
+    }
+
+    let mut elements = vec![first];
+    while eat(s, ";") {
+        if s.at_element_end() {
+            elements.push(s.symbol(system::Null));
+        } else {
+            elements.push(set(s)?);
+        }
+    }
+
+    Ok(call(s, system::CompoundExpression, elements))
+}
+
+/// `x = y` and `x := y`, which are right-associative.
+fn set<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let lhs = postfix_operators(s)?;
+
+    let head = if eat(s, "=") {
+        system::Set
+    } else if eat(s, ":=") {
+        system::SetDelayed
+    } else {
+        return Ok(lhs);
+    };
+
+    let rhs = set(s)?;
+    Ok(call(s, head, vec![lhs, rhs]))
+}
+
+/// `x // f` and `body &`.
+fn postfix_operators<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let mut node = replace(s)?;
+
+    loop {
+        if eat(s, "&") {
+            node = call(s, system::Function, vec![node]);
+        } else if eat(s, "//") {
+            let function = replace(s)?;
+            node = s.apply(function, vec![node]);
+        } else {
+            return Ok(node);
+        }
+    }
+}
+
+/// `x /. rules` and `x //. rules`
+pub fn replace<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let mut lhs = rule(s)?;
+
+    loop {
+        let head = if eat(s, "/.") {
+            system::ReplaceAll
+        } else if eat(s, "//.") {
+            system::ReplaceRepeated
+        } else {
+            return Ok(lhs);
+        };
+
+        let rhs = rule(s)?;
+        lhs = call(s, head, vec![lhs, rhs]);
+    }
+}
+
+/// `x -> y` and `x :> y`, which are right-associative.
+fn rule<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let lhs = or(s)?;
+
+    let head = if eat(s, "->") {
+        system::Rule
+    } else if eat(s, ":>") {
+        system::RuleDelayed
+    } else {
+        return Ok(lhs);
+    };
+
+    let rhs = rule(s)?;
+    Ok(call(s, head, vec![lhs, rhs]))
+}
+
+/// `x || y || ...`
+fn or<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let mut operands = vec![and(s)?];
+    while eat(s, "||") {
+        operands.push(and(s)?);
+    }
+    Ok(flatten(s, system::Or, operands))
+}
+
+/// `x && y && ...`
+fn and<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let mut operands = vec![not(s)?];
+    while eat(s, "&&") {
+        operands.push(not(s)?);
+    }
+    Ok(flatten(s, system::And, operands))
+}
+
+/// `!x`
+fn not<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    if eat(s, "!") {
+        let operand = not(s)?;
+        return Ok(call(s, system::Not, vec![operand]));
+    }
+
+    comparison(s)
+}
+
+/// `x == y`, `x != y`, `x === y`, `x =!= y`, `x < y`, `x <= y`, `x > y` and
+/// `x >= y`.
+///
+/// A chain of the same comparison, like `x < y < z`, is a single expression.
+fn comparison<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    const COMPARISONS: [(&str, SystemSymbol); 8] = [
+        ("==", system::Equal),
+        ("!=", system::Unequal),
+        ("===", system::SameQ),
+        ("=!=", system::UnsameQ),
+        ("<", system::Less),
+        ("<=", system::LessEqual),
+        (">", system::Greater),
+        (">=", system::GreaterEqual),
+    ];
+
+    let mut operands = vec![plus(s)?];
+    let mut chain_head = None;
+
+    while let Some((_, head)) = COMPARISONS.iter().find(|(op, _)| at(s, op)) {
+        match chain_head {
+            None => chain_head = Some(*head),
+            Some(chain_head) if chain_head == *head => (),
+            Some(_) => {
+                return Err(s.error(
+                    "chains of different comparison operators are not supported",
+                ))
+            },
+        }
+
+        s.skip_operator();
+        operands.push(plus(s)?);
+    }
+
+    Ok(match chain_head {
+        Some(head) => call(s, head, operands),
+        None => operands.pop().unwrap(),
+    })
+}
+
+/// `x + y - z`
+fn plus<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let mut terms = vec![times(s, false)?];
+
+    loop {
+        if eat(s, "+") {
+            terms.push(times(s, false)?);
+        } else if eat(s, "-") {
+            terms.push(times(s, true)?);
+        } else {
+            return Ok(flatten(s, system::Plus, terms));
+        }
+    }
+}
+
+/// `x * y / z`, `x y` and `-x`, multiplied by `-1` if `negative` is `true`.
+///
+/// Each prefix `-`, as in `- -x`, negates the product again.
+fn times<S: Syntax>(s: &mut S, mut negative: bool) -> Result<S::Node, S::Error> {
+    while eat(s, "-") {
+        negative = !negative;
+    }
+
+    let mut factors = vec![power(s)?];
+
+    loop {
+        if eat(s, "*") {
+            factors.push(factor(s)?);
+        } else if eat(s, "/") {
+            let divisor = factor(s)?;
+            let inverse = vec![divisor, s.integer(-1)];
+            factors.push(call(s, system::Power, inverse));
+        } else if s.at_operand() {
+            factors.push(power(s)?);
+        } else {
+            break;
+        }
+    }
+
+    if negative {
+        let first = factors.remove(0);
+        match s.negate_number(&first) {
+            Some(negated) => factors.insert(0, negated),
+            None => factors.splice(0..0, vec![s.integer(-1), first]).for_each(drop),
+        }
+    }
+
+    Ok(flatten(s, system::Times, factors))
+}
+
+/// A factor after `*` or `/`, which may be negated: `x * -y`.
+fn factor<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    if eat(s, "-") {
+        let factor = factor(s)?;
+        return Ok(negate(s, factor));
+    }
+
+    power(s)
+}
+
+/// `x ^ y`, which is right-associative.
+fn power<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let base = prefix_application(s)?;
+    if !eat(s, "^") {
+        return Ok(base);
+    }
+
+    let exponent = factor(s)?;
+    Ok(call(s, system::Power, vec![base, exponent]))
+}
+
+/// `f @ x`, `f @@ x` and `f /@ x`, which are right-associative.
+fn prefix_application<S: Syntax>(s: &mut S) -> Result<S::Node, S::Error> {
+    let function = s.operand()?;
+
+    let head = if eat(s, "@") {
+        let arg = prefix_application(s)?;
+        return Ok(s.apply(function, vec![arg]));
+    } else if eat(s, "@@") {
+        system::Apply
+    } else if eat(s, "/@") {
+        system::Map
+    } else {
+        return Ok(function);
+    };
+
+    let arg = prefix_application(s)?;
+    Ok(call(s, head, vec![function, arg]))
+}
+
+//======================================
+// Helpers
+//======================================
+
+/// `head[elements...]`
+fn call<S: Syntax>(s: &S, head: SystemSymbol, elements: Vec<S::Node>) -> S::Node {
+    s.apply(s.symbol(head), elements)
+}
+
+fn at<S: Syntax>(s: &S, op: &str) -> bool {
+    s.peek_operator() == Some(op)
+}
+
+fn eat<S: Syntax>(s: &mut S, op: &str) -> bool {
+    let matched = at(s, op);
+    if matched {
+        s.skip_operator();
+    }
+    matched
+}
+
+/// `head[nodes...]`, or the single node if there is only one.
+fn flatten<S: Syntax>(s: &S, head: SystemSymbol, mut nodes: Vec<S::Node>) -> S::Node {
+    match nodes.len() {
+        1 => nodes.pop().unwrap(),
+        _ => call(s, head, nodes),
+    }
+}
+
+/// `-node`: a negative number, or `Times[-1, node]`.
+fn negate<S: Syntax>(s: &S, node: S::Node) -> S::Node {
+    match s.negate_number(&node) {
+        Some(negated) => negated,
+        None => call(s, system::Times, vec![s.integer(-1), node]),
+    }
+}
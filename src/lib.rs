@@ -4,9 +4,25 @@
 #![warn(missing_docs)]
 
 mod association;
+mod attributes;
+mod compile;
 mod conversion;
+mod derivative;
+mod escape;
+mod evaluate;
+#[doc(hidden)]
+pub mod grammar;
 mod number;
+mod numerical;
+mod options;
+mod order;
+mod part;
+mod polynomial;
+mod simplify;
 pub mod symbol;
+pub mod system;
+mod template;
+mod view;
 #[cfg(feature = "wxf")]
 mod wxf;
 
@@ -17,15 +33,29 @@ mod test_readme {
 }
 
 
-pub use self::association::Association;
-pub use self::number::{Number, F32, F64};
+pub use self::association::{Association, AssociationError, RuleKind};
+pub use self::attributes::{Attribute, AttributeTable, Attributes};
+pub use self::compile::{CompileError, CompiledFn};
+pub use self::evaluate::{
+    Builtin, Definition, Definitions, EvalError, Evaluator, FunctionError,
+};
+pub use self::number::{Complex, Number, RealFormat, F32, F64};
+pub use self::numerical::NumericError;
+pub use self::options::{OptionName, Options};
+pub use self::part::{PartError, PartSpec};
+pub use self::polynomial::{Polynomial, PolynomialError};
+pub use self::simplify::Simplifier;
+pub use self::template::{ExprTemplate, TemplateError};
+pub use self::view::{FailureRef, FunctionRef, ListRef, MissingRef, RuleRef, ViewError};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::mem;
 use std::sync::Arc;
 
 
 #[doc(inline)]
 pub use self::symbol::Symbol;
+pub use self::system::SystemSymbol;
 
 #[cfg(feature = "unstable_parse")]
 pub mod parse {
@@ -41,7 +71,7 @@ pub mod parse {
 /// ```
 /// use wolfram_expr::{Expr, Symbol};
 ///
-/// let expr = Expr::normal(Symbol::new("System`List"), vec![
+/// let expr = Expr::function(Symbol::new("System`List"), vec![
 ///     Expr::from(1),
 ///     Expr::from(2),
 ///     Expr::from(3)
@@ -52,7 +82,13 @@ pub mod parse {
 ///
 /// Internally, `Expr` is an atomically reference-counted [`ExprKind`]. This makes cloning
 /// an expression computationally inexpensive.
-#[derive(Clone, PartialEq, Eq, Hash)]
+///
+/// # Deeply nested expressions
+///
+/// Dropping, formatting, comparing, hashing and serializing an `Expr` do not recurse on
+/// the Rust call stack, so expressions with arbitrarily deep nesting (e.g.
+/// `f[f[f[...]]]` received from a kernel) can be processed without overflowing it.
+#[derive(Clone)]
 pub struct Expr {
     inner: Arc<ExprKind>,
 }
@@ -84,16 +120,19 @@ impl Expr {
     // 1, then performance is very good, but if the reference count is >1, a deeper clone
     // must be done.
     #[allow(clippy::wrong_self_convention)]
-    pub fn to_kind(self) -> ExprKind {
-        match Arc::try_unwrap(self.inner) {
-            Ok(kind) => kind,
-            Err(self_) => (*self_).clone(),
+    pub fn to_kind(mut self) -> ExprKind {
+        // NOTE: `Expr` implements `Drop`, so `self.inner` cannot be moved out of `self`
+        //       with `Arc::try_unwrap()`. Instead, swap a trivial placeholder into the
+        //       uniquely owned allocation, which `self` then drops cheaply.
+        match Arc::get_mut(&mut self.inner) {
+            Some(kind) => mem::replace(kind, ExprKind::Integer(0)),
+            None => (*self.inner).clone(),
         }
     }
 
     /// Get the [`ExprKind`] representing this expression.
     pub fn kind(&self) -> &ExprKind {
-        &*self.inner
+        &self.inner
     }
 
     /// Get mutable access to the [`ExprKind`] that represents this expression.
@@ -124,11 +163,15 @@ impl Expr {
         Self::normal(head.into(), contents)
     }
 
-    // TODO: Should Expr's be cached? Especially Symbol exprs? Would certainly save
-    //       a lot of allocations.
     /// Construct a new expression from a [`Symbol`].
+    ///
+    /// The symbols in the [`system`] module are cached, and constructing an expression
+    /// from one of them does not allocate.
     pub fn symbol(s: impl Into<Symbol>) -> Expr {
         let s = s.into();
+        if let Some(symbol) = SystemSymbol::lookup(s.as_str()) {
+            return symbol.expr().clone();
+        }
         Expr {
             inner: Arc::new(ExprKind::Symbol(s)),
         }
@@ -184,6 +227,7 @@ impl Expr {
             ExprKind::Integer(_) | ExprKind::Real(_) | ExprKind::String(_) => None,
             ExprKind::Normal(ref normal) => normal.head.tag(),
             ExprKind::Symbol(ref sym) => Some(sym.clone()),
+            ExprKind::Association(_) => Some(Symbol::from(system::Association)),
         }
     }
 
@@ -195,7 +239,8 @@ impl Expr {
             ExprKind::Symbol(_)
             | ExprKind::Integer(_)
             | ExprKind::Real(_)
-            | ExprKind::String(_) => None,
+            | ExprKind::String(_)
+            | ExprKind::Association(_) => None,
         }
     }
 
@@ -206,14 +251,16 @@ impl Expr {
     ///
     /// `index` is 0-based. The 0th index is the first element, not the head.
     ///
-    /// This function does not panic.
+    /// This function does not panic. See [`Expr::part()`] for access to parts using
+    /// Wolfram Language indexing conventions.
     pub fn normal_part(&self, index_0: usize) -> Option<&Expr> {
         match self.kind() {
             ExprKind::Normal(ref normal) => normal.contents.get(index_0),
             ExprKind::Symbol(_)
             | ExprKind::Integer(_)
             | ExprKind::Real(_)
-            | ExprKind::String(_) => None,
+            | ExprKind::String(_)
+            | ExprKind::Association(_) => None,
         }
     }
 
@@ -232,7 +279,7 @@ impl Expr {
     /// [`Null`](https://reference.wolfram.com/language/ref/Null.html) <sub>WL</sub>.
     #[inline]
     pub fn null() -> Expr {
-        Expr::symbol(unsafe { Symbol::unchecked_new("System`Null") })
+        Expr::from(system::Null)
     }
 
     //==================================
@@ -255,7 +302,7 @@ impl Expr {
     pub fn rule<LHS: Into<Expr>>(lhs: LHS, rhs: Expr) -> Expr {
         let lhs = lhs.into();
 
-        Expr::function("System`Rule", vec![lhs, rhs])
+        Expr::normal(system::Rule.into(), vec![lhs, rhs])
     }
     /// Construct a new `RuleDelayed[_, _]` expression from the left-hand side and right-hand
     /// side.
@@ -267,13 +314,13 @@ impl Expr {
     /// ```
     /// use wolfram_expr::{Expr, Symbol};
     ///
-    /// let delayed = Expr::rule(Symbol::new("Global`x"), Expr::normal(Symbol::new("System`RandomReal"), vec![]));
+    /// let delayed = Expr::rule(Symbol::new("Global`x"), Expr::function(Symbol::new("System`RandomReal"), vec![]));
     /// ```
     #[inline]
     pub fn rule_delayed<LHS: Into<Expr>>(lhs: LHS, rhs: Expr) -> Expr {
         let lhs = lhs.into();
 
-        Expr::function("System`RuleDelayed", vec![lhs, rhs])
+        Expr::normal(system::RuleDelayed.into(), vec![lhs, rhs])
     }
 
     /// Construct a new `List[...]`(`{...}`) expression from it's elements.
@@ -289,9 +336,12 @@ impl Expr {
     /// ```
     #[inline]
     pub fn list(elements: Vec<Expr>) -> Expr {
-        Expr::function("System`List", elements)
+        Expr::normal(system::List.into(), elements)
     }
-    /// Construct a new `Association[...]`(`<|...|>`) expression from it's elements.
+    /// Construct a new association (`<|...|>`) expression.
+    ///
+    /// The resulting expression is an [`ExprKind::Association`], which provides
+    /// constant-time key lookups through [`Expr::try_as_association()`].
     ///
     /// # Example
     ///
@@ -304,6 +354,8 @@ impl Expr {
     /// assoc.insert("a", Expr::from(1));
     /// assoc.insert_delayed("b", Expr::from(2));
     /// let expr = Expr::association(assoc);
+    ///
+    /// assert_eq!(expr.try_as_association().unwrap().get_str("a"), Some(&Expr::from(1)));
     /// ```
     #[inline]
     pub fn association(map: impl Into<Association>) -> Expr {
@@ -320,6 +372,7 @@ pub enum ExprKind<E = Expr> {
     String(String),
     Symbol(Symbol),
     Normal(Normal<E>),
+    Association(Association),
 }
 
 /// Wolfram Language "normal" expression: `f[...]`.
@@ -351,6 +404,24 @@ impl Normal {
         }
     }
 
+    /// Construct a new normal expression from the head and an iterator of elements.
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, Normal, Symbol};
+    ///
+    /// let elements = (1..=3).map(Expr::from);
+    /// let plus = Normal::from_elements(Symbol::new("System`Plus"), elements);
+    ///
+    /// assert_eq!(Expr::from(plus).to_string(), "System`Plus[1, 2, 3]");
+    /// ```
+    pub fn from_elements<E, I>(head: E, elements: I) -> Self
+    where
+        E: Into<Expr>,
+        I: IntoIterator<Item = Expr>,
+    {
+        Normal::new(head, elements.into_iter().collect())
+    }
+
     /// The head of this normal expression.
     pub fn head(&self) -> &Expr {
         &self.head
@@ -375,6 +446,127 @@ impl Normal {
     pub fn has_head(&self, sym: &Symbol) -> bool {
         self.head == *sym
     }
+
+    //==================================
+    // Mutation
+    //==================================
+
+    /// Mutable access to the head of this normal expression.
+    pub fn head_mut(&mut self) -> &mut Expr {
+        &mut self.head
+    }
+
+    /// Mutable access to the elements of this normal expression.
+    ///
+    /// Use [`Normal::push()`], [`Normal::insert()`], [`Normal::remove()`], etc. to change
+    /// the number of elements.
+    ///
+    /// # Example
+    ///
+    /// Increment every integer element of a uniquely owned expression in place:
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, ExprKind};
+    ///
+    /// let mut expr = Expr::list(vec![Expr::from(1), Expr::from(2)]);
+    ///
+    /// if let ExprKind::Normal(normal) = expr.kind_mut() {
+    ///     for elem in normal.elements_mut() {
+    ///         if let ExprKind::Integer(int) = elem.kind_mut() {
+    ///             *int += 1;
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(expr, Expr::list(vec![Expr::from(2), Expr::from(3)]));
+    /// ```
+    pub fn elements_mut(&mut self) -> &mut [Expr] {
+        &mut self.contents
+    }
+
+    /// Append `elem` after the last element of this expression.
+    pub fn push(&mut self, elem: Expr) {
+        self.contents.push(elem)
+    }
+
+    /// Remove the last element of this expression and return it, or `None` if there are
+    /// no elements.
+    pub fn pop(&mut self) -> Option<Expr> {
+        self.contents.pop()
+    }
+
+    /// Insert `elem` so that it has the 0-based position `index` in the elements of this
+    /// expression.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `index > self.elements().len()`.
+    pub fn insert(&mut self, index: usize, elem: Expr) {
+        self.contents.insert(index, elem)
+    }
+
+    /// Remove and return the element at the 0-based position `index`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Expr {
+        self.contents.remove(index)
+    }
+
+    /// Retain only the elements for which `keep` returns `true`.
+    pub fn retain<F: FnMut(&Expr) -> bool>(&mut self, keep: F) {
+        self.contents.retain(keep)
+    }
+
+    /// Replace the elements in `range` with the elements of `replace_with`, returning an
+    /// iterator over the removed elements.
+    ///
+    /// See [`Vec::splice()`] for details.
+    pub fn splice<R, I>(
+        &mut self,
+        range: R,
+        replace_with: I,
+    ) -> std::vec::Splice<'_, I::IntoIter>
+    where
+        R: std::ops::RangeBounds<usize>,
+        I: IntoIterator<Item = Expr>,
+    {
+        self.contents.splice(range, replace_with)
+    }
+}
+
+/// Append the elements of an iterator to a normal expression.
+///
+/// ```
+/// use wolfram_expr::{Expr, Normal, Symbol};
+///
+/// let mut normal = Normal::new(Symbol::new("System`Plus"), vec![Expr::from(1)]);
+/// normal.extend((2..=3).map(Expr::from));
+///
+/// assert_eq!(Expr::from(normal).to_string(), "System`Plus[1, 2, 3]");
+/// ```
+impl Extend<Expr> for Normal {
+    fn extend<I: IntoIterator<Item = Expr>>(&mut self, iter: I) {
+        self.contents.extend(iter)
+    }
+}
+
+/// Collect an iterator into a `List[...]` expression.
+///
+/// Use [`Normal::from_elements()`] to build a normal expression with a different head.
+///
+/// ```
+/// use wolfram_expr::{Expr, Normal};
+///
+/// let list: Normal = (1..=3).map(Expr::from).collect();
+///
+/// assert_eq!(Expr::from(list).to_string(), "System`List[1, 2, 3]");
+/// ```
+impl std::iter::FromIterator<Expr> for Normal {
+    fn from_iter<I: IntoIterator<Item = Expr>>(iter: I) -> Self {
+        Normal::new(system::List, iter.into_iter().collect())
+    }
 }
 
 //=======================================
@@ -394,26 +586,77 @@ impl fmt::Debug for Expr {
 /// literals needing precision and accuracy marks will have them.
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.inner)
+        fmt::Display::fmt(self.kind(), f)
     }
 }
 
 impl fmt::Display for ExprKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            ExprKind::Normal(ref normal) => fmt::Display::fmt(normal, f),
-            ExprKind::Integer(ref int) => fmt::Display::fmt(int, f),
-            ExprKind::Real(ref real) => fmt::Display::fmt(real, f),
-            ExprKind::String(ref string) => {
-                // Escape any '"' which appear in the string.
-                // Using the Debug implementation will cause \n, \t, etc. to appear in
-                // place of the literal character they are escapes for. This is necessary
-                // when printing expressions in a way that they can be read back in as a
-                // string, such as with ToExpression.
-                write!(f, "{:?}", string)
-            },
-            ExprKind::Symbol(ref symbol) => fmt::Display::fmt(symbol, f),
+        /// Pending piece of output. `Normal` expressions are expanded into a sequence of
+        /// these, so that nesting depth does not translate into call stack depth.
+        enum Item<'e> {
+            Kind(&'e ExprKind),
+            Str(&'static str),
+        }
+
+        // Matches the non-alternate `Display` of `Association`.
+        fn push_rules<'e>(assoc: &'e Association, stack: &mut Vec<Item<'e>>) {
+            stack.push(Item::Str("|>"));
+            let rules: Vec<_> = assoc.rules().collect();
+            for (idx, (key, kind, value)) in rules.into_iter().enumerate().rev() {
+                stack.push(Item::Kind(value.kind()));
+                stack.push(Item::Str(match kind {
+                    RuleKind::Rule => " -> ",
+                    RuleKind::RuleDelayed => " :> ",
+                }));
+                stack.push(Item::Kind(key.kind()));
+                if idx != 0 {
+                    stack.push(Item::Str(", "));
+                }
+            }
+            stack.push(Item::Str("<|"));
         }
+
+        let mut stack = vec![Item::Kind(self)];
+
+        while let Some(item) = stack.pop() {
+            let kind = match item {
+                Item::Kind(kind) => kind,
+                Item::Str(str) => {
+                    f.write_str(str)?;
+                    continue;
+                },
+            };
+
+            match *kind {
+                ExprKind::Normal(ref normal) => {
+                    stack.push(Item::Str("]"));
+                    for (idx, elem) in normal.contents.iter().enumerate().rev() {
+                        stack.push(Item::Kind(elem.kind()));
+                        if idx != 0 {
+                            stack.push(Item::Str(", "));
+                        }
+                    }
+                    stack.push(Item::Str("["));
+                    stack.push(Item::Kind(normal.head.kind()));
+                },
+                ExprKind::Integer(ref int) => fmt::Display::fmt(int, f)?,
+                ExprKind::Real(ref real) => {
+                    fmt::Display::fmt(&RealFormat::input_form().display(**real), f)?
+                },
+                ExprKind::String(ref string) => {
+                    // Escape any '"' which appear in the string, and use WL escape
+                    // sequences for special and non-ASCII characters. This is necessary
+                    // when printing expressions in a way that they can be read back in
+                    // as a string, such as with ToExpression.
+                    fmt::Display::fmt(&escape::Escaped(string), f)?
+                },
+                ExprKind::Symbol(ref symbol) => fmt::Display::fmt(symbol, f)?,
+                ExprKind::Association(ref assoc) => push_rules(assoc, &mut stack),
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -442,18 +685,164 @@ impl fmt::Display for Number {
             Number::Integer(ref int) => write!(f, "{}", int),
             Number::Real(ref real) => {
                 // Make sure we're not printing NotNan (which surprisingly implements
-                // Display)
-                let real: f64 = **real;
-                write!(f, "{:?}", real)
+                // Display, without the trailing `.` of whole numbers)
+                write!(f, "{}", RealFormat::input_form().display(**real))
             },
         }
     }
 }
 
+//======================================
+// Drop impl
+//======================================
+
+impl Drop for Expr {
+    fn drop(&mut self) {
+        // Fast path: only uniquely owned normal expressions and associations can free
+        // children.
+        match Arc::get_mut(&mut self.inner) {
+            Some(ExprKind::Normal(_) | ExprKind::Association(_)) => (),
+            _ => return,
+        }
+
+        // Move the children of every uniquely owned normal expression onto an explicit
+        // stack instead of letting the compiler-generated drop glue recurse into them.
+        // Each `Expr` popped off the stack has had its own children removed by the time
+        // it is dropped, so the nested `Expr::drop()` calls are always shallow.
+        fn take_children(expr: &mut Expr, stack: &mut Vec<Expr>) {
+            let kind = match Arc::get_mut(&mut expr.inner) {
+                Some(kind @ (ExprKind::Normal(_) | ExprKind::Association(_))) => kind,
+                _ => return,
+            };
+
+            match mem::replace(kind, ExprKind::Integer(0)) {
+                ExprKind::Normal(Normal { head, contents }) => {
+                    stack.push(head);
+                    stack.extend(contents);
+                },
+                ExprKind::Association(mut assoc) => {
+                    for (key, (_, value)) in assoc.drain(..) {
+                        stack.push(key);
+                        stack.push(value);
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        let mut stack: Vec<Expr> = Vec::new();
+
+        take_children(self, &mut stack);
+
+        while let Some(mut expr) = stack.pop() {
+            take_children(&mut expr, &mut stack);
+        }
+    }
+}
+
 //======================================
 // Comparision trait impls
 //======================================
 
+impl PartialEq for Expr {
+    fn eq(&self, other: &Expr) -> bool {
+        let mut stack: Vec<(&Expr, &Expr)> = vec![(self, other)];
+
+        while let Some((lhs, rhs)) = stack.pop() {
+            if Arc::ptr_eq(&lhs.inner, &rhs.inner) {
+                continue;
+            }
+
+            match (lhs.kind(), rhs.kind()) {
+                (ExprKind::Normal(lhs), ExprKind::Normal(rhs)) => {
+                    if lhs.contents.len() != rhs.contents.len() {
+                        return false;
+                    }
+                    stack.push((&lhs.head, &rhs.head));
+                    stack.extend(lhs.contents.iter().zip(rhs.contents.iter()));
+                },
+                (ExprKind::Integer(lhs), ExprKind::Integer(rhs)) => {
+                    if lhs != rhs {
+                        return false;
+                    }
+                },
+                (ExprKind::Real(lhs), ExprKind::Real(rhs)) => {
+                    if lhs != rhs {
+                        return false;
+                    }
+                },
+                (ExprKind::String(lhs), ExprKind::String(rhs)) => {
+                    if lhs != rhs {
+                        return false;
+                    }
+                },
+                (ExprKind::Symbol(lhs), ExprKind::Symbol(rhs)) => {
+                    if lhs != rhs {
+                        return false;
+                    }
+                },
+                (ExprKind::Association(lhs), ExprKind::Association(rhs)) => {
+                    if lhs.len() != rhs.len() {
+                        return false;
+                    }
+                    for ((lkey, (ldelayed, lvalue)), (rkey, (rdelayed, rvalue))) in
+                        lhs.iter().zip(rhs.iter())
+                    {
+                        if ldelayed != rdelayed {
+                            return false;
+                        }
+                        stack.push((lkey, rkey));
+                        stack.push((lvalue, rvalue));
+                    }
+                },
+                (ExprKind::Normal(_), _)
+                | (ExprKind::Integer(_), _)
+                | (ExprKind::Real(_), _)
+                | (ExprKind::String(_), _)
+                | (ExprKind::Symbol(_), _)
+                | (ExprKind::Association(_), _) => return false,
+            }
+        }
+
+        true
+    }
+}
+
+impl Eq for Expr {}
+
+impl Hash for Expr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut stack: Vec<&Expr> = vec![self];
+
+        while let Some(expr) = stack.pop() {
+            let kind = expr.kind();
+            mem::discriminant(kind).hash(state);
+
+            match kind {
+                ExprKind::Normal(normal) => {
+                    normal.contents.len().hash(state);
+                    stack.extend(normal.contents.iter().rev());
+                    stack.push(&normal.head);
+                },
+                ExprKind::Integer(int) => int.hash(state),
+                ExprKind::Real(real) => real.hash(state),
+                ExprKind::String(string) => string.hash(state),
+                ExprKind::Symbol(symbol) => symbol.hash(state),
+                ExprKind::Association(assoc) => {
+                    assoc.len().hash(state);
+                    for (_, (delayed, _)) in assoc.iter() {
+                        delayed.hash(state);
+                    }
+                    for (key, (_, value)) in assoc.iter().rev() {
+                        stack.push(value);
+                        stack.push(key);
+                    }
+                },
+            }
+        }
+    }
+}
+
 impl PartialEq<Symbol> for Expr {
     fn eq(&self, other: &Symbol) -> bool {
         match self.kind() {
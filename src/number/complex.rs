@@ -0,0 +1,300 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use super::RealFormat;
+
+/// Complex number with machine-precision real and imaginary parts.
+///
+/// The elementary functions on `Complex` use the same principal branches as the
+/// corresponding Wolfram Language functions.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    /// The real part.
+    pub re: f64,
+    /// The imaginary part.
+    pub im: f64,
+}
+
+impl Complex {
+    /// The imaginary unit,
+    /// [`I`](https://reference.wolfram.com/language/ref/I.html) <sub>WL</sub>.
+    pub const I: Complex = Complex { re: 0.0, im: 1.0 };
+
+    /// Construct the complex number `re + im I`.
+    pub const fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    /// Returns `true` if the imaginary part of this number is zero.
+    pub fn is_real(&self) -> bool {
+        self.im == 0.0
+    }
+
+    /// Returns `true` if neither part of this number is infinite or NaN.
+    pub fn is_finite(&self) -> bool {
+        self.re.is_finite() && self.im.is_finite()
+    }
+
+    /// The absolute value (modulus) of this number.
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// The argument (phase angle) of this number, in the interval `(-π, π]`.
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    /// The complex conjugate of this number.
+    pub fn conj(self) -> Complex {
+        Complex::new(self.re, -self.im)
+    }
+
+    //==================================
+    // Elementary functions
+    //==================================
+
+    /// `e^self`
+    pub fn exp(self) -> Complex {
+        if self.is_real() {
+            return Complex::from(self.re.exp());
+        }
+        let scale = self.re.exp();
+        Complex::new(scale * self.im.cos(), scale * self.im.sin())
+    }
+
+    /// The principal natural logarithm of this number.
+    pub fn ln(self) -> Complex {
+        if self.is_real() && self.re >= 0.0 {
+            return Complex::from(self.re.ln());
+        }
+        Complex::new(self.abs().ln(), self.arg())
+    }
+
+    /// The principal square root of this number.
+    pub fn sqrt(self) -> Complex {
+        if self.is_real() && self.re >= 0.0 {
+            return Complex::from(self.re.sqrt());
+        }
+        if self.re == 0.0 && self.im == 0.0 {
+            return Complex::default();
+        }
+
+        let t = ((self.abs() + self.re.abs()) / 2.0).sqrt();
+        if self.re >= 0.0 {
+            Complex::new(t, self.im / (2.0 * t))
+        } else {
+            Complex::new(self.im.abs() / (2.0 * t), t.copysign(self.im))
+        }
+    }
+
+    /// `self^exp`, using the principal branch of the logarithm.
+    pub fn pow(self, exp: Complex) -> Complex {
+        if exp.is_real() {
+            let is_integer = exp.re.fract() == 0.0;
+            if self.is_real() && (self.re >= 0.0 || is_integer) {
+                return Complex::from(self.re.powf(exp.re));
+            }
+            if is_integer && exp.re.abs() <= f64::from(i32::MAX) {
+                return self.powi(exp.re as i32);
+            }
+        }
+
+        if self.re == 0.0 && self.im == 0.0 {
+            return match exp.re > 0.0 {
+                true => Complex::default(),
+                false => Complex::new(f64::INFINITY, 0.0),
+            };
+        }
+
+        (exp * self.ln()).exp()
+    }
+
+    fn powi(self, exp: i32) -> Complex {
+        let mut result = Complex::from(1.0);
+        let mut base = self;
+        let mut n = exp.unsigned_abs();
+
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            n >>= 1;
+        }
+
+        match exp < 0 {
+            true => Complex::from(1.0) / result,
+            false => result,
+        }
+    }
+
+    /// The sine of this number.
+    pub fn sin(self) -> Complex {
+        if self.is_real() {
+            return Complex::from(self.re.sin());
+        }
+        Complex::new(
+            self.re.sin() * self.im.cosh(),
+            self.re.cos() * self.im.sinh(),
+        )
+    }
+
+    /// The cosine of this number.
+    pub fn cos(self) -> Complex {
+        if self.is_real() {
+            return Complex::from(self.re.cos());
+        }
+        Complex::new(
+            self.re.cos() * self.im.cosh(),
+            -self.re.sin() * self.im.sinh(),
+        )
+    }
+
+    /// The tangent of this number.
+    pub fn tan(self) -> Complex {
+        if self.is_real() {
+            return Complex::from(self.re.tan());
+        }
+        self.sin() / self.cos()
+    }
+
+    /// The hyperbolic sine of this number.
+    pub fn sinh(self) -> Complex {
+        if self.is_real() {
+            return Complex::from(self.re.sinh());
+        }
+        Complex::new(
+            self.re.sinh() * self.im.cos(),
+            self.re.cosh() * self.im.sin(),
+        )
+    }
+
+    /// The hyperbolic cosine of this number.
+    pub fn cosh(self) -> Complex {
+        if self.is_real() {
+            return Complex::from(self.re.cosh());
+        }
+        Complex::new(
+            self.re.cosh() * self.im.cos(),
+            self.re.sinh() * self.im.sin(),
+        )
+    }
+
+    /// The hyperbolic tangent of this number.
+    pub fn tanh(self) -> Complex {
+        if self.is_real() {
+            return Complex::from(self.re.tanh());
+        }
+        self.sinh() / self.cosh()
+    }
+
+    /// The principal inverse sine of this number.
+    pub fn asin(self) -> Complex {
+        if self.is_real() && self.re.abs() <= 1.0 {
+            return Complex::from(self.re.asin());
+        }
+        // -i ln(i z + sqrt(1 - z^2))
+        let root = (Complex::from(1.0) - self * self).sqrt();
+        -Complex::I * (Complex::I * self + root).ln()
+    }
+
+    /// The principal inverse cosine of this number.
+    pub fn acos(self) -> Complex {
+        if self.is_real() && self.re.abs() <= 1.0 {
+            return Complex::from(self.re.acos());
+        }
+        Complex::from(std::f64::consts::FRAC_PI_2) - self.asin()
+    }
+
+    /// The principal inverse tangent of this number.
+    pub fn atan(self) -> Complex {
+        if self.is_real() {
+            return Complex::from(self.re.atan());
+        }
+        // (i / 2) (ln(1 - i z) - ln(1 + i z))
+        let iz = Complex::I * self;
+        let one = Complex::from(1.0);
+        Complex::new(0.0, 0.5) * ((one - iz).ln() - (one + iz).ln())
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Complex {
+        Complex::new(re, 0.0)
+    }
+}
+
+//======================================
+// Arithmetic
+//======================================
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, rhs: Complex) -> Complex {
+        if self.is_real() && rhs.is_real() {
+            return Complex::from(self.re * rhs.re);
+        }
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+
+    fn div(self, rhs: Complex) -> Complex {
+        if rhs.is_real() {
+            return Complex::new(self.re / rhs.re, self.im / rhs.re);
+        }
+        let denom = rhs.re * rhs.re + rhs.im * rhs.im;
+        Complex::new(
+            (self.re * rhs.re + self.im * rhs.im) / denom,
+            (self.im * rhs.re - self.re * rhs.im) / denom,
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+/// Formats the number as `re + im I`, or just `re` if the imaginary part is zero, with
+/// the parts written in [`RealFormat::input_form()`].
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = RealFormat::input_form();
+        let re = format.display(self.re);
+
+        if self.is_real() {
+            write!(f, "{}", re)
+        } else if self.im < 0.0 {
+            write!(f, "{} - {} I", re, format.display(-self.im))
+        } else {
+            write!(f, "{} + {} I", re, format.display(self.im))
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+/// Formatting options for machine reals in Wolfram Language syntax.
+///
+/// Reals are written with the fewest digits that read back as the same `f64`, with a
+/// trailing `.` for whole numbers, and with a `*^` exponent if they are smaller than
+/// `1*^-5` or at least `1*^6` in magnitude:
+///
+/// ```
+/// use wolfram_expr::RealFormat;
+///
+/// let input_form = RealFormat::input_form();
+///
+/// assert_eq!(input_form.display(1500.0).to_string(), "1500.");
+/// assert_eq!(input_form.display(0.1).to_string(), "0.1");
+/// assert_eq!(input_form.display(1e-7).to_string(), "1.*^-7");
+/// assert_eq!(input_form.display(-2.5e300).to_string(), "-2.5*^300");
+///
+/// assert_eq!(RealFormat::full_form().display(0.1).to_string(), "0.1`");
+/// assert_eq!(
+///     input_form.number_marks(true).display(1e-7).to_string(),
+///     "1.`*^-7"
+/// );
+/// ```
+///
+/// Infinite values, which have no literal syntax, are written as
+/// ``System`DirectedInfinity[1]`` and ``System`DirectedInfinity[-1]``, and NaN is
+/// written as ``System`Indeterminate``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RealFormat {
+    number_marks: bool,
+}
+
+impl RealFormat {
+    /// The format of
+    /// [`InputForm`](https://reference.wolfram.com/language/ref/InputForm.html)
+    /// <sub>WL</sub>, which omits the `` ` `` machine precision mark by default.
+    ///
+    /// This is the format used by the `Display` impls of [`Expr`][crate::Expr] and
+    /// [`Number`][crate::Number].
+    pub const fn input_form() -> Self {
+        RealFormat {
+            number_marks: false,
+        }
+    }
+
+    /// The format of
+    /// [`FullForm`](https://reference.wolfram.com/language/ref/FullForm.html)
+    /// <sub>WL</sub>, which includes the `` ` `` machine precision mark by default.
+    pub const fn full_form() -> Self {
+        RealFormat { number_marks: true }
+    }
+
+    /// Set whether a `` ` `` mark is written after the digits of a real to indicate
+    /// that it has machine precision, as with the
+    /// [`NumberMarks`](https://reference.wolfram.com/language/ref/NumberMarks.html)
+    /// <sub>WL</sub> option.
+    pub const fn number_marks(self, number_marks: bool) -> Self {
+        RealFormat { number_marks }
+    }
+
+    /// Returns a value which implements [`Display`][fmt::Display] by writing `real` in
+    /// this format.
+    pub fn display(self, real: f64) -> impl fmt::Display {
+        DisplayReal {
+            real,
+            format: self,
+        }
+    }
+}
+
+impl Default for RealFormat {
+    fn default() -> Self {
+        RealFormat::input_form()
+    }
+}
+
+struct DisplayReal {
+    real: f64,
+    format: RealFormat,
+}
+
+impl fmt::Display for DisplayReal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let DisplayReal { real, format } = *self;
+
+        if real.is_infinite() {
+            let direction = if real > 0.0 { 1 } else { -1 };
+            return write!(f, "System`DirectedInfinity[{}]", direction);
+        }
+        if real.is_nan() {
+            return f.write_str("System`Indeterminate");
+        }
+
+        // `LowerExp` writes the shortest digits which round-trip, e.g. `-1.25e-7`.
+        let scientific = format!("{:e}", real);
+        let (mantissa, exponent) = scientific
+            .split_once('e')
+            .expect("LowerExp output has an exponent");
+        let exponent: i32 = exponent.parse().expect("LowerExp exponent is an integer");
+
+        let (sign, mantissa) = match mantissa.strip_prefix('-') {
+            Some(mantissa) => ("-", mantissa),
+            None => ("", mantissa),
+        };
+        let digits = mantissa.replace('.', "");
+        let mark = if format.number_marks { "`" } else { "" };
+
+        f.write_str(sign)?;
+
+        match exponent {
+            0..=5 => {
+                let integer_len = exponent as usize + 1;
+                if digits.len() > integer_len {
+                    let (integer, fraction) = digits.split_at(integer_len);
+                    write!(f, "{}.{}", integer, fraction)?;
+                } else {
+                    let zeros = integer_len - digits.len();
+                    write!(f, "{}{}.", digits, "0".repeat(zeros))?;
+                }
+                f.write_str(mark)
+            },
+            -5..=-1 => {
+                let zeros = "0".repeat((-exponent - 1) as usize);
+                write!(f, "0.{}{}{}", zeros, digits, mark)
+            },
+            _ => {
+                let (first, rest) = digits.split_at(1);
+                write!(f, "{}.{}{}*^{}", first, rest, mark, exponent)
+            },
+        }
+    }
+}
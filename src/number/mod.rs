@@ -1,3 +1,13 @@
+mod complex;
+mod format;
+mod numeric;
+mod rational;
+
+pub use self::complex::Complex;
+pub use self::format::RealFormat;
+pub(crate) use self::numeric::Numeric;
+pub(crate) use self::rational::Rational;
+
 /// 64-bit floating-point real number. Not NaN.
 pub type F64 = ordered_float::NotNan<f64>;
 /// 32-bit floating-point real number. Not NaN.
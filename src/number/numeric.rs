@@ -0,0 +1,225 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+use crate::{number::Rational, system, Expr, ExprKind, Normal};
+
+/// An exact or machine-precision real number, as represented by an integer, a
+/// `Rational[n, d]` expression, or a machine real.
+///
+/// Arithmetic on two exact numbers is exact, and arithmetic involving a machine real
+/// produces a machine real, matching the Wolfram Language. Exact operations return
+/// `None` if the result does not fit in 64-bit integers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Numeric {
+    /// Exact number. Integers are represented by a [`Rational`] with denominator `1`.
+    Exact(Rational),
+    /// Machine real. Never NaN.
+    Real(f64),
+}
+
+impl Numeric {
+    pub(crate) const ZERO: Numeric = Numeric::Exact(Rational::ZERO);
+    pub(crate) const ONE: Numeric = Numeric::Exact(Rational::ONE);
+
+    pub(crate) fn integer(value: i64) -> Numeric {
+        Numeric::Exact(Rational::from_integer(value))
+    }
+
+    /// Interpret `expr` as a number.
+    ///
+    /// Returns `None` if `expr` is not an integer, a real, or a `Rational[n, d]`
+    /// expression with integer `n` and non-zero `d`.
+    ///
+    /// Infinite reals are accepted, but arithmetic which produces a non-finite real
+    /// returns `None`.
+    pub(crate) fn from_expr(expr: &Expr) -> Option<Numeric> {
+        match expr.kind() {
+            ExprKind::Integer(value) => Some(Numeric::integer(*value)),
+            ExprKind::Real(value) => Some(Numeric::Real(**value)),
+            ExprKind::Normal(normal) => {
+                Numeric::from_rational(normal).map(Numeric::Exact)
+            },
+            ExprKind::String(_) | ExprKind::Symbol(_) | ExprKind::Association(_) => None,
+        }
+    }
+
+    /// Interpret `normal` as `Rational[n, d]`.
+    pub(crate) fn from_rational(normal: &Normal) -> Option<Rational> {
+        match normal.head.try_as_symbol() {
+            Some(head) if head.as_str() == "System`Rational" => (),
+            _ => return None,
+        }
+
+        match normal.elements() {
+            [numer, denom] => match (numer.kind(), denom.kind()) {
+                (ExprKind::Integer(numer), ExprKind::Integer(denom)) => {
+                    Rational::new(*numer, *denom)
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Convert this number to an expression: an integer, a `Rational[n, d]`
+    /// expression, or a real.
+    pub(crate) fn to_expr(self) -> Expr {
+        match self {
+            Numeric::Exact(value) if value.is_integer() => Expr::from(value.numer()),
+            Numeric::Exact(value) => Expr::function(system::Rational, vec![
+                Expr::from(value.numer()),
+                Expr::from(value.denom()),
+            ]),
+            Numeric::Real(value) => Expr::real(value),
+        }
+    }
+
+    pub(crate) fn to_f64(self) -> f64 {
+        match self {
+            Numeric::Exact(value) => value.to_f64(),
+            Numeric::Real(value) => value,
+        }
+    }
+
+    /// Get the value of this number if it is an exact integer.
+    pub(crate) fn as_integer(self) -> Option<i64> {
+        match self {
+            Numeric::Exact(value) if value.is_integer() => Some(value.numer()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_real(self) -> bool {
+        matches!(self, Numeric::Real(_))
+    }
+
+    /// Returns `true` for exact or inexact zero.
+    pub(crate) fn is_zero(self) -> bool {
+        self.to_f64() == 0.0
+    }
+
+    /// Returns `true` for exact one only.
+    pub(crate) fn is_one(self) -> bool {
+        self == Numeric::ONE
+    }
+
+    pub(crate) fn is_negative(self) -> bool {
+        self.to_f64() < 0.0
+    }
+
+    //==================================
+    // Arithmetic
+    //==================================
+
+    pub(crate) fn checked_add(self, other: Numeric) -> Option<Numeric> {
+        match (self, other) {
+            (Numeric::Exact(a), Numeric::Exact(b)) => {
+                a.checked_add(b).map(Numeric::Exact)
+            },
+            (a, b) => Numeric::real(a.to_f64() + b.to_f64()),
+        }
+    }
+
+    pub(crate) fn checked_sub(self, other: Numeric) -> Option<Numeric> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    pub(crate) fn checked_mul(self, other: Numeric) -> Option<Numeric> {
+        match (self, other) {
+            (Numeric::Exact(a), Numeric::Exact(b)) => {
+                a.checked_mul(b).map(Numeric::Exact)
+            },
+            (a, b) => Numeric::real(a.to_f64() * b.to_f64()),
+        }
+    }
+
+    /// Returns `None` if `other` is zero.
+    pub(crate) fn checked_div(self, other: Numeric) -> Option<Numeric> {
+        if other.is_zero() {
+            return None;
+        }
+        match (self, other) {
+            (Numeric::Exact(a), Numeric::Exact(b)) => {
+                a.checked_div(b).map(Numeric::Exact)
+            },
+            (a, b) => Numeric::real(a.to_f64() / b.to_f64()),
+        }
+    }
+
+    pub(crate) fn checked_neg(self) -> Option<Numeric> {
+        match self {
+            Numeric::Exact(value) => value.checked_neg().map(Numeric::Exact),
+            Numeric::Real(value) => Some(Numeric::Real(-value)),
+        }
+    }
+
+    /// Raise this number to the power `exp`.
+    ///
+    /// Returns `None` if the result is not a real number, e.g. `(-1)^(1/2)`, if the
+    /// result is an irrational number which cannot be represented exactly, e.g.
+    /// `2^(1/2)`, or if `self` is zero and `exp` is not positive.
+    pub(crate) fn checked_pow(self, exp: Numeric) -> Option<Numeric> {
+        if self.is_zero() && exp.to_f64() <= 0.0 {
+            return None;
+        }
+
+        match (self, exp) {
+            (Numeric::Exact(base), Numeric::Exact(exp)) if exp.is_integer() => {
+                base.checked_pow(exp.numer()).map(Numeric::Exact)
+            },
+            (Numeric::Exact(base), Numeric::Exact(exp)) => {
+                let root = base.root(exp.denom())?;
+                root.checked_pow(exp.numer()).map(Numeric::Exact)
+            },
+            (Numeric::Real(base), Numeric::Exact(exp)) if exp.is_integer() => {
+                let exp = i32::try_from(exp.numer()).ok()?;
+                Numeric::real(base.powi(exp))
+            },
+            (base, exp) => {
+                // A negative number to a fractional power is complex.
+                if base.is_negative() && exp.to_f64().fract() != 0.0 {
+                    return None;
+                }
+                Numeric::real(base.to_f64().powf(exp.to_f64()))
+            },
+        }
+    }
+
+    /// The largest integer less than or equal to this number.
+    pub(crate) fn floor(self) -> Option<i64> {
+        match self {
+            Numeric::Exact(value) => Some(value.floor()),
+            Numeric::Real(value) => {
+                let floor = value.floor();
+                // `as` saturates, so check that the value is in range first.
+                if floor >= i64::MIN as f64 && floor < i64::MAX as f64 {
+                    Some(floor as i64)
+                } else {
+                    None
+                }
+            },
+        }
+    }
+
+    fn real(value: f64) -> Option<Numeric> {
+        if value.is_finite() {
+            Some(Numeric::Real(value))
+        } else {
+            None
+        }
+    }
+}
+
+// Machine reals are never NaN, so equality is reflexive.
+impl Eq for Numeric {}
+
+impl PartialOrd for Numeric {
+    fn partial_cmp(&self, other: &Numeric) -> Option<Ordering> {
+        match (self, other) {
+            (Numeric::Exact(a), Numeric::Exact(b)) => Some(a.cmp(b)),
+            (Numeric::Exact(a), Numeric::Real(b)) => a.cmp_f64(*b),
+            (Numeric::Real(a), Numeric::Exact(b)) => b.cmp_f64(*a).map(Ordering::reverse),
+            (Numeric::Real(a), Numeric::Real(b)) => a.partial_cmp(b),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+/// Exact rational number `numer / denom`, always in lowest terms with `denom > 0`.
+///
+/// All arithmetic is checked: operations whose result does not fit in an `i64`
+/// numerator or denominator return `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Rational {
+    numer: i64,
+    denom: i64,
+}
+
+impl Rational {
+    pub(crate) const ZERO: Rational = Rational { numer: 0, denom: 1 };
+    pub(crate) const ONE: Rational = Rational { numer: 1, denom: 1 };
+
+    /// Construct the rational number `numer / denom`, reduced to lowest terms.
+    ///
+    /// Returns `None` if `denom` is zero, or if the reduced number does not fit in
+    /// `i64`s.
+    pub(crate) fn new(numer: i64, denom: i64) -> Option<Rational> {
+        Rational::from_i128(i128::from(numer), i128::from(denom))
+    }
+
+    pub(crate) fn from_integer(value: i64) -> Rational {
+        Rational {
+            numer: value,
+            denom: 1,
+        }
+    }
+
+    fn from_i128(numer: i128, denom: i128) -> Option<Rational> {
+        if denom == 0 {
+            return None;
+        }
+
+        let gcd = gcd(numer.unsigned_abs(), denom.unsigned_abs()) as i128;
+        let (mut numer, mut denom) = (numer / gcd, denom / gcd);
+
+        if denom < 0 {
+            numer = -numer;
+            denom = -denom;
+        }
+
+        Some(Rational {
+            numer: i64::try_from(numer).ok()?,
+            denom: i64::try_from(denom).ok()?,
+        })
+    }
+
+    pub(crate) fn numer(&self) -> i64 {
+        self.numer
+    }
+
+    pub(crate) fn denom(&self) -> i64 {
+        self.denom
+    }
+
+    pub(crate) fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    pub(crate) fn is_negative(&self) -> bool {
+        self.numer < 0
+    }
+
+    pub(crate) fn to_f64(self) -> f64 {
+        self.numer as f64 / self.denom as f64
+    }
+
+    //==================================
+    // Arithmetic
+    //==================================
+
+    pub(crate) fn checked_add(self, other: Rational) -> Option<Rational> {
+        let (a, b) = (self.wide(), other.wide());
+        Rational::from_i128(a.0 * b.1 + b.0 * a.1, a.1 * b.1)
+    }
+
+    pub(crate) fn checked_mul(self, other: Rational) -> Option<Rational> {
+        let (a, b) = (self.wide(), other.wide());
+        Rational::from_i128(a.0 * b.0, a.1 * b.1)
+    }
+
+    /// Returns `None` if `other` is zero.
+    pub(crate) fn checked_div(self, other: Rational) -> Option<Rational> {
+        let (a, b) = (self.wide(), other.wide());
+        Rational::from_i128(a.0 * b.1, a.1 * b.0)
+    }
+
+    pub(crate) fn checked_neg(self) -> Option<Rational> {
+        Some(Rational {
+            numer: self.numer.checked_neg()?,
+            denom: self.denom,
+        })
+    }
+
+    /// Returns `None` if this number is zero.
+    pub(crate) fn checked_recip(self) -> Option<Rational> {
+        Rational::ONE.checked_div(self)
+    }
+
+    /// Raise this number to an integer power.
+    ///
+    /// Returns `None` if `exp` is negative and this number is zero.
+    pub(crate) fn checked_pow(self, exp: i64) -> Option<Rational> {
+        let base = if exp < 0 { self.checked_recip()? } else { self };
+        let exp = u32::try_from(exp.unsigned_abs()).ok()?;
+
+        Some(Rational {
+            numer: base.numer.checked_pow(exp)?,
+            denom: base.denom.checked_pow(exp)?,
+        })
+    }
+
+    /// The largest integer less than or equal to this number.
+    pub(crate) fn floor(self) -> i64 {
+        self.numer.div_euclid(self.denom)
+    }
+
+    /// The exact `n`-th root of this number, if it is rational.
+    ///
+    /// Returns `None` for negative numbers.
+    pub(crate) fn root(self, n: i64) -> Option<Rational> {
+        if self.is_negative() || n <= 0 {
+            return None;
+        }
+
+        Some(Rational {
+            numer: integer_root(self.numer, n)?,
+            denom: integer_root(self.denom, n)?,
+        })
+    }
+
+    fn wide(self) -> (i128, i128) {
+        (i128::from(self.numer), i128::from(self.denom))
+    }
+
+    /// Compare this number with `real` exactly, without converting either one to the
+    /// type of the other. Returns `None` if `real` is NaN.
+    pub(crate) fn cmp_f64(self, real: f64) -> Option<Ordering> {
+        if real.is_nan() {
+            return None;
+        }
+        if real.is_infinite() {
+            return Some(if real > 0.0 { Ordering::Less } else { Ordering::Greater });
+        }
+
+        let real_sign = if real > 0.0 {
+            1
+        } else if real < 0.0 {
+            -1
+        } else {
+            0
+        };
+        let sign = self.numer.signum();
+        if sign != real_sign || sign == 0 {
+            return Some(sign.cmp(&real_sign));
+        }
+
+        let numer = u128::from(self.numer.unsigned_abs());
+        let denom = u128::from(self.denom.unsigned_abs());
+
+        // |real| is exactly `mantissa * 2^exponent`, with `mantissa < 2^53`.
+        let (mantissa, exponent) = decode_f64(real.abs());
+        let magnitude = if exponent >= 0 {
+            // |self| < 2^63, while |real| >= 2^75.
+            if exponent > 74 {
+                Ordering::Less
+            } else {
+                compare_fractions(numer, denom, mantissa << exponent, 1)
+            }
+        } else {
+            // |self| >= 1 / 2^63, while |real| < 2^-74.
+            if exponent < -126 {
+                Ordering::Greater
+            } else {
+                compare_fractions(numer, denom, mantissa, 1 << -exponent)
+            }
+        };
+
+        Some(if sign < 0 { magnitude.reverse() } else { magnitude })
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        let (a, b) = (self.wide(), other.wide());
+        // Denominators are positive, so cross-multiplying preserves the order.
+        (a.0 * b.1).cmp(&(b.0 * a.1))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let rem = a % b;
+        a = b;
+        b = rem;
+    }
+    // gcd(0, 0) is 0, but 1 is the more useful value when reducing 0 / d.
+    a.max(1)
+}
+
+/// Compare `a / b` with `c / d`, where `b` and `d` are non-zero.
+///
+/// This compares the continued fraction expansions of the two fractions, so unlike
+/// cross-multiplying, it cannot overflow.
+fn compare_fractions(mut a: u128, mut b: u128, mut c: u128, mut d: u128) -> Ordering {
+    loop {
+        let ordering = (a / b).cmp(&(c / d));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        let (a_rem, c_rem) = (a % b, c % d);
+        match (a_rem, c_rem) {
+            (0, 0) => return Ordering::Equal,
+            (0, _) => return Ordering::Less,
+            (_, 0) => return Ordering::Greater,
+            // a_rem / b < c_rem / d exactly when d / c_rem < b / a_rem.
+            _ => (a, b, c, d) = (d, c_rem, b, a_rem),
+        }
+    }
+}
+
+/// Split a finite, non-negative `f64` into `(mantissa, exponent)` such that its value is
+/// exactly `mantissa * 2^exponent`.
+fn decode_f64(real: f64) -> (u128, i32) {
+    let bits = real.to_bits();
+    let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = u128::from(bits & ((1 << 52) - 1));
+
+    if biased_exponent == 0 {
+        // Subnormal numbers have no implicit leading bit.
+        (fraction, -1074)
+    } else {
+        (fraction | (1 << 52), biased_exponent - 1075)
+    }
+}
+
+/// The exact non-negative `n`-th root of `value`, if it is an integer.
+fn integer_root(value: i64, n: i64) -> Option<i64> {
+    if value < 0 {
+        return None;
+    }
+    if n == 1 || value < 2 {
+        return Some(value);
+    }
+
+    let n = u32::try_from(n).ok()?;
+    let estimate = (value as f64).powf(1.0 / f64::from(n)).round() as i64;
+
+    (estimate.saturating_sub(1)..=estimate.saturating_add(1))
+        .find(|root| *root >= 0 && root.checked_pow(n) == Some(value))
+}
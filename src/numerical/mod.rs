@@ -0,0 +1,213 @@
+//! Numerical evaluation of expressions to machine-precision numbers, as in
+//! [`N`](https://reference.wolfram.com/language/ref/N.html) <sub>WL</sub>.
+
+use std::f64::consts;
+use std::fmt;
+
+use crate::{Complex, Expr, ExprKind, Normal};
+
+/// Error returned when an expression cannot be evaluated to a machine-precision
+/// number.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumericError {
+    /// `expr` is not a number, a numeric constant, or a numeric function applied to
+    /// numeric arguments.
+    ///
+    /// This is the first such sub-expression found, searching depth-first from left to
+    /// right.
+    #[allow(missing_docs)]
+    NonNumeric { expr: Expr },
+    /// Evaluation of `expr` produced an infinite or indeterminate result, e.g. `1/0`.
+    #[allow(missing_docs)]
+    NotFinite { expr: Expr },
+    /// A real number was required, but `expr` evaluated to the complex number `value`.
+    #[allow(missing_docs)]
+    NotReal { expr: Expr, value: Complex },
+}
+
+impl Expr {
+    /// Evaluate this expression to a machine-precision complex number.
+    ///
+    /// The following expressions are numeric:
+    ///
+    /// * integers, reals, and `Rational[n, d]` and `Complex[re, im]` with numeric
+    ///   arguments,
+    /// * the constants `Pi`, `E`, `I`, `Degree`, `GoldenRatio` and `EulerGamma`,
+    /// * `Plus`, `Times`, `Power`, `Subtract`, `Minus`, `Divide`, `Sqrt`, `Exp`, `Log`,
+    ///   `Abs`, `Sin`, `Cos`, `Tan`, `Cot`, `Sec`, `Csc`, `ArcSin`, `ArcCos`, `ArcTan`,
+    ///   `Sinh`, `Cosh`, `Tanh` and `N` applied to numeric arguments.
+    ///
+    /// Multivalued functions use the principal branch, so e.g. `(-8)^(1/3)` is the
+    /// complex number `1 + 1.732... I`.
+    ///
+    /// # Example
+    ///
+    /// Evaluate `Times[Rational[1, 2], Power[2, Rational[1, 2]]]`, which is
+    /// `Sqrt[2] / 2`:
+    ///
+    /// ```
+    /// use wolfram_expr::{Complex, Expr, Symbol};
+    ///
+    /// let rational = Expr::function(
+    ///     Symbol::new("System`Rational"),
+    ///     vec![Expr::from(1), Expr::from(2)],
+    /// );
+    /// let expr = Expr::function(Symbol::new("System`Times"), vec![
+    ///     rational.clone(),
+    ///     Expr::function(Symbol::new("System`Power"), vec![Expr::from(2), rational]),
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     expr.numeric_value(),
+    ///     Ok(Complex::from(std::f64::consts::FRAC_1_SQRT_2))
+    /// );
+    /// ```
+    pub fn numeric_value(&self) -> Result<Complex, NumericError> {
+        let value = match self.kind() {
+            ExprKind::Integer(value) => Complex::from(*value as f64),
+            ExprKind::Real(value) => Complex::from(**value),
+            ExprKind::String(_) | ExprKind::Association(_) => {
+                return Err(non_numeric(self))
+            },
+            ExprKind::Symbol(symbol) => match constant(symbol.as_str()) {
+                Some(value) => value,
+                None => return Err(non_numeric(self)),
+            },
+            ExprKind::Normal(normal) => numeric_function(self, normal)?,
+        };
+
+        if !value.is_finite() {
+            return Err(NumericError::NotFinite { expr: self.clone() });
+        }
+
+        Ok(value)
+    }
+
+    /// Evaluate this expression to a machine-precision real number.
+    ///
+    /// This is equivalent to [`Expr::numeric_value()`], but returns an error if the
+    /// result has a non-zero imaginary part.
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, NumericError, Symbol};
+    ///
+    /// let x = Expr::symbol(Symbol::new("Global`x"));
+    /// let expr = Expr::function(Symbol::new("System`Sin"), vec![x.clone()]);
+    ///
+    /// assert_eq!(expr.to_f64(), Err(NumericError::NonNumeric { expr: x }));
+    /// ```
+    pub fn to_f64(&self) -> Result<f64, NumericError> {
+        let value = self.numeric_value()?;
+
+        match value.is_real() {
+            true => Ok(value.re),
+            false => Err(NumericError::NotReal {
+                expr: self.clone(),
+                value,
+            }),
+        }
+    }
+}
+
+/// The value of the numeric constant named `name`.
+pub(crate) fn constant(name: &str) -> Option<Complex> {
+    let value = match name {
+        "System`Pi" => consts::PI,
+        "System`E" => consts::E,
+        "System`Degree" => consts::PI / 180.0,
+        "System`GoldenRatio" => (1.0 + 5f64.sqrt()) / 2.0,
+        "System`EulerGamma" => 0.577_215_664_901_532_9,
+        "System`I" => return Some(Complex::I),
+        _ => return None,
+    };
+
+    Some(Complex::from(value))
+}
+
+fn numeric_function(expr: &Expr, normal: &Normal) -> Result<Complex, NumericError> {
+    let name = match normal.head.try_as_symbol() {
+        Some(head) => head.as_str(),
+        None => return Err(non_numeric(expr)),
+    };
+
+    let args = normal.elements();
+
+    let value = match (name, args) {
+        ("System`Plus", _) => {
+            let mut sum = Complex::default();
+            for arg in args {
+                sum = sum + arg.numeric_value()?;
+            }
+            sum
+        },
+        ("System`Times", _) => {
+            let mut product = Complex::from(1.0);
+            for arg in args {
+                product = product * arg.numeric_value()?;
+            }
+            product
+        },
+        ("System`Power", [base, exp]) => base.numeric_value()?.pow(exp.numeric_value()?),
+        ("System`Subtract", [x, y]) => x.numeric_value()? - y.numeric_value()?,
+        ("System`Minus", [x]) => -x.numeric_value()?,
+        ("System`Divide", [x, y]) | ("System`Rational", [x, y]) => {
+            x.numeric_value()? / y.numeric_value()?
+        },
+        ("System`Complex", [re, im]) => {
+            re.numeric_value()? + Complex::I * im.numeric_value()?
+        },
+        ("System`N", [x]) | ("System`N", [x, _]) => x.numeric_value()?,
+        ("System`Sqrt", [x]) => x.numeric_value()?.sqrt(),
+        ("System`Exp", [x]) => x.numeric_value()?.exp(),
+        ("System`Log", [x]) => x.numeric_value()?.ln(),
+        ("System`Log", [base, x]) => {
+            let base = base.numeric_value()?;
+            x.numeric_value()?.ln() / base.ln()
+        },
+        ("System`Abs", [x]) => Complex::from(x.numeric_value()?.abs()),
+        ("System`Sin", [x]) => x.numeric_value()?.sin(),
+        ("System`Cos", [x]) => x.numeric_value()?.cos(),
+        ("System`Tan", [x]) => x.numeric_value()?.tan(),
+        ("System`Cot", [x]) => Complex::from(1.0) / x.numeric_value()?.tan(),
+        ("System`Sec", [x]) => Complex::from(1.0) / x.numeric_value()?.cos(),
+        ("System`Csc", [x]) => Complex::from(1.0) / x.numeric_value()?.sin(),
+        ("System`ArcSin", [x]) => x.numeric_value()?.asin(),
+        ("System`ArcCos", [x]) => x.numeric_value()?.acos(),
+        ("System`ArcTan", [x]) => x.numeric_value()?.atan(),
+        // ArcTan[x, y] is the argument of x + I y.
+        ("System`ArcTan", [x, y]) => {
+            let (x, y) = (x.to_f64()?, y.to_f64()?);
+            Complex::from(y.atan2(x))
+        },
+        ("System`Sinh", [x]) => x.numeric_value()?.sinh(),
+        ("System`Cosh", [x]) => x.numeric_value()?.cosh(),
+        ("System`Tanh", [x]) => x.numeric_value()?.tanh(),
+        _ => return Err(non_numeric(expr)),
+    };
+
+    Ok(value)
+}
+
+fn non_numeric(expr: &Expr) -> NumericError {
+    NumericError::NonNumeric { expr: expr.clone() }
+}
+
+impl fmt::Display for NumericError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumericError::NonNumeric { expr } => {
+                write!(f, "{} is not a numerical value.", expr)
+            },
+            NumericError::NotFinite { expr } => {
+                write!(f, "Numerical evaluation of {} is not finite.", expr)
+            },
+            NumericError::NotReal { expr, value } => write!(
+                f,
+                "{} evaluates to the complex number {}, not a real number.",
+                expr, value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NumericError {}
@@ -0,0 +1,365 @@
+//! Trailing option rules, as used by
+//! [`OptionValue`](https://reference.wolfram.com/language/ref/OptionValue.html)
+//! <sub>WL</sub> and
+//! [`FilterRules`](https://reference.wolfram.com/language/ref/FilterRules.html)
+//! <sub>WL</sub>.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{system, Expr, ExprKind, Normal, RuleKind, RuleRef, Symbol};
+
+/// The name of an option: the left-hand side of an option rule.
+///
+/// Following the Wolfram Language, a `String` name refers to the same option as a
+/// `Symbol` with the same [symbol name][Symbol::symbol_name], so `"PlotRange" -> All`
+/// and ``System`PlotRange -> All`` set the same option.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OptionName {
+    /// A symbol, e.g. `PlotRange`.
+    Symbol(Symbol),
+    /// A string, e.g. `"PlotRange"`.
+    String(String),
+}
+
+/// The positional arguments and trailing option rules of an expression like
+/// `Plot[f, {x, 0, 1}, PlotRange -> All, Axes :> False]`.
+///
+/// # Example
+///
+/// ```
+/// use wolfram_expr::{Expr, Options, Symbol};
+///
+/// let sym = |name: &str| Expr::symbol(Symbol::new(name));
+///
+/// let elements = vec![
+///     sym("Global`f"),
+///     Expr::rule(Symbol::new("System`PlotRange"), sym("System`All")),
+///     Expr::rule(Expr::string("Axes"), sym("System`False")),
+/// ];
+///
+/// let options = Options::from_elements(&elements);
+///
+/// assert_eq!(options.positional(), &[sym("Global`f")]);
+/// assert_eq!(options.get("PlotRange"), Some(&sym("System`All")));
+/// assert_eq!(options.get(Symbol::new("System`Axes")), Some(&sym("System`False")));
+/// assert_eq!(options.to_elements(), elements);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    positional: Vec<Expr>,
+    /// (name, kind, value), in the order they were given.
+    rules: Vec<(OptionName, RuleKind, Expr)>,
+}
+
+impl OptionName {
+    /// Returns the name of this option, without any context.
+    pub fn as_str(&self) -> &str {
+        match self {
+            OptionName::Symbol(symbol) => symbol.symbol_name().as_str(),
+            OptionName::String(string) => string,
+        }
+    }
+
+    /// Returns `true` if `self` and `other` name the same option.
+    ///
+    /// Two symbols must be identical, while a string matches any symbol with the same
+    /// symbol name.
+    pub fn matches(&self, other: &OptionName) -> bool {
+        match (self, other) {
+            (OptionName::Symbol(a), OptionName::Symbol(b)) => a == b,
+            _ => self.as_str() == other.as_str(),
+        }
+    }
+
+    /// Convert an option rule left-hand side to an `OptionName`, or return `None` if
+    /// `expr` is not a symbol or string.
+    pub fn from_expr(expr: &Expr) -> Option<OptionName> {
+        match expr.kind() {
+            ExprKind::Symbol(symbol) => Some(OptionName::Symbol(symbol.clone())),
+            ExprKind::String(string) => Some(OptionName::String(string.clone())),
+            _ => None,
+        }
+    }
+
+    /// Convert this name to a `Symbol` or `String` expression.
+    pub fn to_expr(&self) -> Expr {
+        match self {
+            OptionName::Symbol(symbol) => Expr::symbol(symbol.clone()),
+            OptionName::String(string) => Expr::string(string.clone()),
+        }
+    }
+}
+
+impl Options {
+    /// Construct an empty set of options, with no positional arguments.
+    pub fn new() -> Self {
+        Options::default()
+    }
+
+    /// Split `elements` into positional arguments and trailing option rules.
+    ///
+    /// The trailing option rules are the longest run of `Rule` and `RuleDelayed`
+    /// elements at the end of `elements` whose left-hand sides are symbols or strings.
+    /// Every element before them is positional, even if it is itself a rule.
+    pub fn from_elements(elements: &[Expr]) -> Self {
+        let rule_count = elements
+            .iter()
+            .rev()
+            .take_while(|elem| option_rule(elem).is_some())
+            .count();
+        let (positional, rules) = elements.split_at(elements.len() - rule_count);
+
+        Options {
+            positional: positional.to_vec(),
+            rules: rules.iter().filter_map(option_rule).collect(),
+        }
+    }
+
+    /// Split the elements of `normal` into positional arguments and trailing option
+    /// rules.
+    ///
+    /// See [`Options::from_elements()`].
+    pub fn from_normal(normal: &Normal) -> Self {
+        Options::from_elements(normal.elements())
+    }
+
+    /// Construct options from a list of rules, like the value of
+    /// [`Options`](https://reference.wolfram.com/language/ref/Options.html)
+    /// <sub>WL</sub> `[f]`, or return `None` if `expr` is not a list of option rules.
+    pub fn from_rule_list(expr: &Expr) -> Option<Self> {
+        let list = match expr.try_as_normal() {
+            Some(list) if list.has_head(system::List.symbol()) => list,
+            _ => return None,
+        };
+
+        let rules = list
+            .elements()
+            .iter()
+            .map(option_rule)
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Options {
+            positional: Vec::new(),
+            rules,
+        })
+    }
+
+    //==================================
+    // Accessors
+    //==================================
+
+    /// The positional arguments which precede the option rules.
+    pub fn positional(&self) -> &[Expr] {
+        &self.positional
+    }
+
+    /// Mutable access to the positional arguments.
+    pub fn positional_mut(&mut self) -> &mut Vec<Expr> {
+        &mut self.positional
+    }
+
+    /// The number of option rules.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns `true` if there are no option rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Iterate over the option rules in order, as `(name, kind, value)` tuples.
+    pub fn rules(&self) -> impl Iterator<Item = (&OptionName, RuleKind, &Expr)> {
+        self.rules.iter().map(|(name, kind, value)| (name, *kind, value))
+    }
+
+    /// Returns `true` if an option matching `name` is set.
+    pub fn contains<N: Into<OptionName>>(&self, name: N) -> bool {
+        self.position(&name.into()).is_some()
+    }
+
+    /// The value of the option `name`, or `None` if it is not set.
+    ///
+    /// As in the Wolfram Language, if the option is given more than once, the first
+    /// value is used.
+    pub fn get<N: Into<OptionName>>(&self, name: N) -> Option<&Expr> {
+        let index = self.position(&name.into())?;
+        Some(&self.rules[index].2)
+    }
+
+    /// Whether the option `name` is set with `Rule` or `RuleDelayed`, or `None` if it
+    /// is not set.
+    pub fn rule_kind<N: Into<OptionName>>(&self, name: N) -> Option<RuleKind> {
+        let index = self.position(&name.into())?;
+        Some(self.rules[index].1)
+    }
+
+    /// The value of the option `name`, falling back to its value in `defaults`, as in
+    /// `OptionValue[f, {opts}, name]`, where `defaults` are the `Options[f]`.
+    pub fn option_value<'a, N: Into<OptionName>>(
+        &'a self,
+        defaults: &'a Options,
+        name: N,
+    ) -> Option<&'a Expr> {
+        let name = name.into();
+        self.get(name.clone()).or_else(|| defaults.get(name))
+    }
+
+    fn position(&self, name: &OptionName) -> Option<usize> {
+        self.rules.iter().position(|(key, _, _)| key.matches(name))
+    }
+
+    //==================================
+    // Modification
+    //==================================
+
+    /// Set the option `name` to `value` using `Rule`.
+    ///
+    /// If the option is already set, its first occurrence is replaced, and later
+    /// occurrences are removed.
+    pub fn set<N: Into<OptionName>>(&mut self, name: N, value: Expr) {
+        self.set_rule(name.into(), RuleKind::Rule, value)
+    }
+
+    /// Set the option `name` to `value` using `RuleDelayed`.
+    ///
+    /// See [`Options::set()`].
+    pub fn set_delayed<N: Into<OptionName>>(&mut self, name: N, value: Expr) {
+        self.set_rule(name.into(), RuleKind::RuleDelayed, value)
+    }
+
+    fn set_rule(&mut self, name: OptionName, kind: RuleKind, value: Expr) {
+        match self.position(&name) {
+            Some(index) => {
+                self.rules[index] = (name.clone(), kind, value);
+                let mut position = 0;
+                self.rules.retain(|(key, _, _)| {
+                    position += 1;
+                    position - 1 <= index || !key.matches(&name)
+                });
+            },
+            None => self.rules.push((name, kind, value)),
+        }
+    }
+
+    /// Remove every occurrence of the option `name`, returning the value of the first.
+    pub fn remove<N: Into<OptionName>>(&mut self, name: N) -> Option<Expr> {
+        let name = name.into();
+        let index = self.position(&name)?;
+        let (_, _, value) = self.rules.remove(index);
+        self.rules.retain(|(key, _, _)| !key.matches(&name));
+        Some(value)
+    }
+
+    /// Append the options in `defaults` which are not already set.
+    ///
+    /// Positional arguments in `defaults` are ignored.
+    pub fn with_defaults(mut self, defaults: &Options) -> Self {
+        for (name, kind, value) in &defaults.rules {
+            if self.position(name).is_none() {
+                self.rules.push((name.clone(), *kind, value.clone()));
+            }
+        }
+        self
+    }
+
+    /// Keep only the options whose names match one of `names`, as in
+    /// ``FilterRules[{opts}, names]``.
+    ///
+    /// The positional arguments are unchanged.
+    pub fn filter_rules(&self, names: &[OptionName]) -> Self {
+        self.filter(|name| names.iter().any(|known| known.matches(name)))
+    }
+
+    /// Keep only the options which are also set in `known`, as in
+    /// ``FilterRules[{opts}, Options[f]]``.
+    ///
+    /// The positional arguments are unchanged.
+    pub fn filter_known(&self, known: &Options) -> Self {
+        self.filter(|name| known.position(name).is_some())
+    }
+
+    /// Remove the options whose names match one of `names`, as in
+    /// ``FilterRules[{opts}, Except[names]]``.
+    ///
+    /// The positional arguments are unchanged.
+    pub fn filter_rules_except(&self, names: &[OptionName]) -> Self {
+        self.filter(|name| !names.iter().any(|known| known.matches(name)))
+    }
+
+    fn filter<F: Fn(&OptionName) -> bool>(&self, keep: F) -> Self {
+        Options {
+            positional: self.positional.clone(),
+            rules: self
+                .rules
+                .iter()
+                .filter(|(name, _, _)| keep(name))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    //==================================
+    // Conversion
+    //==================================
+
+    /// The option rules as a list of `Rule` and `RuleDelayed` expressions, without the
+    /// positional arguments.
+    pub fn rule_list(&self) -> Expr {
+        Expr::list(self.rules.iter().map(to_rule).collect())
+    }
+
+    /// The positional arguments followed by the option rules.
+    pub fn to_elements(&self) -> Vec<Expr> {
+        let mut elements = self.positional.clone();
+        elements.extend(self.rules.iter().map(to_rule));
+        elements
+    }
+
+    /// Construct `head[positional..., rules...]`.
+    pub fn to_normal<H: Into<Expr>>(&self, head: H) -> Expr {
+        Expr::normal(head.into(), self.to_elements())
+    }
+}
+
+/// Split an option rule `name -> value` or `name :> value` into its parts.
+fn option_rule(expr: &Expr) -> Option<(OptionName, RuleKind, Expr)> {
+    let rule = RuleRef::try_from(expr).ok()?;
+    let name = OptionName::from_expr(rule.lhs())?;
+    Some((name, rule.kind(), rule.rhs().clone()))
+}
+
+fn to_rule((name, kind, value): &(OptionName, RuleKind, Expr)) -> Expr {
+    kind.to_expr(name.to_expr(), value.clone())
+}
+
+impl From<Symbol> for OptionName {
+    fn from(symbol: Symbol) -> Self {
+        OptionName::Symbol(symbol)
+    }
+}
+
+impl From<&Symbol> for OptionName {
+    fn from(symbol: &Symbol) -> Self {
+        OptionName::Symbol(symbol.clone())
+    }
+}
+
+impl From<String> for OptionName {
+    fn from(string: String) -> Self {
+        OptionName::String(string)
+    }
+}
+
+impl From<&str> for OptionName {
+    fn from(string: &str) -> Self {
+        OptionName::String(string.to_owned())
+    }
+}
+
+impl fmt::Display for OptionName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_expr(), f)
+    }
+}
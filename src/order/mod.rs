@@ -0,0 +1,259 @@
+//! Canonical ordering of expressions, following
+//! [`Order`](https://reference.wolfram.com/language/ref/Order.html) <sub>WL</sub>.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use crate::{number::Numeric, Association, Expr, ExprKind, Normal, Symbol};
+
+impl Expr {
+    /// Compare two expressions using the Wolfram Language canonical order.
+    ///
+    /// This is the order used by
+    /// [`Sort`](https://reference.wolfram.com/language/ref/Sort.html) <sub>WL</sub>, and
+    /// is also the order used by the [`Ord`] implementation of `Expr`:
+    ///
+    /// 1. Numbers, including `Rational[n, d]`, come first, ordered by value. An exact
+    ///    number comes before a real with the same value, and an integer before a
+    ///    `Rational[n, d]` with the same value. `Rational`s with the same value, like
+    ///    `Rational[1, 2]` and `Rational[2, 4]`, are ordered by `n` and then `d`.
+    /// 2. Strings come next, ordered alphabetically ignoring case. Strings which differ
+    ///    only in case are ordered with lowercase letters first.
+    /// 3. Symbols come next, ordered by their name as for strings, and then by
+    ///    their context.
+    /// 4. Normal expressions come next. Shorter expressions come first; expressions with
+    ///    the same length are ordered by their heads, and then by their elements.
+    /// 5. Associations come last. Smaller associations come first; associations with the
+    ///    same length are ordered by whether each rule is delayed, and then by their
+    ///    keys and values from left to right.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, Symbol};
+    ///
+    /// let mut exprs = vec![
+    ///     Expr::symbol(Symbol::new("Global`x")),
+    ///     Expr::string("B"),
+    ///     Expr::from(2),
+    ///     Expr::string("a"),
+    ///     Expr::real(1.5),
+    /// ];
+    ///
+    /// exprs.sort();
+    ///
+    /// assert_eq!(exprs, vec![
+    ///     Expr::real(1.5),
+    ///     Expr::from(2),
+    ///     Expr::string("a"),
+    ///     Expr::string("B"),
+    ///     Expr::symbol(Symbol::new("Global`x")),
+    /// ]);
+    /// ```
+    pub fn canonical_cmp(&self, other: &Expr) -> Ordering {
+        // The stack only allocates once a pair of normal expressions or associations is
+        // compared, so comparing two atoms does not allocate.
+        let mut stack: Vec<(&Expr, &Expr)> = Vec::new();
+        let mut next = Some((self, other));
+
+        while let Some((lhs, rhs)) = next.take().or_else(|| stack.pop()) {
+            let ordering = match (lhs.kind(), rhs.kind()) {
+                (ExprKind::Integer(lhs), ExprKind::Integer(rhs)) => lhs.cmp(rhs),
+                (ExprKind::String(lhs), ExprKind::String(rhs)) => {
+                    compare_strings(lhs, rhs)
+                },
+                (ExprKind::Symbol(lhs), ExprKind::Symbol(rhs)) => {
+                    lhs.canonical_cmp(rhs)
+                },
+                _ => match (Numeric::from_expr(lhs), Numeric::from_expr(rhs)) {
+                    (Some(lhs_value), Some(rhs_value)) => {
+                        compare_numbers(lhs_value, rhs_value)
+                            .then_with(|| rational_parts(lhs).cmp(&rational_parts(rhs)))
+                    },
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => match (lhs.kind(), rhs.kind()) {
+                        (ExprKind::Normal(lhs), ExprKind::Normal(rhs)) => {
+                            let ordering = lhs.contents.len().cmp(&rhs.contents.len());
+                            if ordering == Ordering::Equal {
+                                // Compare the heads first, then the elements from left
+                                // to right.
+                                stack.extend(
+                                    lhs.contents.iter().zip(rhs.contents.iter()).rev(),
+                                );
+                                stack.push((&lhs.head, &rhs.head));
+                            }
+                            ordering
+                        },
+                        (ExprKind::Association(lhs), ExprKind::Association(rhs)) => {
+                            let ordering = lhs
+                                .len()
+                                .cmp(&rhs.len())
+                                .then_with(|| rule_kinds(lhs).cmp(rule_kinds(rhs)));
+                            if ordering == Ordering::Equal {
+                                // Compare the rules from left to right, keys first.
+                                for (lhs, rhs) in lhs.iter().zip(rhs.iter()).rev() {
+                                    stack.push((&lhs.1 .1, &rhs.1 .1));
+                                    stack.push((lhs.0, rhs.0));
+                                }
+                            }
+                            ordering
+                        },
+                        (lhs, rhs) => kind_rank(lhs).cmp(&kind_rank(rhs)),
+                    },
+                },
+            };
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        Ordering::Equal
+    }
+
+    /// Return a copy of this normal expression with its elements in canonical order, as
+    /// in [`Sort`](https://reference.wolfram.com/language/ref/Sort.html) <sub>WL</sub>.
+    ///
+    /// Returns `None` if this is not a normal expression.
+    pub fn sort(&self) -> Option<Expr> {
+        let mut normal: Normal = self.try_as_normal()?.clone();
+        normal.contents.sort();
+        Some(Expr::from(normal))
+    }
+
+    /// Return a copy of this normal expression with its elements in canonical order and
+    /// duplicates removed, as in
+    /// [`Union`](https://reference.wolfram.com/language/ref/Union.html) <sub>WL</sub>.
+    ///
+    /// Returns `None` if this is not a normal expression.
+    pub fn union(&self) -> Option<Expr> {
+        let mut normal: Normal = self.try_as_normal()?.clone();
+        normal.contents.sort();
+        normal.contents.dedup();
+        Some(Expr::from(normal))
+    }
+
+    /// Return a copy of this normal expression with all but the first occurrence of each
+    /// element removed, as in
+    /// [`DeleteDuplicates`](https://reference.wolfram.com/language/ref/DeleteDuplicates.html)
+    /// <sub>WL</sub>.
+    ///
+    /// Unlike [`Expr::union()`], the order of the remaining elements is unchanged.
+    ///
+    /// Returns `None` if this is not a normal expression.
+    pub fn delete_duplicates(&self) -> Option<Expr> {
+        let normal = self.try_as_normal()?;
+
+        let mut seen: HashSet<&Expr> = HashSet::with_capacity(normal.contents.len());
+        let contents = normal
+            .contents
+            .iter()
+            .filter(|elem| seen.insert(elem))
+            .cloned()
+            .collect();
+
+        Some(Expr::normal(normal.head.clone(), contents))
+    }
+}
+
+impl Symbol {
+    /// Compare two symbols using the Wolfram Language canonical order.
+    ///
+    /// Symbols are ordered by their [name][Symbol::symbol_name], using the same order as
+    /// strings, and then by their [context][Symbol::context].
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use wolfram_expr::Symbol;
+    ///
+    /// let a = Symbol::new("Global`a");
+    /// let b = Symbol::new("Global`B");
+    /// let system_a = Symbol::new("System`a");
+    ///
+    /// assert_eq!(a.canonical_cmp(&b), Ordering::Less);
+    /// assert_eq!(a.canonical_cmp(&system_a), Ordering::Less);
+    /// ```
+    pub fn canonical_cmp(&self, other: &Symbol) -> Ordering {
+        compare_strings(self.symbol_name().as_str(), other.symbol_name().as_str())
+            .then_with(|| {
+                compare_strings(self.context().as_str(), other.context().as_str())
+            })
+    }
+}
+
+impl PartialOrd for Expr {
+    fn partial_cmp(&self, other: &Expr) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders expressions using [`Expr::canonical_cmp()`].
+impl Ord for Expr {
+    fn cmp(&self, other: &Expr) -> Ordering {
+        self.canonical_cmp(other)
+    }
+}
+
+//======================================
+// Helpers
+//======================================
+
+/// Rank of each kind of expression in the canonical order.
+fn kind_rank(kind: &ExprKind) -> u8 {
+    match kind {
+        ExprKind::Integer(_) | ExprKind::Real(_) => 0,
+        ExprKind::String(_) => 1,
+        ExprKind::Symbol(_) => 2,
+        ExprKind::Normal(_) => 3,
+        ExprKind::Association(_) => 4,
+    }
+}
+
+/// Whether each rule of `assoc` is delayed, in order.
+fn rule_kinds(assoc: &Association) -> impl Iterator<Item = bool> + '_ {
+    assoc.rules().map(|(_, kind, _)| kind.is_delayed())
+}
+
+/// Compare two numbers by value, with exact numbers before reals of the same value.
+fn compare_numbers(lhs: Numeric, rhs: Numeric) -> Ordering {
+    // Neither value can be NaN.
+    lhs.partial_cmp(&rhs)
+        .unwrap_or(Ordering::Equal)
+        .then(lhs.is_real().cmp(&rhs.is_real()))
+}
+
+/// The elements of `Rational[n, d]` as written, or `None` for an integer or a real.
+///
+/// This distinguishes numbers with the same value, so that the order agrees with `Eq`.
+fn rational_parts(expr: &Expr) -> Option<(i64, i64)> {
+    match expr.try_as_normal()?.elements() {
+        [numer, denom] => match (numer.kind(), denom.kind()) {
+            (ExprKind::Integer(numer), ExprKind::Integer(denom)) => {
+                Some((*numer, *denom))
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Compare two strings ignoring case, then with lowercase before uppercase, then by their
+/// code points (which only distinguishes strings that differ in ways that do not affect
+/// case, e.g. "ß" and "ss").
+pub(crate) fn compare_strings(lhs: &str, rhs: &str) -> Ordering {
+    fn folded(s: &str) -> impl Iterator<Item = char> + '_ {
+        s.chars().flat_map(char::to_lowercase)
+    }
+
+    folded(lhs)
+        .cmp(folded(rhs))
+        .then_with(|| {
+            lhs.chars()
+                .zip(rhs.chars())
+                .find(|(l, r)| l != r)
+                .map(|(l, r)| r.is_lowercase().cmp(&l.is_lowercase()))
+                .unwrap_or(Ordering::Equal)
+        })
+        .then_with(|| lhs.cmp(rhs))
+}
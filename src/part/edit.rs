@@ -0,0 +1,217 @@
+//! Path-based, copy-on-write editing of expressions.
+//!
+//! A *path* is a sequence of [`PartSpec::Index`] indices, as in `expr[[2, -1, 1]]`. The
+//! editors below clone `self` (which only increments a reference count), and then use
+//! [`Expr::kind_mut()`] to make each node along the path unique before modifying it. Any
+//! subexpression not on the path remains shared with the original expression.
+
+use crate::{
+    part::{resolve_index, PartError, PartSpec},
+    Expr, ExprKind,
+};
+
+impl Expr {
+    /// Return a copy of this expression with the part at `path` replaced by `new`, as in
+    /// [`ReplacePart`](https://reference.wolfram.com/language/ref/ReplacePart.html)
+    /// <sub>WL</sub>.
+    ///
+    /// Index `0` refers to a head, so `expr.replace_part(&[0], h)` changes the head of
+    /// `expr` to `h`. An empty `path` replaces the whole expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wolfram_expr::Expr;
+    ///
+    /// let inner = Expr::list(vec![Expr::from(1), Expr::from(2)]);
+    /// let list = Expr::list(vec![inner.clone(), Expr::from(3)]);
+    ///
+    /// let updated = list.replace_part(&[1, -1], Expr::from(20)).unwrap();
+    ///
+    /// assert_eq!(updated.to_string(), "System`List[System`List[1, 20], 3]");
+    /// // The original expression is unchanged.
+    /// assert_eq!(list.to_string(), "System`List[System`List[1, 2], 3]");
+    /// ```
+    pub fn replace_part(&self, path: &[i64], new: Expr) -> Result<Expr, PartError> {
+        let mut result = self.clone();
+        *result.part_mut(path)? = new;
+        Ok(result)
+    }
+
+    /// Return a copy of this expression with `new` inserted so that it is at `path`, as
+    /// in [`Insert`](https://reference.wolfram.com/language/ref/Insert.html)
+    /// <sub>WL</sub>.
+    ///
+    /// The last index of `path` is the position `new` will have in the normal expression
+    /// at the preceding indices. Negative positions count from the end, so a final index
+    /// of `-1` appends `new`.
+    ///
+    /// ```
+    /// use wolfram_expr::Expr;
+    ///
+    /// let list = Expr::list(vec![Expr::from(1), Expr::from(3)]);
+    ///
+    /// assert_eq!(
+    ///     list.insert_at(&[2], Expr::from(2)).unwrap(),
+    ///     Expr::list(vec![Expr::from(1), Expr::from(2), Expr::from(3)])
+    /// );
+    /// ```
+    pub fn insert_at(&self, path: &[i64], new: Expr) -> Result<Expr, PartError> {
+        let (&index, parent) = split_path(path)?;
+
+        let mut result = self.clone();
+        let target = result.part_mut(parent)?;
+        let len = target.normal_len()?;
+
+        // Positions `1..=len + 1` and `-(len + 1)..=-1` are valid insertion points.
+        let len = len as i64;
+        let position = if 1 <= index && index <= len + 1 {
+            index
+        } else if index <= -1 && index >= -(len + 1) {
+            len + 2 + index
+        } else {
+            return Err(PartError::PartDoesNotExist {
+                index,
+                expr: target.clone(),
+            });
+        };
+
+        target.contents_mut().insert(position as usize - 1, new);
+        Ok(result)
+    }
+
+    /// Return a copy of this expression with the element at `path` removed, as in
+    /// [`Delete`](https://reference.wolfram.com/language/ref/Delete.html)
+    /// <sub>WL</sub>.
+    ///
+    /// The head of an expression cannot be deleted.
+    ///
+    /// ```
+    /// use wolfram_expr::Expr;
+    ///
+    /// let list = Expr::list(vec![Expr::from(1), Expr::from(2), Expr::from(3)]);
+    ///
+    /// assert_eq!(
+    ///     list.delete_at(&[-1]).unwrap(),
+    ///     Expr::list(vec![Expr::from(1), Expr::from(2)])
+    /// );
+    /// ```
+    pub fn delete_at(&self, path: &[i64]) -> Result<Expr, PartError> {
+        let (&index, parent) = split_path(path)?;
+
+        let mut result = self.clone();
+        let target = result.part_mut(parent)?;
+        let len = target.normal_len()?;
+
+        match resolve_index(index, len) {
+            Some(position) if position != 0 => {
+                target.contents_mut().remove(position - 1);
+            },
+            _ => {
+                return Err(PartError::PartDoesNotExist {
+                    index,
+                    expr: target.clone(),
+                })
+            },
+        }
+
+        Ok(result)
+    }
+
+    /// Return a copy of this expression with `new` added after the last element of the
+    /// normal expression at `path`, as in
+    /// [`Append`](https://reference.wolfram.com/language/ref/Append.html)
+    /// <sub>WL</sub>.
+    ///
+    /// An empty `path` appends to this expression.
+    pub fn append_to(&self, path: &[i64], new: Expr) -> Result<Expr, PartError> {
+        let mut result = self.clone();
+        let target = result.part_mut(path)?;
+        target.normal_len()?;
+        target.contents_mut().push(new);
+        Ok(result)
+    }
+
+    /// Return a copy of this expression with `new` added before the first element of the
+    /// normal expression at `path`, as in
+    /// [`Prepend`](https://reference.wolfram.com/language/ref/Prepend.html)
+    /// <sub>WL</sub>.
+    ///
+    /// An empty `path` prepends to this expression.
+    pub fn prepend_to(&self, path: &[i64], new: Expr) -> Result<Expr, PartError> {
+        let mut result = self.clone();
+        let target = result.part_mut(path)?;
+        target.normal_len()?;
+        target.contents_mut().insert(0, new);
+        Ok(result)
+    }
+
+    /// Get mutable access to the part of this expression at `path`.
+    ///
+    /// Every expression along `path` is made unique using [`Expr::kind_mut()`].
+    pub(crate) fn part_mut(&mut self, path: &[i64]) -> Result<&mut Expr, PartError> {
+        let mut current = self;
+
+        for &index in path {
+            let position = match current.kind() {
+                ExprKind::Normal(normal) => resolve_index(index, normal.contents.len()),
+                // The head of an association is not stored, and cannot be edited.
+                ExprKind::Association(assoc) if index != 0 => {
+                    resolve_index(index, assoc.len())
+                },
+                _ => {
+                    return Err(PartError::DepthExceeded {
+                        spec: PartSpec::Index(index),
+                        expr: current.clone(),
+                    })
+                },
+            };
+
+            let position = match position {
+                Some(position) => position,
+                None => {
+                    return Err(PartError::PartDoesNotExist {
+                        index,
+                        expr: current.clone(),
+                    })
+                },
+            };
+
+            current = match current.kind_mut() {
+                ExprKind::Normal(normal) => match position {
+                    0 => &mut normal.head,
+                    _ => &mut normal.contents[position - 1],
+                },
+                ExprKind::Association(assoc) => &mut assoc[position - 1].1,
+                _ => unreachable!(),
+            };
+        }
+
+        Ok(current)
+    }
+
+    /// Get the number of elements in this expression, or an error if it is not a normal
+    /// expression.
+    fn normal_len(&self) -> Result<usize, PartError> {
+        match self.kind() {
+            ExprKind::Normal(normal) => Ok(normal.contents.len()),
+            _ => Err(PartError::AtomicExpression { expr: self.clone() }),
+        }
+    }
+
+    /// Get the elements of this normal expression, making it unique first.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `self` is not a normal expression.
+    fn contents_mut(&mut self) -> &mut Vec<Expr> {
+        match self.kind_mut() {
+            ExprKind::Normal(normal) => &mut normal.contents,
+            _ => panic!("Expr::contents_mut(): expression is not normal"),
+        }
+    }
+}
+
+fn split_path(path: &[i64]) -> Result<(&i64, &[i64]), PartError> {
+    path.split_last().ok_or(PartError::EmptyPath)
+}
@@ -0,0 +1,353 @@
+//! Extraction of parts of expressions, following the semantics of
+//! [`Part`](https://reference.wolfram.com/language/ref/Part.html) <sub>WL</sub>.
+
+use std::fmt;
+
+mod edit;
+
+use crate::{system, Association, Expr, ExprKind, RuleKind};
+
+/// Specification of the part(s) to take at a single level of an expression.
+///
+/// A sequence of `PartSpec`s is the Rust equivalent of the part specification in an
+/// expression like `expr[[2, -1, 1;;3]]`:
+///
+/// ```
+/// use wolfram_expr::PartSpec;
+///
+/// let spec = [PartSpec::Index(2), PartSpec::Index(-1), PartSpec::span(1, 3)];
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PartSpec {
+    /// A single part: `expr[[i]]`.
+    ///
+    /// Indices are 1-based. Index `0` refers to the head of the expression, and negative
+    /// indices count backwards from the last element, which is `-1`.
+    Index(i64),
+    /// [`All`](https://reference.wolfram.com/language/ref/All.html) <sub>WL</sub>: every
+    /// element.
+    All,
+    /// [`Span`](https://reference.wolfram.com/language/ref/Span.html) <sub>WL</sub>: the
+    /// elements from `start` through `end` in steps of `step`: `expr[[start;;end;;step]]`.
+    ///
+    /// `start` and `end` are interpreted in the same way as [`PartSpec::Index`].
+    #[allow(missing_docs)]
+    Span { start: i64, end: i64, step: i64 },
+}
+
+/// Error returned when a part specification is not valid for an expression.
+///
+/// Each variant corresponds to a message issued by `Part` in the Wolfram Language.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartError {
+    /// `Part::partw`: the part at `index` does not exist in `expr`.
+    #[allow(missing_docs)]
+    PartDoesNotExist { index: i64, expr: Expr },
+    /// `Part::partd`: the part specification is longer than the depth of `expr`, i.e. a
+    /// part was requested from an atomic expression.
+    #[allow(missing_docs)]
+    DepthExceeded { spec: PartSpec, expr: Expr },
+    /// `Part::take`: the positions `start` through `end` cannot be taken from `expr`.
+    #[allow(missing_docs)]
+    InvalidSpan { start: i64, end: i64, expr: Expr },
+    /// `Part::span`: a span with a step of `0` was given.
+    ZeroStep,
+    /// `Append::normal`: a normal expression was required, but `expr` is atomic.
+    #[allow(missing_docs)]
+    AtomicExpression { expr: Expr },
+    /// An empty path was given to an operation that requires at least one index.
+    EmptyPath,
+}
+
+impl PartSpec {
+    /// Construct the span `start;;end`, with a step of `1`.
+    pub fn span(start: i64, end: i64) -> Self {
+        PartSpec::Span {
+            start,
+            end,
+            step: 1,
+        }
+    }
+}
+
+impl From<i64> for PartSpec {
+    fn from(index: i64) -> Self {
+        PartSpec::Index(index)
+    }
+}
+
+impl Expr {
+    /// Extract the part of this expression specified by `spec`, as in `expr[[spec]]`.
+    ///
+    /// Each element of `spec` is applied to successively deeper levels of the
+    /// expression. When a [`PartSpec::All`] or [`PartSpec::Span`] is applied to a normal
+    /// expression, the result keeps that expression's head and contains the remaining
+    /// part specification applied to each selected element.
+    ///
+    /// An empty `spec` returns the expression itself.
+    ///
+    /// # Example
+    ///
+    /// Evaluate `{{1, 2}, {3, 4}, {5, 6}}[[-1, 1]]` and
+    /// `{{1, 2}, {3, 4}, {5, 6}}[[2;;3, 2]]`:
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, PartSpec};
+    ///
+    /// let pair = |a: i64, b: i64| Expr::list(vec![Expr::from(a), Expr::from(b)]);
+    /// let matrix = Expr::list(vec![pair(1, 2), pair(3, 4), pair(5, 6)]);
+    ///
+    /// assert_eq!(
+    ///     matrix.part(&[PartSpec::Index(-1), PartSpec::Index(1)]),
+    ///     Ok(Expr::from(5))
+    /// );
+    /// assert_eq!(
+    ///     matrix.part(&[PartSpec::span(2, 3), PartSpec::Index(2)]),
+    ///     Ok(Expr::list(vec![Expr::from(4), Expr::from(6)]))
+    /// );
+    /// ```
+    pub fn part(&self, spec: &[PartSpec]) -> Result<Expr, PartError> {
+        let (first, rest) = match spec.split_first() {
+            Some(split) => split,
+            None => return Ok(self.clone()),
+        };
+
+        match *first {
+            PartSpec::Index(index) => self.part_index(index)?.part(rest),
+            PartSpec::All => self.part_span(1, -1, 1, *first, rest),
+            PartSpec::Span { start, end, step } => {
+                self.part_span(start, end, step, *first, rest)
+            },
+        }
+    }
+
+    /// Get the part of this expression at the single `index`, as in `expr[[index]]`.
+    ///
+    /// See [`PartSpec::Index`] for the meaning of `index`.
+    ///
+    /// The head of an atomic expression is its type, e.g. `` System`Integer ``, and can be
+    /// accessed with an index of `0`.
+    ///
+    /// The parts of an association are its values, in order.
+    pub fn part_index(&self, index: i64) -> Result<Expr, PartError> {
+        let normal = match self.kind() {
+            ExprKind::Normal(normal) => normal,
+            _ if index == 0 => return Ok(self.head()),
+            ExprKind::Association(assoc) => {
+                return match resolve_index(index, assoc.len()) {
+                    Some(position) => Ok(assoc[position - 1].1.clone()),
+                    None => Err(PartError::PartDoesNotExist {
+                        index,
+                        expr: self.clone(),
+                    }),
+                }
+            },
+            _ => {
+                return Err(PartError::DepthExceeded {
+                    spec: PartSpec::Index(index),
+                    expr: self.clone(),
+                })
+            },
+        };
+
+        match resolve_index(index, normal.elements().len()) {
+            Some(0) => Ok(normal.head().clone()),
+            Some(position) => Ok(normal.elements()[position - 1].clone()),
+            None => Err(PartError::PartDoesNotExist {
+                index,
+                expr: self.clone(),
+            }),
+        }
+    }
+
+    fn part_span(
+        &self,
+        start: i64,
+        end: i64,
+        step: i64,
+        spec: PartSpec,
+        rest: &[PartSpec],
+    ) -> Result<Expr, PartError> {
+        if step == 0 {
+            return Err(PartError::ZeroStep);
+        }
+
+        let normal = match self.kind() {
+            ExprKind::Normal(normal) => normal,
+            ExprKind::Association(assoc) => {
+                return self.association_span(assoc, start, end, step, rest)
+            },
+            _ => {
+                return Err(PartError::DepthExceeded {
+                    spec,
+                    expr: self.clone(),
+                })
+            },
+        };
+
+        let positions = match span_positions(start, end, step, normal.elements().len()) {
+            Some(positions) => positions,
+            None => {
+                return Err(PartError::InvalidSpan {
+                    start,
+                    end,
+                    expr: self.clone(),
+                })
+            },
+        };
+
+        let elements = positions
+            .into_iter()
+            .map(|position| match position {
+                0 => normal.head().part(rest),
+                _ => normal.elements()[position - 1].part(rest),
+            })
+            .collect::<Result<Vec<Expr>, PartError>>()?;
+
+        Ok(Expr::normal(normal.head().clone(), elements))
+    }
+
+    /// Take the rules at positions `start;;end;;step` of `assoc`, applying `rest` to each
+    /// value.
+    fn association_span(
+        &self,
+        assoc: &Association,
+        start: i64,
+        end: i64,
+        step: i64,
+        rest: &[PartSpec],
+    ) -> Result<Expr, PartError> {
+        let positions = span_positions(start, end, step, assoc.len())
+            .filter(|positions| !positions.contains(&0))
+            .ok_or_else(|| PartError::InvalidSpan {
+                start,
+                end,
+                expr: self.clone(),
+            })?;
+
+        let mut result = Association::new();
+        for position in positions {
+            let (key, (delayed, value)) = assoc.get_index(position - 1).unwrap();
+            let kind = RuleKind::from_delayed(*delayed);
+            result.insert_rule(key.clone(), kind, value.part(rest)?);
+        }
+
+        Ok(Expr::from(result))
+    }
+
+    /// The head of this expression, as in `Head[expr]`.
+    ///
+    /// The head of an atomic expression is its type, e.g. `` System`Integer `` for `5`.
+    pub(crate) fn head(&self) -> Expr {
+        let head = match self.kind() {
+            ExprKind::Integer(_) => system::Integer,
+            ExprKind::Real(_) => system::Real,
+            ExprKind::String(_) => system::String,
+            ExprKind::Symbol(_) => system::Symbol,
+            ExprKind::Association(_) => system::Association,
+            ExprKind::Normal(normal) => return normal.head().clone(),
+        };
+
+        Expr::from(head)
+    }
+}
+
+/// Convert a WL part index into a 0-based position where `0` is the head and `1..=len`
+/// are the elements, or `None` if the index is out of range.
+pub(crate) fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+
+    let position = if index < 0 { len + 1 + index } else { index };
+
+    if (0..=len).contains(&position) && !(index < 0 && position == 0) {
+        Some(position as usize)
+    } else {
+        None
+    }
+}
+
+/// Compute the positions selected by the span `start;;end;;step` in an expression with
+/// `len` elements, using the same numbering as [`resolve_index()`].
+///
+/// Returns `None` if the span does not fit within the expression. `step` must not be
+/// `0`.
+pub(crate) fn span_positions(
+    start: i64,
+    end: i64,
+    step: i64,
+    len: usize,
+) -> Option<Vec<usize>> {
+    debug_assert!(step != 0);
+
+    let len = len as i64;
+    let absolute = |index: i64| if index < 0 { len + 1 + index } else { index };
+
+    let (from, to) = (absolute(start), absolute(end));
+
+    // An empty span is allowed if `end` is exactly one position "before" `start`.
+    let valid = if step > 0 {
+        (0..=len + 1).contains(&from) && (0..=len).contains(&to) && to >= from - 1
+    } else {
+        (0..=len).contains(&from) && (0..=len + 1).contains(&to) && to <= from + 1
+    };
+
+    if !valid {
+        return None;
+    }
+
+    let mut positions = Vec::new();
+    let mut position = from;
+    while (step > 0 && position <= to) || (step < 0 && position >= to) {
+        positions.push(position as usize);
+        // A step past `i64::MAX` or `i64::MIN` is also past `to`.
+        position = match position.checked_add(step) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    Some(positions)
+}
+
+//======================================
+// Formatting impls
+//======================================
+
+impl fmt::Display for PartSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PartSpec::Index(index) => write!(f, "{}", index),
+            PartSpec::All => write!(f, "System`All"),
+            PartSpec::Span { start, end, step } => {
+                write!(f, "System`Span[{}, {}, {}]", start, end, step)
+            },
+        }
+    }
+}
+
+impl fmt::Display for PartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PartError::PartDoesNotExist { index, expr } => {
+                write!(f, "Part {} of {} does not exist.", index, expr)
+            },
+            PartError::DepthExceeded { spec, expr } => write!(
+                f,
+                "Part specification {}[[{}]] is longer than depth of object.",
+                expr, spec
+            ),
+            PartError::InvalidSpan { start, end, expr } => write!(
+                f,
+                "Cannot take positions {} through {} in {}.",
+                start, end, expr
+            ),
+            PartError::ZeroStep => write!(f, "Span step size cannot be 0."),
+            PartError::AtomicExpression { expr } => {
+                write!(f, "Nonatomic expression expected instead of {}.", expr)
+            },
+            PartError::EmptyPath => write!(f, "Position specification cannot be empty."),
+        }
+    }
+}
+
+impl std::error::Error for PartError {}
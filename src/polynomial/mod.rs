@@ -0,0 +1,784 @@
+//! Sparse multivariate polynomials with integer, rational or machine real coefficients.
+//!
+//! [`Polynomial`] is used to implement [`Expr::expand()`], [`Expr::collect()`],
+//! [`Expr::coefficient()`] and [`Expr::exponent()`], which behave like the Wolfram
+//! Language functions of the same names on polynomial expressions.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{
+    evaluate::as_normal_with_head,
+    number::Numeric,
+    system, Expr, ExprKind, Symbol,
+};
+
+/// Exponents of each variable of a [`Polynomial`] in a single term.
+type Monomial = Vec<u32>;
+
+/// Sparse multivariate polynomial in [`Symbol`] variables, with integer, rational or
+/// machine real coefficients.
+///
+/// Exact coefficients are limited to the range of 64-bit integers; operations whose
+/// result would overflow, or would produce an infinite real coefficient, return
+/// [`PolynomialError::Overflow`].
+///
+/// Two polynomials are equal if they have the same terms and the same variables, in
+/// the same order.
+///
+/// # Example
+///
+/// Expand `(x + 1)^2 - 1`:
+///
+/// ```
+/// use wolfram_expr::{Expr, Polynomial, Symbol};
+///
+/// let x = Symbol::new("Global`x");
+/// let one = Polynomial::from(1);
+/// let x_plus_1 = Polynomial::variable(x.clone()).checked_add(&one).unwrap();
+///
+/// let poly = x_plus_1.checked_pow(2).unwrap().checked_sub(&one).unwrap();
+///
+/// assert_eq!(poly.degree(&x), Some(2));
+/// let call = |head: &str, elems| Expr::function(Symbol::new(head), elems);
+/// assert_eq!(
+///     poly.to_expr(),
+///     call("System`Plus", vec![
+///         call("System`Power", vec![Expr::from(x.clone()), Expr::from(2)]),
+///         call("System`Times", vec![Expr::from(2), Expr::from(x)]),
+///     ])
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    variables: Vec<Symbol>,
+    /// Non-zero coefficient of each monomial that appears in the polynomial.
+    terms: BTreeMap<Monomial, Numeric>,
+}
+
+/// Error returned when an expression cannot be converted to a [`Polynomial`], or when
+/// polynomial arithmetic overflows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolynomialError {
+    /// `expr` is not a polynomial in the given variables, or is a `Power` expression
+    /// without exactly two elements.
+    ///
+    /// This is the first such sub-expression found, searching depth-first from left to
+    /// right.
+    #[allow(missing_docs)]
+    NotPolynomial { expr: Expr },
+    /// A coefficient or exponent does not fit in a machine integer, or a real
+    /// coefficient is infinite.
+    Overflow,
+}
+
+impl Polynomial {
+    /// Construct the polynomial `0`, with no variables.
+    pub fn zero() -> Self {
+        Polynomial {
+            variables: Vec::new(),
+            terms: BTreeMap::new(),
+        }
+    }
+
+    /// Construct the polynomial `x`.
+    pub fn variable(x: Symbol) -> Self {
+        let mut terms = BTreeMap::new();
+        terms.insert(vec![1], Numeric::ONE);
+
+        Polynomial {
+            variables: vec![x],
+            terms,
+        }
+    }
+
+    fn constant(variables: Vec<Symbol>, value: Numeric) -> Self {
+        let mut terms = BTreeMap::new();
+        if !value.is_zero() {
+            terms.insert(vec![0; variables.len()], value);
+        }
+
+        Polynomial { variables, terms }
+    }
+
+    /// Convert `expr` to a polynomial in `variables`.
+    ///
+    /// `expr` may be built from integers, `Rational[n, d]`, finite reals, the symbols in
+    /// `variables`, and `Plus`, `Times`, `Subtract`, `Minus`, `Power` with a
+    /// non-negative integer exponent, and `Divide` by a non-zero number.
+    pub fn from_expr(expr: &Expr, variables: &[Symbol]) -> Result<Self, PolynomialError> {
+        let not_polynomial = || PolynomialError::NotPolynomial { expr: expr.clone() };
+
+        let normal = match expr.kind() {
+            ExprKind::Integer(value) => {
+                let value = Numeric::integer(*value);
+                return Ok(Polynomial::constant(variables.to_vec(), value));
+            },
+            ExprKind::Real(value) if value.is_finite() => {
+                let value = Numeric::Real(**value);
+                return Ok(Polynomial::constant(variables.to_vec(), value));
+            },
+            ExprKind::Symbol(symbol) => {
+                let index = match variables.iter().position(|x| x == symbol) {
+                    Some(index) => index,
+                    None => return Err(not_polynomial()),
+                };
+                let mut monomial = vec![0; variables.len()];
+                monomial[index] = 1;
+
+                let mut terms = BTreeMap::new();
+                terms.insert(monomial, Numeric::ONE);
+                return Ok(Polynomial {
+                    variables: variables.to_vec(),
+                    terms,
+                });
+            },
+            ExprKind::Real(_) | ExprKind::String(_) | ExprKind::Association(_) => {
+                return Err(not_polynomial())
+            },
+            ExprKind::Normal(normal) => normal,
+        };
+
+        let name = match normal.head.try_as_symbol() {
+            Some(head) => head.as_str(),
+            None => return Err(not_polynomial()),
+        };
+        let from_expr = |elem: &Expr| Polynomial::from_expr(elem, variables);
+
+        match (name, normal.elements()) {
+            ("System`Rational", _) => match Numeric::from_expr(expr) {
+                Some(value) => Ok(Polynomial::constant(variables.to_vec(), value)),
+                None => Err(not_polynomial()),
+            },
+            ("System`Plus", elems) => {
+                let mut sum = Polynomial::constant(variables.to_vec(), Numeric::ZERO);
+                for elem in elems {
+                    sum = sum.checked_add(&from_expr(elem)?)?;
+                }
+                Ok(sum)
+            },
+            ("System`Times", elems) => {
+                let mut product = Polynomial::constant(variables.to_vec(), Numeric::ONE);
+                for elem in elems {
+                    product = product.checked_mul(&from_expr(elem)?)?;
+                }
+                Ok(product)
+            },
+            ("System`Subtract", [x, y]) => from_expr(x)?.checked_sub(&from_expr(y)?),
+            ("System`Minus", [x]) => {
+                Polynomial::constant(variables.to_vec(), Numeric::ZERO)
+                    .checked_sub(&from_expr(x)?)
+            },
+            ("System`Power", [base, exp]) => match exp.kind() {
+                ExprKind::Integer(exp) if *exp >= 0 => {
+                    let exp = u32::try_from(*exp).map_err(|_| PolynomialError::Overflow)?;
+                    from_expr(base)?.checked_pow(exp)
+                },
+                _ => Err(not_polynomial()),
+            },
+            ("System`Divide", [x, y]) => {
+                let divisor = match from_expr(y)?.as_constant() {
+                    Some(divisor) if !divisor.is_zero() => divisor,
+                    _ => return Err(not_polynomial()),
+                };
+                let recip = Numeric::ONE
+                    .checked_div(divisor)
+                    .ok_or(PolynomialError::Overflow)?;
+                from_expr(x)?.scale(recip)
+            },
+            _ => Err(not_polynomial()),
+        }
+    }
+
+    /// Convert this polynomial to an expanded `Plus` of terms, sorted into
+    /// [canonical order][Expr::canonical_cmp].
+    pub fn to_expr(&self) -> Expr {
+        self.to_expr_with(&Atoms::default())
+    }
+
+    fn to_expr_with(&self, atoms: &Atoms) -> Expr {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(monomial, coeff)| {
+                let mut factors = vec![coeff.to_expr()];
+                factors.extend(self.monomial_factors(monomial, None, atoms));
+                product(factors)
+            })
+            .collect();
+
+        sum(terms)
+    }
+
+    //==================================
+    // Properties
+    //==================================
+
+    /// The variables of this polynomial.
+    ///
+    /// This may include variables which do not appear in any term.
+    pub fn variables(&self) -> &[Symbol] {
+        &self.variables
+    }
+
+    /// Returns `true` if this is the zero polynomial.
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The number of terms with a non-zero coefficient.
+    pub fn term_count(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// The highest power of `x` which appears in this polynomial, as in
+    /// [`Exponent`](https://reference.wolfram.com/language/ref/Exponent.html)
+    /// <sub>WL</sub>, or `None` if this is the zero polynomial.
+    pub fn degree(&self, x: &Symbol) -> Option<u32> {
+        let index = self.variables.iter().position(|var| var == x);
+
+        self.terms
+            .keys()
+            .map(|monomial| index.map_or(0, |index| monomial[index]))
+            .max()
+    }
+
+    /// The highest total degree of any term of this polynomial, or `None` if this is
+    /// the zero polynomial.
+    pub fn total_degree(&self) -> Option<u32> {
+        self.terms.keys().map(|monomial| monomial.iter().sum()).max()
+    }
+
+    /// The coefficient of `x^n` in this polynomial, as in
+    /// [`Coefficient`](https://reference.wolfram.com/language/ref/Coefficient.html)
+    /// <sub>WL</sub>.
+    ///
+    /// The result is a polynomial in the remaining variables.
+    pub fn coefficient(&self, x: &Symbol, n: u32) -> Polynomial {
+        let index = match self.variables.iter().position(|var| var == x) {
+            Some(index) => index,
+            None if n == 0 => return self.clone(),
+            None => return Polynomial::constant(self.variables.clone(), Numeric::ZERO),
+        };
+
+        let terms = self
+            .terms
+            .iter()
+            .filter(|(monomial, _)| monomial[index] == n)
+            .map(|(monomial, coeff)| {
+                let mut monomial = monomial.clone();
+                monomial[index] = 0;
+                (monomial, *coeff)
+            })
+            .collect();
+
+        Polynomial {
+            variables: self.variables.clone(),
+            terms,
+        }
+    }
+
+    /// Convert this polynomial to an expression in which terms with the same powers of
+    /// `variables` are collected together, as in
+    /// [`Collect`](https://reference.wolfram.com/language/ref/Collect.html)
+    /// <sub>WL</sub>.
+    ///
+    /// The coefficient of each power of `variables` is an expanded polynomial in the
+    /// remaining variables.
+    pub fn collect(&self, variables: &[Symbol]) -> Expr {
+        self.collect_with(variables, &Atoms::default())
+    }
+
+    fn collect_with(&self, variables: &[Symbol], atoms: &Atoms) -> Expr {
+        let indices: Vec<usize> = variables
+            .iter()
+            .filter_map(|x| self.variables.iter().position(|var| var == x))
+            .collect();
+
+        // The coefficient of each distinct monomial in `variables`.
+        let mut groups: BTreeMap<Monomial, Polynomial> = BTreeMap::new();
+
+        for (monomial, coeff) in &self.terms {
+            let mut key = vec![0; self.variables.len()];
+            let mut rest = monomial.clone();
+            for &index in &indices {
+                key[index] = monomial[index];
+                rest[index] = 0;
+            }
+
+            let zero = || Polynomial::constant(self.variables.clone(), Numeric::ZERO);
+            groups.entry(key).or_insert_with(zero).terms.insert(rest, *coeff);
+        }
+
+        let terms = groups
+            .into_iter()
+            .map(|(key, coeff)| {
+                let mut factors = vec![coeff.to_expr_with(atoms)];
+                factors.extend(self.monomial_factors(&key, Some(&indices), atoms));
+                product(factors)
+            })
+            .collect();
+
+        sum(terms)
+    }
+
+    //==================================
+    // Arithmetic
+    //==================================
+
+    /// `self + other`
+    pub fn checked_add(&self, other: &Polynomial) -> Result<Polynomial, PolynomialError> {
+        let (mut result, other) = self.unify(other);
+
+        for (monomial, coeff) in other.terms {
+            result.add_term(monomial, coeff)?;
+        }
+
+        Ok(result)
+    }
+
+    /// `self - other`
+    pub fn checked_sub(&self, other: &Polynomial) -> Result<Polynomial, PolynomialError> {
+        let (mut result, other) = self.unify(other);
+
+        for (monomial, coeff) in other.terms {
+            let coeff = coeff.checked_neg().ok_or(PolynomialError::Overflow)?;
+            result.add_term(monomial, coeff)?;
+        }
+
+        Ok(result)
+    }
+
+    /// `self * other`
+    pub fn checked_mul(&self, other: &Polynomial) -> Result<Polynomial, PolynomialError> {
+        let (lhs, rhs) = self.unify(other);
+        let mut result = Polynomial::constant(lhs.variables.clone(), Numeric::ZERO);
+
+        for (left, left_coeff) in &lhs.terms {
+            for (right, right_coeff) in &rhs.terms {
+                let monomial = left
+                    .iter()
+                    .zip(right)
+                    .map(|(a, b)| a.checked_add(*b))
+                    .collect::<Option<Monomial>>()
+                    .ok_or(PolynomialError::Overflow)?;
+                let coeff = left_coeff
+                    .checked_mul(*right_coeff)
+                    .ok_or(PolynomialError::Overflow)?;
+                result.add_term(monomial, coeff)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// `self^exp`
+    pub fn checked_pow(&self, mut exp: u32) -> Result<Polynomial, PolynomialError> {
+        let mut result = Polynomial::constant(self.variables.clone(), Numeric::ONE);
+        let mut base = self.clone();
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.checked_mul(&base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.checked_mul(&base)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    //==================================
+    // Helpers
+    //==================================
+
+    /// The value of this polynomial if it is a constant.
+    fn as_constant(&self) -> Option<Numeric> {
+        match self.terms.iter().next() {
+            None => Some(Numeric::ZERO),
+            Some((monomial, coeff)) if self.terms.len() == 1 => {
+                monomial.iter().all(|exp| *exp == 0).then_some(*coeff)
+            },
+            Some(_) => None,
+        }
+    }
+
+    /// Multiply every coefficient by `factor`.
+    fn scale(mut self, factor: Numeric) -> Result<Polynomial, PolynomialError> {
+        for coeff in self.terms.values_mut() {
+            *coeff = coeff.checked_mul(factor).ok_or(PolynomialError::Overflow)?;
+        }
+
+        self.terms.retain(|_, coeff| !coeff.is_zero());
+
+        Ok(self)
+    }
+
+    fn add_term(
+        &mut self,
+        monomial: Monomial,
+        coeff: Numeric,
+    ) -> Result<(), PolynomialError> {
+        let sum = match self.terms.get(&monomial) {
+            Some(existing) => {
+                existing.checked_add(coeff).ok_or(PolynomialError::Overflow)?
+            },
+            None => coeff,
+        };
+
+        if sum.is_zero() {
+            self.terms.remove(&monomial);
+        } else {
+            self.terms.insert(monomial, sum);
+        }
+
+        Ok(())
+    }
+
+    /// Convert `self` and `other` to polynomials in the same variables: the variables
+    /// of `self`, followed by any variables of `other` which are not in `self`.
+    fn unify(&self, other: &Polynomial) -> (Polynomial, Polynomial) {
+        if self.variables == other.variables {
+            return (self.clone(), other.clone());
+        }
+
+        let mut variables = self.variables.clone();
+        for var in &other.variables {
+            if !variables.contains(var) {
+                variables.push(var.clone());
+            }
+        }
+
+        (self.with_variables(&variables), other.with_variables(&variables))
+    }
+
+    /// Convert this polynomial to one in `variables`, which must contain all of the
+    /// variables of `self`.
+    fn with_variables(&self, variables: &[Symbol]) -> Polynomial {
+        let indices: Vec<usize> = self
+            .variables
+            .iter()
+            .map(|var| variables.iter().position(|x| x == var).unwrap())
+            .collect();
+
+        let terms = self
+            .terms
+            .iter()
+            .map(|(monomial, coeff)| {
+                let mut new = vec![0; variables.len()];
+                for (exp, index) in monomial.iter().zip(&indices) {
+                    new[*index] = *exp;
+                }
+                (new, *coeff)
+            })
+            .collect();
+
+        Polynomial {
+            variables: variables.to_vec(),
+            terms,
+        }
+    }
+
+    /// The factors `x^n` of `monomial`, restricted to the variables at `indices` if
+    /// given, with the placeholder variables of `atoms` replaced by their expressions.
+    fn monomial_factors<'s>(
+        &'s self,
+        monomial: &'s [u32],
+        indices: Option<&'s [usize]>,
+        atoms: &'s Atoms,
+    ) -> impl Iterator<Item = Expr> + 's {
+        self.variables
+            .iter()
+            .zip(monomial)
+            .enumerate()
+            .filter(move |(index, _)| match indices {
+                Some(indices) => indices.contains(index),
+                None => true,
+            })
+            .filter(|(_, (_, exp))| **exp > 0)
+            .map(move |(_, (var, exp))| match exp {
+                1 => atoms.variable_expr(var),
+                _ => Expr::function(system::Power, vec![
+                    atoms.variable_expr(var),
+                    Expr::from(i64::from(*exp)),
+                ]),
+            })
+    }
+}
+
+impl From<i64> for Polynomial {
+    fn from(value: i64) -> Polynomial {
+        Polynomial::constant(Vec::new(), Numeric::integer(value))
+    }
+}
+
+//======================================
+// Expr methods
+//======================================
+
+impl Expr {
+    /// Expand products and powers of sums in this expression, as in
+    /// [`Expand`](https://reference.wolfram.com/language/ref/Expand.html)
+    /// <sub>WL</sub>.
+    ///
+    /// Every symbol in the expression is treated as a variable. Sub-expressions which
+    /// are not polynomials, like `Sin[x]` or `x^(1/2)`, are treated as opaque
+    /// variables, so `(a + Sin[x])^2` expands to `a^2 + 2 a Sin[x] + Sin[x]^2`.
+    ///
+    /// Returns an error if a `Power` expression does not have exactly two elements, or
+    /// if a coefficient or exponent overflows.
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, Symbol};
+    ///
+    /// let call = |head: &str, elems| Expr::function(Symbol::new(head), elems);
+    /// let x = Expr::from(Symbol::new("Global`x"));
+    /// let y = Expr::from(Symbol::new("Global`y"));
+    ///
+    /// // (x + y) (x - y)
+    /// let expr = call("System`Times", vec![
+    ///     call("System`Plus", vec![x.clone(), y.clone()]),
+    ///     call("System`Subtract", vec![x.clone(), y.clone()]),
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     expr.expand().unwrap(),
+    ///     call("System`Plus", vec![
+    ///         call("System`Power", vec![x, Expr::from(2)]),
+    ///         call("System`Times", vec![
+    ///             Expr::from(-1),
+    ///             call("System`Power", vec![y, Expr::from(2)]),
+    ///         ]),
+    ///     ])
+    /// );
+    /// ```
+    pub fn expand(&self) -> Result<Expr, PolynomialError> {
+        let (polynomial, atoms) = self.to_polynomial()?;
+        Ok(polynomial.to_expr_with(&atoms))
+    }
+
+    /// Collect together terms of this expression with the same powers of `variables`,
+    /// as in [`Collect`](https://reference.wolfram.com/language/ref/Collect.html)
+    /// <sub>WL</sub>.
+    ///
+    /// Sub-expressions which are not polynomials are treated as in [`Expr::expand()`].
+    /// See [`Polynomial::collect()`].
+    pub fn collect(&self, variables: &[Symbol]) -> Result<Expr, PolynomialError> {
+        let (polynomial, atoms) = self.to_polynomial()?;
+        Ok(polynomial.collect_with(variables, &atoms))
+    }
+
+    /// The coefficient of `x^n` in this expression, as in
+    /// [`Coefficient`](https://reference.wolfram.com/language/ref/Coefficient.html)
+    /// <sub>WL</sub>.
+    ///
+    /// Sub-expressions which are not polynomials are treated as in [`Expr::expand()`].
+    pub fn coefficient(&self, x: &Symbol, n: u32) -> Result<Expr, PolynomialError> {
+        let (polynomial, atoms) = self.to_polynomial()?;
+        Ok(polynomial.coefficient(x, n).to_expr_with(&atoms))
+    }
+
+    /// The highest power of `x` in this expression, as in
+    /// [`Exponent`](https://reference.wolfram.com/language/ref/Exponent.html)
+    /// <sub>WL</sub>, or `None` if the expression is zero.
+    ///
+    /// Sub-expressions which are not polynomials are treated as in [`Expr::expand()`].
+    pub fn exponent(&self, x: &Symbol) -> Result<Option<u32>, PolynomialError> {
+        Ok(self.to_polynomial()?.0.degree(x))
+    }
+
+    /// Convert to a polynomial in every symbol which appears in this expression, and a
+    /// placeholder variable for each sub-expression which is not a polynomial.
+    fn to_polynomial(&self) -> Result<(Polynomial, Atoms), PolynomialError> {
+        let mut symbols = Vec::new();
+        collect_symbols(self, &mut symbols);
+
+        let mut atoms = Atoms::default();
+        let expr = atoms.replace_non_polynomial(self, &symbols)?;
+
+        let mut variables = Vec::new();
+        collect_symbols(&expr, &mut variables);
+        Ok((Polynomial::from_expr(&expr, &variables)?, atoms))
+    }
+}
+
+//======================================
+// Opaque sub-expressions
+//======================================
+
+/// Sub-expressions which are not polynomials, like `Sin[x]`, each of which is
+/// represented by a placeholder variable while an expression is converted to a
+/// [`Polynomial`].
+#[derive(Default)]
+struct Atoms {
+    placeholders: Vec<(Symbol, Expr)>,
+}
+
+impl Atoms {
+    /// Replace each sub-expression of `expr` which is not a polynomial with a
+    /// placeholder variable that is not one of `symbols`.
+    fn replace_non_polynomial(
+        &mut self,
+        expr: &Expr,
+        symbols: &[Symbol],
+    ) -> Result<Expr, PolynomialError> {
+        let normal = match expr.kind() {
+            ExprKind::Integer(_) | ExprKind::Symbol(_) => return Ok(expr.clone()),
+            ExprKind::Real(value) if value.is_finite() => return Ok(expr.clone()),
+            ExprKind::Normal(normal) => normal,
+            _ => return Ok(self.placeholder(expr, symbols)),
+        };
+
+        let mut replace = |elem: &Expr| self.replace_non_polynomial(elem, symbols);
+
+        match (normal.head.try_as_symbol().map(Symbol::as_str), normal.elements()) {
+            (Some("System`Rational"), _) if Numeric::from_expr(expr).is_some() => {
+                Ok(expr.clone())
+            },
+            (Some("System`Plus" | "System`Times"), elems)
+            | (Some("System`Subtract"), elems @ [_, _])
+            | (Some("System`Minus"), elems @ [_]) => {
+                let elems = elems.iter().map(replace).collect::<Result<_, _>>()?;
+                Ok(Expr::normal(normal.head.clone(), elems))
+            },
+            (Some("System`Divide"), [x, y]) => match Numeric::from_expr(y) {
+                Some(divisor) if !divisor.is_zero() => {
+                    let x = replace(x)?;
+                    Ok(Expr::normal(normal.head.clone(), vec![x, y.clone()]))
+                },
+                // x / y is x y^-1, where y^-1 is not a polynomial.
+                _ => {
+                    let x = replace(x)?;
+                    let recip = Expr::function(system::Power, vec![
+                        y.clone(),
+                        Expr::from(-1),
+                    ]);
+                    let recip = self.placeholder(&recip, symbols);
+                    Ok(Expr::function(system::Times, vec![x, recip]))
+                },
+            },
+            (Some("System`Power"), [base, exp]) => {
+                if let ExprKind::Integer(0..) = exp.kind() {
+                    let base = replace(base)?;
+                    return Ok(Expr::normal(normal.head.clone(), vec![base, exp.clone()]));
+                }
+
+                // A number to an integer power, like 2^-1, is a constant.
+                let value = Numeric::from_expr(base)
+                    .zip(Numeric::from_expr(exp).filter(|exp| exp.as_integer().is_some()))
+                    .and_then(|(base, exp)| base.checked_pow(exp));
+
+                Ok(match value {
+                    Some(value) => value.to_expr(),
+                    None => self.placeholder(expr, symbols),
+                })
+            },
+            (Some("System`Power"), _) => {
+                Err(PolynomialError::NotPolynomial { expr: expr.clone() })
+            },
+            _ => Ok(self.placeholder(expr, symbols)),
+        }
+    }
+
+    /// The placeholder variable for `expr`, which is the same for equal expressions.
+    fn placeholder(&mut self, expr: &Expr, symbols: &[Symbol]) -> Expr {
+        let existing = self.placeholders.iter().find(|(_, atom)| atom == expr);
+        if let Some((placeholder, _)) = existing {
+            return Expr::from(placeholder.clone());
+        }
+
+        let is_unused = |symbol: &Symbol| {
+            !symbols.contains(symbol)
+                && !self.placeholders.iter().any(|(placeholder, _)| placeholder == symbol)
+        };
+        let placeholder = (1..)
+            .map(|n| Symbol::new(&format!("Polynomial`Private`atom${}", n)))
+            .find(is_unused)
+            .unwrap();
+
+        self.placeholders.push((placeholder.clone(), expr.clone()));
+        Expr::from(placeholder)
+    }
+
+    /// The expression represented by the variable `var`.
+    fn variable_expr(&self, var: &Symbol) -> Expr {
+        match self.placeholders.iter().find(|(placeholder, _)| placeholder == var) {
+            Some((_, expr)) => expr.clone(),
+            None => Expr::from(var.clone()),
+        }
+    }
+}
+
+/// Add each symbol which appears in `expr`, other than as the head of a normal
+/// expression, to `symbols`.
+fn collect_symbols(expr: &Expr, symbols: &mut Vec<Symbol>) {
+    match expr.kind() {
+        ExprKind::Symbol(symbol) => {
+            if !symbols.contains(symbol) {
+                symbols.push(symbol.clone());
+            }
+        },
+        ExprKind::Normal(normal) => {
+            for elem in normal.elements() {
+                collect_symbols(elem, symbols);
+            }
+        },
+        ExprKind::Integer(_)
+        | ExprKind::Real(_)
+        | ExprKind::String(_)
+        | ExprKind::Association(_) => (),
+    }
+}
+
+/// `Plus[terms...]`, with the terms sorted, or the single term if there is only one.
+fn sum(mut terms: Vec<Expr>) -> Expr {
+    match terms.len() {
+        0 => Expr::from(0),
+        1 => terms.pop().unwrap(),
+        _ => {
+            terms.sort();
+            Expr::function(system::Plus, terms)
+        },
+    }
+}
+
+/// `Times[factors...]`, with nested products flattened, factors of `1` removed, and
+/// the factors sorted.
+fn product(factors: Vec<Expr>) -> Expr {
+    let mut flat = Vec::with_capacity(factors.len());
+
+    for factor in factors {
+        match as_normal_with_head(&factor, "System`Times") {
+            Some(inner) => flat.extend(inner.elements().iter().cloned()),
+            None if factor == Expr::from(1) => (),
+            None => flat.push(factor),
+        }
+    }
+
+    match flat.len() {
+        0 => Expr::from(1),
+        1 => flat.pop().unwrap(),
+        _ => {
+            flat.sort();
+            Expr::function(system::Times, flat)
+        },
+    }
+}
+
+impl fmt::Display for PolynomialError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolynomialError::NotPolynomial { expr } => {
+                write!(f, "{} is not a polynomial.", expr)
+            },
+            PolynomialError::Overflow => {
+                write!(f, "Polynomial arithmetic overflowed a machine integer.")
+            },
+        }
+    }
+}
+
+impl std::error::Error for PolynomialError {}
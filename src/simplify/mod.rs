@@ -0,0 +1,286 @@
+//! Algebraic simplification of arithmetic expressions.
+
+use std::fmt;
+
+use crate::{
+    attributes::{flatten_and_sort, AttributeTable, Attributes},
+    evaluate::{add, multiply, raise},
+    Expr, ExprKind, Symbol,
+};
+
+/// Simplifies arithmetic expressions, using built-in arithmetic identities and
+/// user-supplied rules.
+///
+/// The built-in simplifications are the arithmetic rules used by
+/// [`Evaluator`][crate::Evaluator]:
+///
+/// * Nested `Plus` and `Times` expressions are flattened, and their elements are
+///   sorted into [canonical order][Expr::canonical_cmp].
+/// * Numbers are added, multiplied and exponentiated exactly, e.g. `2 + 1/2` becomes
+///   `5/2`.
+/// * Identities and annihilators are eliminated: `x + 0`, `x * 1`, `x^1` and `x * 0`
+///   become `x`, `x`, `x` and `0`, and `x^0` and `1^x` become `1`.
+/// * Like terms are combined, e.g. `x + 2 x` becomes `3 x`.
+/// * Powers with the same base are merged, e.g. `x * x^2` becomes `x^3`, and
+///   `(x^2)^3` becomes `x^6`.
+/// * `Subtract`, `Minus` and `Divide` are rewritten in terms of `Plus`, `Times` and
+///   `Power`.
+///
+/// Expressions with other heads are left unchanged, though their elements are
+/// simplified unless they are held by the [attributes][AttributeTable::system] of the
+/// head.
+///
+/// # Example
+///
+/// Simplify `f[Plus[x, 0], Times[1, y], Power[z, 1]]` with an extra rule that
+/// rewrites `Sin[0]` to `0`:
+///
+/// ```
+/// use wolfram_expr::{Expr, Simplifier, Symbol};
+///
+/// let call = |head: &str, elems| Expr::function(Symbol::new(head), elems);
+/// let sym = |name: &str| Expr::symbol(Symbol::new(name));
+///
+/// let sin_0 = call("System`Sin", vec![Expr::from(0)]);
+///
+/// let mut simplifier = Simplifier::new();
+/// simplifier.add_rule(move |expr| match *expr == sin_0 {
+///     true => Some(Expr::from(0)),
+///     false => None,
+/// });
+///
+/// let expr = call("Global`f", vec![
+///     call("System`Plus", vec![
+///         sym("Global`x"),
+///         call("System`Sin", vec![Expr::from(0)]),
+///     ]),
+///     call("System`Times", vec![Expr::from(1), sym("Global`y")]),
+///     call("System`Power", vec![sym("Global`z"), Expr::from(1)]),
+/// ]);
+///
+/// assert_eq!(
+///     simplifier.simplify(&expr),
+///     call("Global`f", vec![sym("Global`x"), sym("Global`y"), sym("Global`z")])
+/// );
+/// ```
+pub struct Simplifier {
+    rules: Vec<Rule>,
+    iteration_limit: usize,
+}
+
+/// A user rule added with [`Simplifier::add_rule()`].
+type Rule = Box<dyn Fn(&Expr) -> Option<Expr> + Send + Sync>;
+
+impl Expr {
+    /// Simplify this expression using the built-in arithmetic identities of
+    /// [`Simplifier`].
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, Symbol};
+    ///
+    /// let call = |head: &str, elems| Expr::function(Symbol::new(head), elems);
+    /// let x = Expr::symbol(Symbol::new("Global`x"));
+    ///
+    /// // x + 0 + Plus[x, 2 x]
+    /// let expr = call("System`Plus", vec![
+    ///     x.clone(),
+    ///     Expr::from(0),
+    ///     call("System`Plus", vec![
+    ///         x.clone(),
+    ///         call("System`Times", vec![Expr::from(2), x.clone()]),
+    ///     ]),
+    /// ]);
+    ///
+    /// assert_eq!(expr.simplify(), call("System`Times", vec![Expr::from(4), x]));
+    /// ```
+    pub fn simplify(&self) -> Expr {
+        Simplifier::new().simplify(self)
+    }
+}
+
+impl Simplifier {
+    /// Default value of [`Simplifier::iteration_limit()`].
+    pub const DEFAULT_ITERATION_LIMIT: usize = 4096;
+
+    /// Construct a new simplifier with no user rules.
+    pub fn new() -> Self {
+        Simplifier {
+            rules: Vec::new(),
+            iteration_limit: Simplifier::DEFAULT_ITERATION_LIMIT,
+        }
+    }
+
+    /// Add a rule, which is tried on each sub-expression after its elements and the
+    /// built-in identities have been simplified.
+    ///
+    /// A rule returns `None` if it does not apply to the given expression. Otherwise the
+    /// expression is replaced by the returned expression, which is then simplified
+    /// again. Rules are tried in the order they were added.
+    pub fn add_rule<F>(&mut self, rule: F)
+    where
+        F: Fn(&Expr) -> Option<Expr> + Send + Sync + 'static,
+    {
+        self.rules.push(Box::new(rule));
+    }
+
+    /// The maximum number of times user rules will be applied while simplifying an
+    /// expression.
+    ///
+    /// Once the limit is reached, no more rules are applied, and the expression is
+    /// returned with only the built-in simplifications applied to the remaining
+    /// sub-expressions.
+    pub fn iteration_limit(&self) -> usize {
+        self.iteration_limit
+    }
+
+    /// Set the maximum number of times user rules will be applied while simplifying an
+    /// expression.
+    pub fn set_iteration_limit(&mut self, limit: usize) {
+        self.iteration_limit = limit;
+    }
+
+    /// Simplify `expr`.
+    pub fn simplify(&self, expr: &Expr) -> Expr {
+        let mut iterations = 0;
+        self.simplify_with(expr, &mut iterations)
+    }
+
+    fn simplify_with(&self, expr: &Expr, iterations: &mut usize) -> Expr {
+        let expr = match expr.kind() {
+            ExprKind::Normal(normal) => {
+                let head = self.simplify_with(&normal.head, iterations);
+                let attrs = match head.kind() {
+                    ExprKind::Symbol(symbol) => AttributeTable::system().get(symbol),
+                    _ => Attributes::empty(),
+                };
+
+                let contents: Vec<Expr> = normal
+                    .elements()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, elem)| match attrs.holds(index) {
+                        true => elem.clone(),
+                        false => self.simplify_with(elem, iterations),
+                    })
+                    .collect();
+
+                builtin_identities(head, contents)
+            },
+            _ => expr.clone(),
+        };
+
+        if *iterations >= self.iteration_limit {
+            return expr;
+        }
+
+        for rule in &self.rules {
+            if let Some(result) = rule(&expr) {
+                if result != expr {
+                    *iterations += 1;
+                    return self.simplify_with(&result, iterations);
+                }
+            }
+        }
+
+        expr
+    }
+}
+
+/// Construct `head[contents...]`, applying the built-in identities if `head` is an
+/// arithmetic function.
+fn builtin_identities(head: Expr, mut contents: Vec<Expr>) -> Expr {
+    let name = match head.try_as_symbol() {
+        Some(symbol) => symbol.as_str(),
+        None => return Expr::normal(head, contents),
+    };
+
+    let minus_one = || Expr::from(-1);
+
+    match name {
+        "System`Plus" | "System`Times" => arithmetic(name, contents),
+        "System`Power" if contents.len() == 2 => arithmetic(name, contents),
+        // x - y == x + (-1 * y)
+        "System`Subtract" if contents.len() == 2 => {
+            let y = contents.pop().unwrap();
+            let y = arithmetic("System`Times", vec![minus_one(), y]);
+            contents.push(y);
+            arithmetic("System`Plus", contents)
+        },
+        // -x == -1 * x
+        "System`Minus" if contents.len() == 1 => {
+            contents.insert(0, minus_one());
+            arithmetic("System`Times", contents)
+        },
+        // x / y == x * y^-1
+        "System`Divide" if contents.len() == 2 => {
+            let y = contents.pop().unwrap();
+            let y = arithmetic("System`Power", vec![y, minus_one()]);
+            contents.push(y);
+            arithmetic("System`Times", contents)
+        },
+        _ => Expr::normal(head, contents),
+    }
+}
+
+/// Construct `head[contents...]`, where `head` is `Plus`, `Times` or `Power`, and
+/// simplify it using the arithmetic rules of the evaluator.
+///
+/// `contents` are assumed to already be simplified.
+pub(crate) fn arithmetic(head: &str, contents: Vec<Expr>) -> Expr {
+    let symbol = Symbol::new(head);
+    let attrs = AttributeTable::system().get(&symbol);
+    let symbol = Expr::symbol(symbol);
+
+    let contents = flatten_and_sort(&symbol, contents, attrs);
+
+    let simplified = match (head, contents.as_slice()) {
+        ("System`Plus", _) => add(&contents),
+        ("System`Times", _) => multiply(&contents),
+        ("System`Power", [base, exp]) => raise(base, exp),
+        _ => None,
+    };
+
+    let expr = Expr::normal(symbol, contents);
+
+    match simplified {
+        // The simplified expression may contain new unsimplified sub-expressions, e.g.
+        // x * x^2 becomes x^(1 + 2).
+        Some(simplified) if simplified != expr => resimplify(&simplified),
+        _ => expr,
+    }
+}
+
+/// Re-simplify the `Plus`, `Times` and `Power` sub-expressions of `expr`.
+fn resimplify(expr: &Expr) -> Expr {
+    let normal = match expr.kind() {
+        ExprKind::Normal(normal) => normal,
+        _ => return expr.clone(),
+    };
+
+    match normal.head.try_as_symbol().map(Symbol::as_str) {
+        Some(head @ ("System`Plus" | "System`Times" | "System`Power")) => {
+            arithmetic(head, normal.elements().iter().map(resimplify).collect())
+        },
+        _ => expr.clone(),
+    }
+}
+
+impl Default for Simplifier {
+    fn default() -> Self {
+        Simplifier::new()
+    }
+}
+
+impl fmt::Debug for Simplifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Simplifier {
+            rules,
+            iteration_limit,
+        } = self;
+
+        f.debug_struct("Simplifier")
+            .field("rules", &rules.len())
+            .field("iteration_limit", iteration_limit)
+            .finish()
+    }
+}
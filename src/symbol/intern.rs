@@ -0,0 +1,105 @@
+//! Global interning of [`Symbol`][super::Symbol] strings.
+//!
+//! Every `Symbol` is created through [`intern()`], which guarantees that two live
+//! symbols with the same name share the same allocation. This allows `Symbol`
+//! equality and hashing to operate on the pointer instead of the string.
+//!
+//! The table holds only weak references, so the string of a symbol is freed as soon as
+//! the last `Symbol` referring to it is dropped. The table entries left behind by
+//! freed symbols are removed lazily: whenever a bucket is searched, and in a full sweep
+//! each time the number of entries doubles.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, OnceLock, PoisonError, Weak},
+};
+
+struct Interner {
+    /// Weak references to the interned strings, keyed by the hash of the string.
+    buckets: HashMap<u64, Vec<Weak<String>>>,
+    /// The total number of entries in `buckets`, including dead ones.
+    len: usize,
+    /// Sweep out dead entries once `len` reaches this value.
+    sweep_at: usize,
+}
+
+static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+
+const MIN_SWEEP_AT: usize = 1024;
+
+/// Return the shared string for `name`, allocating and recording a new one if no live
+/// symbol has that name.
+pub(super) fn intern<S: AsRef<str> + Into<String>>(name: S) -> Arc<String> {
+    let mut interner = INTERNER
+        .get_or_init(|| {
+            Mutex::new(Interner {
+                buckets: HashMap::new(),
+                len: 0,
+                sweep_at: MIN_SWEEP_AT,
+            })
+        })
+        .lock()
+        // The table is never left in an inconsistent state, so a panic in another
+        // thread while holding the lock is harmless.
+        .unwrap_or_else(PoisonError::into_inner);
+
+    interner.get_or_insert(name)
+}
+
+impl Interner {
+    fn get_or_insert<S: AsRef<str> + Into<String>>(&mut self, name: S) -> Arc<String> {
+        let hash = {
+            let mut hasher = DefaultHasher::new();
+            name.as_ref().hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let bucket = self.buckets.entry(hash).or_default();
+
+        let mut found = None;
+        let before = bucket.len();
+        bucket.retain(|weak| match weak.upgrade() {
+            Some(string) => {
+                if found.is_none() && string.as_str() == name.as_ref() {
+                    found = Some(string);
+                }
+                true
+            },
+            None => false,
+        });
+        let removed = before - bucket.len();
+
+        let string = match found {
+            Some(string) => string,
+            None => {
+                let string = Arc::new(name.into());
+                bucket.push(Arc::downgrade(&string));
+                self.len += 1;
+                string
+            },
+        };
+
+        if bucket.is_empty() {
+            self.buckets.remove(&hash);
+        }
+        self.len -= removed;
+
+        if self.len >= self.sweep_at {
+            self.sweep();
+        }
+
+        string
+    }
+
+    /// Remove the entries for every symbol which has been freed.
+    fn sweep(&mut self) {
+        self.buckets.retain(|_, bucket| {
+            bucket.retain(|weak| weak.strong_count() > 0);
+            !bucket.is_empty()
+        });
+
+        self.len = self.buckets.values().map(Vec::len).sum();
+        self.sweep_at = usize::max(2 * self.len, MIN_SWEEP_AT);
+    }
+}
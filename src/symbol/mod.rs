@@ -25,14 +25,19 @@
 //!
 //! [ref/SymbolNamesAndContexts]: https://reference.wolfram.com/language/tutorial/InputSyntax.html#6562
 
+mod intern;
 pub(crate) mod parse;
 
 use std::{
+    cmp::Ordering,
     fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
     mem,
     sync::Arc,
 };
 
+use crate::system::SystemSymbol;
+
 
 /* Notes
 
@@ -57,8 +62,17 @@ Operations on Symbols
 /// `` System`Order `` for symbols (and does *not* match it at the moment).
 ///
 /// This type implements `PartialOrd`/`Ord` primarily for the purposes of allowing
-/// instances of this type to be included in ordered sets (e.g. `BTreeMap`).
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// instances of this type to be included in ordered sets (e.g. `BTreeMap`). Use
+/// [`Symbol::canonical_cmp()`] to compare symbols using the Wolfram Language canonical
+/// order.
+///
+/// # Interning
+///
+/// Symbols are interned: all live `Symbol`s with the same name share a single
+/// allocation, so comparing symbols for equality and hashing them do not need to
+/// examine the name. The name of a symbol is freed when the last `Symbol` referring to
+/// it is dropped.
+#[derive(Debug, Clone)]
 #[repr(C)]
 pub struct Symbol(Arc<String>);
 
@@ -98,7 +112,14 @@ impl Symbol {
     /// An absolute symbol is a symbol with an explicit context path. ``"System`Plus"`` is
     /// an absolute symbol, ``"Plus"`` is a relative symbol and/or a [`SymbolName`].
     /// ``"`Plus"`` is also a relative symbol.
+    ///
+    /// Common `` System` `` symbols are returned from a cache without allocating; see
+    /// [`system`][crate::system].
     pub fn try_new(input: &str) -> Option<Self> {
+        if let Some(symbol) = SystemSymbol::lookup(input) {
+            return Some(symbol.symbol().clone());
+        }
+
         let sym_ref = SymbolRef::try_new(input)?;
 
         Some(sym_ref.to_symbol())
@@ -116,7 +137,7 @@ impl Symbol {
     ///
     /// ```
     /// # use wolfram_expr::{Expr, Symbol};
-    /// let expr = Expr::normal(Symbol::new("MyPackage`Foo"), vec![]);
+    /// let expr = Expr::function(Symbol::new("MyPackage`Foo"), vec![]);
     /// ```
     ///
     /// If not using a string literal as the argument, prefer to use [`Symbol::try_new`]
@@ -294,7 +315,7 @@ impl RelativeContext {
 }
 
 macro_rules! common_impls {
-    ($ty:ident) => {
+    ($ty:ident, $new:path) => {
         impl Display for $ty {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
                 let $ty(string) = self;
@@ -323,18 +344,62 @@ macro_rules! common_impls {
             /// rustc were the function not marked `unsafe`. However, this function is so
             /// often *not* what is really needed, it's marked unsafe as a deterent to
             /// possible users.
-            pub(crate) unsafe fn unchecked_new<S: Into<String>>(input: S) -> $ty {
-                let inner: Arc<String> = Arc::new(input.into());
+            pub(crate) unsafe fn unchecked_new<S>(input: S) -> $ty
+            where
+                S: AsRef<str> + Into<String>,
+            {
+                let inner: Arc<String> = $new(input);
                 $ty(inner)
             }
         }
     };
 }
 
-common_impls! { Symbol }
-common_impls!(SymbolName);
-common_impls!(Context);
-common_impls!(RelativeContext);
+common_impls! { Symbol, intern::intern }
+common_impls!(SymbolName, new_inner);
+common_impls!(Context, new_inner);
+common_impls!(RelativeContext, new_inner);
+
+fn new_inner<S: Into<String>>(input: S) -> Arc<String> {
+    Arc::new(input.into())
+}
+
+//======================================
+// Comparison impls
+//======================================
+
+// These rely on symbols being interned: two symbols are equal exactly when they share
+// the same allocation.
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Symbol) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state)
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Symbol) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Symbol) -> Ordering {
+        if self == other {
+            return Ordering::Equal;
+        }
+
+        self.as_str().cmp(other.as_str())
+    }
+}
 
 //======================================
 // Formatting impls
@@ -387,11 +452,3 @@ impl Symbol {
     // }
 }
 */
-
-// impl PartialEq<str> for Symbol {
-//     fn eq(&self, other: &str) -> bool {
-//         let mut lock = acquire_lock();
-//         let other_sym: usize = lock.get_or_intern(other);
-//         self.0 == other_sym
-//     }
-// }
@@ -59,7 +59,7 @@ impl<'s> SymbolRef<'s> {
     /// Convert this borrowed string into an owned [`Symbol`].
     pub fn to_symbol(&self) -> Symbol {
         let SymbolRef(string) = self;
-        unsafe { Symbol::unchecked_new(string.to_owned()) }
+        unsafe { Symbol::unchecked_new(*string) }
     }
 
     #[doc(hidden)]
@@ -0,0 +1,245 @@
+//! Cached `` System` `` symbols.
+//!
+//! Each constant in this module names a common `` System` `` symbol, e.g.
+//! [`system::List`][List] for `` System`List ``. The [`Symbol`] and [`Expr`] for every
+//! constant are allocated once, the first time any of them is used, and are shared
+//! afterwards, so converting a constant into a `Symbol` or `Expr` only increments a
+//! reference count.
+//!
+//! [`Symbol::new()`] and [`Expr::symbol()`] also return the cached values when given
+//! one of these symbols, as do construction helpers like [`Expr::list()`],
+//! [`Expr::rule()`] and [`Expr::null()`].
+//!
+//! This module contains constants named `None` and `String`, so it should be imported
+//! as a module rather than with a glob import.
+//!
+//! # Example
+//!
+//! ```
+//! use wolfram_expr::{system, Expr, Symbol};
+//!
+//! let list = Expr::function(system::List, vec![Expr::from(1), Expr::from(2)]);
+//!
+//! assert_eq!(list, Expr::list(vec![Expr::from(1), Expr::from(2)]));
+//! assert_eq!(system::List.as_str(), "System`List");
+//! assert_eq!(Symbol::from(system::True), Symbol::new("System`True"));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use crate::symbol::SymbolRef;
+use crate::{Expr, ExprKind, Symbol};
+
+/// A common `` System` `` symbol, whose [`Symbol`] and [`Expr`] values are cached.
+///
+/// See the [module documentation][self] for the available symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemSymbol(u16);
+
+struct Cache {
+    symbols: Vec<Symbol>,
+    exprs: Vec<Expr>,
+    indices: HashMap<&'static str, u16>,
+}
+
+static CACHE: OnceLock<Cache> = OnceLock::new();
+
+fn cache() -> &'static Cache {
+    CACHE.get_or_init(|| {
+        let symbols: Vec<Symbol> = NAMES
+            .iter()
+            .map(|name| {
+                debug_assert!(SymbolRef::try_new(name).is_some());
+                unsafe { Symbol::unchecked_new(*name) }
+            })
+            .collect();
+
+        // Construct the expressions directly: `Expr::symbol()` consults this cache.
+        let exprs = symbols
+            .iter()
+            .map(|symbol| Expr {
+                inner: Arc::new(ExprKind::Symbol(symbol.clone())),
+            })
+            .collect();
+
+        let indices = NAMES
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (*name, index as u16))
+            .collect();
+
+        Cache {
+            symbols,
+            exprs,
+            indices,
+        }
+    })
+}
+
+impl SystemSymbol {
+    /// Look up the cached symbol with the absolute name `name`, e.g. ``"System`List"``.
+    ///
+    /// Returns `None` if `name` is not one of the symbols in this module.
+    pub fn lookup(name: &str) -> Option<SystemSymbol> {
+        if !name.starts_with("System`") {
+            return Option::None;
+        }
+
+        cache().indices.get(name).copied().map(SystemSymbol)
+    }
+
+    /// The absolute name of this symbol, e.g. ``"System`List"``.
+    pub fn as_str(self) -> &'static str {
+        NAMES[usize::from(self.0)]
+    }
+
+    /// The cached [`Symbol`].
+    pub fn symbol(self) -> &'static Symbol {
+        &cache().symbols[usize::from(self.0)]
+    }
+
+    /// The cached symbol [`Expr`].
+    pub fn expr(self) -> &'static Expr {
+        &cache().exprs[usize::from(self.0)]
+    }
+
+    /// Iterate over every symbol in this module.
+    pub fn all() -> impl Iterator<Item = SystemSymbol> {
+        (0..NAMES.len() as u16).map(SystemSymbol)
+    }
+}
+
+impl From<SystemSymbol> for Symbol {
+    fn from(symbol: SystemSymbol) -> Symbol {
+        symbol.symbol().clone()
+    }
+}
+
+impl From<SystemSymbol> for Expr {
+    fn from(symbol: SystemSymbol) -> Expr {
+        symbol.expr().clone()
+    }
+}
+
+impl PartialEq<Symbol> for SystemSymbol {
+    fn eq(&self, other: &Symbol) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl PartialEq<SystemSymbol> for Symbol {
+    fn eq(&self, other: &SystemSymbol) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl fmt::Display for SystemSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+//======================================
+// Symbols
+//======================================
+
+macro_rules! system_symbols {
+    ($($name:ident),* $(,)?) => {
+        #[allow(clippy::upper_case_acronyms)]
+        enum Index {
+            $($name),*
+        }
+
+        const NAMES: &[&str] = &[$(concat!("System`", stringify!($name))),*];
+
+        $(
+            #[doc = concat!(
+                "[`", stringify!($name), "`](https://reference.wolfram.com/language/ref/",
+                stringify!($name), ".html) <sub>WL</sub>."
+            )]
+            #[allow(non_upper_case_globals)]
+            pub const $name: SystemSymbol = SystemSymbol(Index::$name as u16);
+        )*
+    };
+}
+
+system_symbols! {
+    // Structure and constants
+    List, Association, Rule, RuleDelayed, Sequence, Nothing, Null, None, All, Automatic,
+    Inherited, True, False, Missing, Failure, Indeterminate, Infinity, ComplexInfinity,
+    DirectedInfinity, Pi, E, I, Degree, EulerGamma, GoldenRatio, Integers, Reals,
+    Complexes, Key, Identity, Composition, RightComposition,
+
+    // Atomic types
+    Integer, Real, Rational, Complex, String, Symbol, ByteArray, NumericArray,
+    SparseArray, Image,
+
+    // Evaluation control
+    Hold, HoldComplete, HoldForm, HoldPattern, ReleaseHold, Unevaluated, Evaluate,
+    Function, Slot, SlotSequence, CompoundExpression, Set, SetDelayed, Unset, Clear,
+    ClearAll, Module, Block, With, If, Which, Switch, Do, For, While, Return, Break,
+    Continue, Throw, Catch, Abort, Check, Quiet, Message, MessageName, Print, Echo,
+    Increment, Decrement, PreIncrement, PreDecrement, AddTo, SubtractFrom, TimesBy,
+    DivideBy, AppendTo, PrependTo,
+
+    // Patterns
+    Blank, BlankSequence, BlankNullSequence, Pattern, Condition, PatternTest,
+    Alternatives, Optional, Repeated, RepeatedNull, Except, Verbatim, KeyValuePattern,
+    OptionsPattern, OptionValue, Options, FilterRules, Default, Replace, ReplaceAll,
+    ReplaceRepeated, Dispatch,
+
+    // Attributes
+    Attributes, SetAttributes, ClearAttributes, Protect, Unprotect, Protected, Locked,
+    ReadProtected, HoldAll, HoldFirst, HoldRest, HoldAllComplete, SequenceHold, Flat,
+    Orderless, OneIdentity, Listable, NumericFunction, Constant, Temporary,
+
+    // Logic and predicates
+    And, Or, Not, Xor, Nand, Nor, Implies, Equal, Unequal, Less, LessEqual, Greater,
+    GreaterEqual, SameQ, UnsameQ, TrueQ, MatchQ, FreeQ, MemberQ, AtomQ, NumberQ,
+    NumericQ, IntegerQ, EvenQ, OddQ, StringQ, ListQ, AssociationQ, KeyExistsQ, OrderedQ,
+    Element, Order,
+
+    // Arithmetic and elementary functions
+    Plus, Times, Power, Subtract, Minus, Divide, Sqrt, Exp, Log, Log2, Log10, Sin, Cos,
+    Tan, Cot, Sec, Csc, ArcSin, ArcCos, ArcTan, Sinh, Cosh, Tanh, Abs, Sign, Floor,
+    Ceiling, Round, IntegerPart, FractionalPart, Mod, Quotient, GCD, LCM, Factorial,
+    Binomial, Max, Min, Re, Im, Conjugate, Arg, Numerator, Denominator, N, Chop,
+    Precision, Accuracy, SetPrecision, MachinePrecision, Interval, Root,
+
+    // Algebra and calculus
+    D, Derivative, Integrate, Sum, Product, Limit, Series, Simplify, FullSimplify,
+    Expand, Factor, Together, Apart, Collect, Solve, Reduce, Normal, Coefficient,
+    CoefficientList, Exponent, Variables, Assumptions, WorkingPrecision, PrecisionGoal,
+    AccuracyGoal, MaxIterations,
+
+    // Lists and associations
+    Head, Length, Depth, Dimensions, Level, Part, Span, Take, Drop, First, Last, Most,
+    Rest, Append, Prepend, Insert, Delete, ReplacePart, Extract, Join, Union,
+    Intersection, Complement, Range, Array, ConstantArray, Table, Reverse, Sort, SortBy,
+    Ordering, DeleteDuplicates, Tally, Count, Position, Cases, DeleteCases, Select, Map,
+    MapAt, MapIndexed, MapThread, Apply, Scan, Fold, FoldList, Nest, NestList,
+    FixedPoint, Thread, Transpose, Flatten, Partition, Riffle, Total, Accumulate, Dot,
+    Inner, Outer, Keys, Values, Lookup, KeyDrop, KeyTake, KeySort, Merge, GroupBy,
+
+    // Strings
+    StringJoin, StringLength, StringSplit, StringReplace, StringTake, StringDrop,
+    StringContainsQ, StringMatchQ, StringTemplate, TemplateApply, TemplateSlot,
+    ToString, ToExpression, ToUpperCase, ToLowerCase, Characters,
+
+    // Formatting
+    FullForm, InputForm, OutputForm, StandardForm, TraditionalForm, Row, Column, Grid,
+    Style, Interpretation, RawBoxes, RowBox, Text, Quantity, DateObject, Entity,
+
+    // Graphics and common options
+    Graphics, Graphics3D, Plot, ListPlot, ListLinePlot, PlotRange, PlotStyle,
+    PlotLabel, Axes, AxesLabel, Frame, FrameLabel, ImageSize, AspectRatio, Method,
+    Directive, RGBColor, GrayLevel, Hue, Opacity, Thickness, PointSize, Dashing, Point,
+    Line, Polygon, Circle, Disk, Rectangle, Arrow, FontSize, FontFamily, FontWeight,
+    FontSlant, Bold, Italic, Plain, Red, Green, Blue, Black, White, Gray, Left, Right,
+    Center, Top, Bottom,
+
+    // Compilation and timing
+    Compile, CompiledFunction, Timing, AbsoluteTiming,
+}
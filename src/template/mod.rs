@@ -0,0 +1,224 @@
+//! Expression templates, written in Wolfram Language syntax.
+//!
+//! An [`ExprTemplate`] is parsed once, and then applied to [`Expr`] values any number
+//! of times. Values are substituted into the parsed expression structurally, so unlike
+//! formatting WL source code with [`format!`], a value can never change the meaning of
+//! the surrounding template:
+//!
+//! ```
+//! use wolfram_expr::{Expr, ExprTemplate};
+//!
+//! let template = ExprTemplate::parse("Plot[`1`, {x, 0, `2`}]").unwrap();
+//!
+//! // The string is inserted as a single string atom, not as source code.
+//! let expr = template.apply(&[Expr::string("x] ; Quit["), Expr::from(5)]).unwrap();
+//!
+//! assert_eq!(
+//!     expr.to_string(),
+//!     "System`Plot[\"x] ; Quit[\", System`List[Global`x, 0, 5]]"
+//! );
+//! ```
+
+mod parse;
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{system, Association, Expr, ExprKind, Normal, Number};
+
+/// An expression containing placeholders, which are replaced by [`Expr`] values
+/// when the template is applied.
+///
+/// The placeholders are:
+///
+/// * [`TemplateSlot`](https://reference.wolfram.com/language/ref/TemplateSlot.html)
+///   <sub>WL</sub>, written `` `1` ``, `` `name` ``, or `` `` `` for the next
+///   positional slot.
+/// * [`Slot`](https://reference.wolfram.com/language/ref/Slot.html) <sub>WL</sub>,
+///   written `#1`, `#name`, or `#` for `#1`. A `Slot` inside the body of a nested
+///   pure function, like `# + 1 &`, belongs to that function, and is left unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExprTemplate {
+    expr: Expr,
+}
+
+/// Error returned when parsing or applying an [`ExprTemplate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    /// The template source is not valid Wolfram Language syntax.
+    Syntax {
+        /// The byte offset of the error in the template source.
+        offset: usize,
+        #[allow(missing_docs)]
+        message: String,
+    },
+    /// No value was given for a placeholder in the template.
+    MissingSlot {
+        /// The placeholder, e.g. `` TemplateSlot[2] ``.
+        slot: Expr,
+    },
+}
+
+impl ExprTemplate {
+    /// Parse a template from Wolfram Language syntax.
+    ///
+    /// Symbol names without a context, like `Plot` or `x`, refer to the
+    /// `` System` `` symbol if it is in the [`system`][crate::system] module, and to a
+    /// `` Global` `` symbol otherwise.
+    pub fn parse(source: &str) -> Result<ExprTemplate, TemplateError> {
+        let expr = parse::parse(source)?;
+        Ok(ExprTemplate { expr })
+    }
+
+    /// Construct a template from an expression that contains placeholders.
+    pub fn new(expr: Expr) -> ExprTemplate {
+        ExprTemplate { expr }
+    }
+
+    /// The template expression, including its placeholders.
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Replace each positional placeholder `` `n` `` or `#n` with `args[n - 1]`.
+    ///
+    /// Returns an error if a placeholder is named, or if `args` has no value for it.
+    ///
+    /// ```
+    /// use wolfram_expr::{Expr, ExprTemplate};
+    ///
+    /// let template = ExprTemplate::parse("f[``, #2 + ``]").unwrap();
+    /// let expr = template.apply(&[Expr::from(1), Expr::from(2)]).unwrap();
+    ///
+    /// assert_eq!(expr.to_string(), "Global`f[1, System`Plus[2, 2]]");
+    /// ```
+    pub fn apply(&self, args: &[Expr]) -> Result<Expr, TemplateError> {
+        substitute(&self.expr, true, &mut |key| {
+            let index = key.try_as_number().and_then(|number| match number {
+                Number::Integer(index) => usize::try_from(index).ok(),
+                Number::Real(_) => None,
+            })?;
+            args.get(index.checked_sub(1)?).cloned()
+        })
+    }
+
+    /// Replace each placeholder with the value of its key in `values`.
+    ///
+    /// The key of `` `name` `` and `#name` is the string `"name"`, and the key of
+    /// `` `n` `` and `#n` is the integer `n`. Returns an error if `values` has no
+    /// value for a placeholder.
+    ///
+    /// ```
+    /// use wolfram_expr::{Association, Expr, ExprTemplate};
+    ///
+    /// let template = ExprTemplate::parse("Range[`min`, #max]").unwrap();
+    ///
+    /// let mut values = Association::new();
+    /// values.insert("min", Expr::from(1));
+    /// values.insert("max", Expr::from(10));
+    ///
+    /// let expr = template.apply_named(&values).unwrap();
+    /// assert_eq!(expr.to_string(), "System`Range[1, 10]");
+    /// ```
+    pub fn apply_named(&self, values: &Association) -> Result<Expr, TemplateError> {
+        substitute(&self.expr, true, &mut |key| {
+            values.get_rule(key).map(|(_, value)| value.clone())
+        })
+    }
+}
+
+/// Replace the placeholders in `expr` with the value of their key returned by
+/// `value`.
+///
+/// `in_slot_scope` is `false` inside the body of a nested pure function, whose `Slot`s
+/// are left unchanged.
+fn substitute(
+    expr: &Expr,
+    in_slot_scope: bool,
+    value: &mut dyn FnMut(&Expr) -> Option<Expr>,
+) -> Result<Expr, TemplateError> {
+    let normal = match expr.kind() {
+        ExprKind::Normal(normal) => normal,
+        ExprKind::Association(assoc) => {
+            let mut error = None;
+            let assoc = assoc.map_rules(|elem| {
+                substitute(elem, in_slot_scope, value).unwrap_or_else(|err| {
+                    error.get_or_insert(err);
+                    elem.clone()
+                })
+            });
+            return match error {
+                Some(err) => Err(err),
+                None => Ok(Expr::from(assoc)),
+            };
+        },
+        _ => return Ok(expr.clone()),
+    };
+
+    let is_placeholder = normal.has_head(system::TemplateSlot.symbol())
+        || (in_slot_scope && normal.has_head(system::Slot.symbol()));
+
+    if is_placeholder {
+        return match normal.elements() {
+            [key] => value(key).ok_or_else(|| TemplateError::MissingSlot {
+                slot: expr.clone(),
+            }),
+            _ => Ok(expr.clone()),
+        };
+    }
+
+    let in_slot_scope = in_slot_scope && !is_pure_function(normal);
+
+    let head = substitute(normal.head(), in_slot_scope, value)?;
+    let elements = normal
+        .elements()
+        .iter()
+        .map(|elem| substitute(elem, in_slot_scope, value))
+        .collect::<Result<Vec<Expr>, TemplateError>>()?;
+
+    Ok(Expr::normal(head, elements))
+}
+
+/// Returns `true` if `normal` is `Function[body]` or `Function[Null, body, attrs]`,
+/// whose body has its own `Slot`s.
+fn is_pure_function(normal: &Normal) -> bool {
+    if !normal.has_head(system::Function.symbol()) {
+        return false;
+    }
+
+    match normal.elements() {
+        [_] => true,
+        [params, _, _] => params.try_as_symbol().is_some_and(|s| *s == system::Null),
+        _ => false,
+    }
+}
+
+impl FromStr for ExprTemplate {
+    type Err = TemplateError;
+
+    fn from_str(source: &str) -> Result<ExprTemplate, TemplateError> {
+        ExprTemplate::parse(source)
+    }
+}
+
+impl fmt::Display for ExprTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expr)
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::Syntax { offset, message } => {
+                write!(f, "Syntax error at offset {}: {}.", offset, message)
+            },
+            TemplateError::MissingSlot { slot } => {
+                write!(f, "No value was given for the template slot {}.", slot)
+            },
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
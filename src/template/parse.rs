@@ -0,0 +1,606 @@
+//! Parsing of Wolfram Language syntax, for [`ExprTemplate::parse()`].
+//!
+//! This supports the syntax that is commonly needed to write expression templates:
+//! atoms, patterns, slots, template slots, lists, associations, function application
+//! and the common operators. It does not evaluate anything, so `1 + 2` is parsed as
+//! `Plus[1, 2]`.
+//!
+//! The operators are parsed by the [`grammar`] shared with the `wl!` macro; this
+//! module tokenizes the source and parses the operands.
+//!
+//! [`ExprTemplate::parse()`]: super::ExprTemplate::parse
+
+use std::{cell::RefCell, convert::TryFrom, sync::Arc};
+
+use crate::grammar::{self, Syntax};
+use crate::{
+    escape, system, Association, Expr, Number, RuleRef, Symbol, SystemSymbol,
+    TemplateError,
+};
+
+/// Parse `source` as a single expression.
+pub(crate) fn parse(source: &str) -> Result<Expr, TemplateError> {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize()?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        end: source.len(),
+        unnegated: RefCell::new(lexer.unnegated),
+    };
+
+    let expr = grammar::compound(&mut parser)?;
+    if let Some(token) = parser.peek() {
+        return Err(syntax_error(token.start, "unexpected input"));
+    }
+    match parser.unnegated.into_inner().first() {
+        None => Ok(expr),
+        Some((_, start)) => Err(syntax_error(*start, "integer is out of range")),
+    }
+}
+
+fn syntax_error(offset: usize, message: &str) -> TemplateError {
+    TemplateError::Syntax {
+        offset,
+        message: message.to_owned(),
+    }
+}
+
+//======================================
+// Lexer
+//======================================
+
+#[derive(Debug, Clone)]
+enum TokenKind {
+    /// A number, string, symbol, pattern, slot or template slot.
+    Atom(Expr),
+    /// An operator or bracket.
+    Op(&'static str),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// The byte offset of the token in the source.
+    start: usize,
+}
+
+struct Lexer<'s> {
+    source: &'s str,
+    position: usize,
+    /// The number of `` `` `` template slots seen so far.
+    sequential_slots: i64,
+    /// The `9223372036854775808` literals and their offsets. These are lexed as
+    /// `i64::MIN`, and are only in range when negated, as in `-9223372036854775808`.
+    unnegated: Vec<(Expr, usize)>,
+}
+
+impl<'s> Lexer<'s> {
+    fn new(source: &'s str) -> Self {
+        Lexer {
+            source,
+            position: 0,
+            sequential_slots: 0,
+            unnegated: Vec::new(),
+        }
+    }
+
+    fn tokenize(&mut self) -> Result<Vec<Token>, TemplateError> {
+        let mut tokens = Vec::new();
+
+        loop {
+            self.skip_whitespace_and_comments()?;
+
+            let start = self.position;
+            let rest = &self.source[start..];
+            let c = match rest.chars().next() {
+                Some(c) => c,
+                None => return Ok(tokens),
+            };
+
+            let kind = if c.is_ascii_digit() {
+                TokenKind::Atom(self.number()?)
+            } else if c == '"' {
+                TokenKind::Atom(Expr::string(self.string()?))
+            } else if is_symbol_start(c) || c == '_' {
+                TokenKind::Atom(self.symbol_or_pattern()?)
+            } else if c == '#' {
+                TokenKind::Atom(self.slot()?)
+            } else if c == '`' {
+                TokenKind::Atom(self.template_slot()?)
+            } else {
+                match grammar::OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+                    Some(op) => {
+                        self.position += op.len();
+                        TokenKind::Op(op)
+                    },
+                    None => return Err(syntax_error(start, "unexpected character")),
+                }
+            };
+
+            tokens.push(Token { kind, start });
+        }
+    }
+
+    fn rest(&self) -> &'s str {
+        &self.source[self.position..]
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), TemplateError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+
+            if !trimmed.starts_with("(*") {
+                return Ok(());
+            }
+
+            match trimmed.find("*)") {
+                Some(end) => self.position += end + 2,
+                None => return Err(syntax_error(self.position, "unterminated comment")),
+            }
+        }
+    }
+
+    /// Consume the longest prefix of the remaining input whose characters satisfy
+    /// `predicate`.
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'s str {
+        let rest = self.rest();
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.position += len;
+        &rest[..len]
+    }
+
+    /// `123`, `1.5`, `1.5*^-3`, `2*^3` or `` 1.5` ``.
+    fn number(&mut self) -> Result<Expr, TemplateError> {
+        let start = self.position;
+        let mut mantissa = self.take_while(|c| c.is_ascii_digit()).to_owned();
+
+        let mut is_real = self.rest().starts_with('.')
+            && !self.rest()[1..].starts_with(|c: char| c == '.' || is_symbol_start(c));
+        if is_real {
+            self.position += 1;
+            mantissa.push('.');
+            mantissa.push_str(self.take_while(|c| c.is_ascii_digit()));
+        }
+
+        // A `` ` `` machine precision mark, as in `` 1.5` `` or `` 1.`*^-7 ``. A backtick
+        // that starts a template slot, as in ``2`x` ``, is not a mark.
+        let starts_slot = |c: char| c == '`' || c.is_ascii_digit() || is_symbol_start(c);
+        let has_mark =
+            self.rest().starts_with('`') && !self.rest()[1..].starts_with(starts_slot);
+        if has_mark {
+            self.position += 1;
+            is_real = true;
+        }
+
+        let mut exponent: i32 = 0;
+        if self.rest().starts_with("*^") {
+            self.position += 2;
+            let sign = match self.rest().starts_with('-') {
+                true => {
+                    self.position += 1;
+                    "-"
+                },
+                false => "",
+            };
+            let digits = self.take_while(|c| c.is_ascii_digit());
+            exponent = format!("{}{}", sign, digits)
+                .parse()
+                .map_err(|_| syntax_error(start, "invalid number exponent"))?;
+        }
+
+        if is_real {
+            return match format!("{}e{}", mantissa, exponent).parse::<f64>() {
+                Ok(real) if real.is_finite() => Ok(Expr::real(real)),
+                _ => Err(syntax_error(start, "real number is out of range")),
+            };
+        }
+
+        let magnitude = u32::try_from(exponent)
+            .ok()
+            .and_then(|exponent| 10u64.checked_pow(exponent))
+            .and_then(|scale| mantissa.parse::<u64>().ok()?.checked_mul(scale));
+
+        match magnitude {
+            Some(magnitude) if magnitude == i64::MIN.unsigned_abs() => {
+                let expr = Expr::from(i64::MIN);
+                self.unnegated.push((expr.clone(), start));
+                Ok(expr)
+            },
+            Some(magnitude) => match i64::try_from(magnitude) {
+                Ok(integer) => Ok(Expr::from(integer)),
+                Err(_) => Err(syntax_error(start, "integer is out of range")),
+            },
+            None => Err(syntax_error(start, "integer is out of range")),
+        }
+    }
+
+    /// A string literal, with the WL escape sequences described in [`escape`].
+    fn string(&mut self) -> Result<String, TemplateError> {
+        let start = self.position;
+        let contents = start + 1;
+
+        let mut escaped = false;
+        let len = self.source[contents..].find(|c| {
+            let is_end = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            is_end
+        });
+
+        let len = match len {
+            Some(len) => len,
+            None => return Err(syntax_error(start, "unterminated string")),
+        };
+        self.position = contents + len + 1;
+
+        escape::unescape(&self.source[contents..contents + len])
+            .map_err(|err| syntax_error(contents + err.offset, err.message))
+    }
+
+    /// A symbol like `x` or ``Global`x``, or a pattern like `x_`, `x__h` or `___`.
+    fn symbol_or_pattern(&mut self) -> Result<Expr, TemplateError> {
+        let start = self.position;
+        let name = self.symbol_name();
+
+        let underscores = self.take_while(|c| c == '_').len();
+        if underscores == 0 {
+            return resolve(name, start);
+        }
+
+        let blank_head = match underscores {
+            1 => system::Blank,
+            2 => system::BlankSequence,
+            3 => system::BlankNullSequence,
+            _ => return Err(syntax_error(start, "expected `_`, `__` or `___`")),
+        };
+
+        let head_start = self.position;
+        let head = self.symbol_name();
+        let blank = match head {
+            "" => Expr::normal(blank_head.into(), vec![]),
+            head => Expr::normal(blank_head.into(), vec![resolve(head, head_start)?]),
+        };
+
+        match name {
+            "" => Ok(blank),
+            name => {
+                let name = resolve(name, start)?;
+                Ok(Expr::normal(system::Pattern.into(), vec![name, blank]))
+            },
+        }
+    }
+
+    /// A symbol name, possibly with a context, or `""` if there is none.
+    fn symbol_name(&mut self) -> &'s str {
+        let rest = self.rest();
+        let mut len = 0;
+
+        for (index, c) in rest.char_indices() {
+            let continues = if c == '`' {
+                rest[index + 1..].starts_with(is_symbol_start)
+            } else if index == len && (len == 0 || rest[..len].ends_with('`')) {
+                is_symbol_start(c)
+            } else {
+                is_symbol_start(c) || c.is_numeric()
+            };
+
+            if !continues {
+                break;
+            }
+            len = index + c.len_utf8();
+        }
+
+        self.position += len;
+        &rest[..len]
+    }
+
+    /// `#`, `#n`, `#name` or `##n`.
+    fn slot(&mut self) -> Result<Expr, TemplateError> {
+        let start = self.position;
+        self.position += 1;
+
+        let head = match self.rest().starts_with('#') {
+            true => {
+                self.position += 1;
+                system::SlotSequence
+            },
+            false => system::Slot,
+        };
+
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if !digits.is_empty() {
+            let index: i64 = digits
+                .parse()
+                .map_err(|_| syntax_error(start, "slot number is out of range"))?;
+            return Ok(Expr::normal(head.into(), vec![Expr::from(index)]));
+        }
+
+        match self.symbol_name() {
+            "" => Ok(Expr::normal(head.into(), vec![Expr::from(1)])),
+            name if head == system::Slot && !name.contains('`') => {
+                Ok(Expr::normal(head.into(), vec![Expr::string(name)]))
+            },
+            _ => Err(syntax_error(start, "invalid slot")),
+        }
+    }
+
+    /// `` `n` ``, `` `name` `` or `` `` ``, which is the next positional slot.
+    fn template_slot(&mut self) -> Result<Expr, TemplateError> {
+        let start = self.position;
+        self.position += 1;
+
+        let name = self.take_while(|c| is_symbol_start(c) || c.is_ascii_digit());
+        if !self.rest().starts_with('`') {
+            let message = "expected a template slot like `1` or `name`";
+            return Err(syntax_error(start, message));
+        }
+        self.position += 1;
+
+        let key = if name.is_empty() {
+            self.sequential_slots += 1;
+            Expr::from(self.sequential_slots)
+        } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+            match name.parse::<i64>() {
+                Ok(index) => Expr::from(index),
+                Err(_) => return Err(syntax_error(start, "invalid template slot")),
+            }
+        } else {
+            Expr::string(name)
+        };
+
+        Ok(Expr::normal(system::TemplateSlot.into(), vec![key]))
+    }
+}
+
+fn is_symbol_start(c: char) -> bool {
+    c.is_alphabetic() || c == '$'
+}
+
+/// Resolve a symbol name to a symbol expression.
+///
+/// A name without a context refers to the `` System` `` symbol if it is in the
+/// [`system`] module, and to a `` Global` `` symbol otherwise.
+fn resolve(name: &str, offset: usize) -> Result<Expr, TemplateError> {
+    let absolute = if name.contains('`') {
+        name.to_owned()
+    } else {
+        let system = format!("System`{}", name);
+        if let Some(symbol) = SystemSymbol::lookup(&system) {
+            return Ok(Expr::from(symbol));
+        }
+        format!("Global`{}", name)
+    };
+
+    match Symbol::try_new(&absolute) {
+        Some(symbol) => Ok(Expr::symbol(symbol)),
+        None => Err(syntax_error(offset, "invalid symbol")),
+    }
+}
+
+//======================================
+// Parser
+//======================================
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// The offset of the end of the source, used for errors at the end of the input.
+    end: usize,
+    /// The `9223372036854775808` literals which have not been negated yet.
+    unnegated: RefCell<Vec<(Expr, usize)>>,
+}
+
+impl Parser {
+    //==================================
+    // Operands of the operators in `grammar`
+    //==================================
+
+    /// `f[x, y]` and `expr[[i, j]]`
+    fn application(&mut self) -> Result<Expr, TemplateError> {
+        let mut expr = self.primary()?;
+
+        while self.at("[") {
+            let is_part = matches!(
+                self.tokens.get(self.position..self.position + 2),
+                Some([open, Token { kind: TokenKind::Op("["), start }])
+                    if *start == open.start + 1
+            );
+
+            if is_part {
+                self.position += 2;
+                let mut elements = vec![expr];
+                elements.extend(self.elements("]")?);
+                self.expect("]")?;
+                expr = Expr::normal(system::Part.into(), elements);
+            } else {
+                self.position += 1;
+                expr = Expr::normal(expr, self.elements("]")?);
+            }
+        }
+
+        Ok(expr)
+    }
+
+    //==================================
+    // Atoms and groups
+    //==================================
+
+    fn primary(&mut self) -> Result<Expr, TemplateError> {
+        let token = match self.tokens.get(self.position) {
+            Some(token) => token.clone(),
+            None => return Err(syntax_error(self.end, "expected an expression")),
+        };
+        self.position += 1;
+
+        match token.kind {
+            TokenKind::Atom(expr) => Ok(expr),
+            TokenKind::Op("(") => {
+                let expr = grammar::compound(self)?;
+                self.expect(")")?;
+                Ok(expr)
+            },
+            TokenKind::Op("{") => Ok(Expr::list(self.elements("}")?)),
+            TokenKind::Op("<|") => self.association(),
+            TokenKind::Op(_) => Err(syntax_error(token.start, "expected an expression")),
+        }
+    }
+
+    /// The rules of an association, after the opening `<|`.
+    fn association(&mut self) -> Result<Expr, TemplateError> {
+        let mut assoc = Association::new();
+
+        while !self.eat("|>") {
+            let start = self.offset();
+            let rule = grammar::replace(self)?;
+            match RuleRef::try_from(&rule) {
+                Ok(rule) => {
+                    assoc.insert_rule(rule.lhs().clone(), rule.kind(), rule.rhs().clone())
+                },
+                Err(_) => return Err(syntax_error(start, "expected a rule")),
+            }
+
+            if !self.eat(",") && !self.at("|>") {
+                return Err(syntax_error(self.offset(), "expected `,` or `|>`"));
+            }
+        }
+
+        Ok(Expr::from(assoc))
+    }
+
+    /// The comma-separated elements of a list or function application, and the
+    /// closing bracket.
+    ///
+    /// As in the Wolfram Language, an empty element is `Null`, so `f[x, ]` is
+    /// `f[x, Null]`.
+    fn elements(&mut self, close: &str) -> Result<Vec<Expr>, TemplateError> {
+        let mut elements = Vec::new();
+        if self.eat(close) {
+            return Ok(elements);
+        }
+
+        loop {
+            if self.at_element_end() {
+                elements.push(Expr::null());
+            } else {
+                elements.push(grammar::compound(self)?);
+            }
+
+            if self.eat(close) {
+                return Ok(elements);
+            }
+            if !self.eat(",") {
+                let message = format!("expected `,` or `{}`", close);
+                return Err(syntax_error(self.offset(), &message));
+            }
+        }
+    }
+
+    //==================================
+    // Tokens
+    //==================================
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn offset(&self) -> usize {
+        self.peek().map_or(self.end, |token| token.start)
+    }
+
+    fn at(&self, op: &str) -> bool {
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::Op(next),
+                ..
+            }) => *next == op,
+            _ => false,
+        }
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        let matched = self.at(op);
+        if matched {
+            self.position += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), TemplateError> {
+        match self.eat(op) {
+            true => Ok(()),
+            false => Err(syntax_error(self.offset(), &format!("expected `{}`", op))),
+        }
+    }
+}
+
+impl Syntax for Parser {
+    type Node = Expr;
+    type Error = TemplateError;
+
+    fn peek_operator(&self) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::Op(op),
+                ..
+            }) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn skip_operator(&mut self) {
+        self.position += 1;
+    }
+
+    fn at_element_end(&self) -> bool {
+        self.peek().is_none() || [",", ")", "]", "}", "|>"].iter().any(|op| self.at(op))
+    }
+
+    fn at_operand(&self) -> bool {
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::Atom(_),
+                ..
+            }) => true,
+            _ => self.at("(") || self.at("{") || self.at("<|"),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Expr, TemplateError> {
+        self.application()
+    }
+
+    fn error(&self, message: &str) -> TemplateError {
+        syntax_error(self.offset(), message)
+    }
+
+    fn symbol(&self, symbol: SystemSymbol) -> Expr {
+        Expr::from(symbol)
+    }
+
+    fn apply(&self, head: Expr, elements: Vec<Expr>) -> Expr {
+        Expr::normal(head, elements)
+    }
+
+    fn integer(&self, value: i64) -> Expr {
+        Expr::from(value)
+    }
+
+    fn negate_number(&self, expr: &Expr) -> Option<Expr> {
+        // -9223372036854775808 is `i64::MIN`, which is how its magnitude was lexed.
+        let mut unnegated = self.unnegated.borrow_mut();
+        let literal = unnegated
+            .iter()
+            .position(|(literal, _)| Arc::ptr_eq(&literal.inner, &expr.inner));
+        if let Some(index) = literal {
+            unnegated.remove(index);
+            return Some(expr.clone());
+        }
+
+        match expr.try_as_number()? {
+            Number::Integer(integer) => Some(Expr::from(integer.checked_neg()?)),
+            Number::Real(real) => Some(Expr::real(-*real)),
+        }
+    }
+}
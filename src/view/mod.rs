@@ -0,0 +1,434 @@
+//! Borrowed, typed views of common kinds of normal expressions.
+//!
+//! Each view checks the head and length of an expression once, when it is constructed
+//! with [`TryFrom`], and then provides infallible accessors for its parts:
+//!
+//! ```
+//! use std::convert::TryFrom;
+//! use wolfram_expr::{Expr, ListRef, RuleRef};
+//!
+//! // {"a" -> 1, "b" :> 2}
+//! let expr = Expr::list(vec![
+//!     Expr::rule("a", Expr::from(1)),
+//!     Expr::rule_delayed("b", Expr::from(2)),
+//! ]);
+//!
+//! let list = ListRef::try_from(&expr).unwrap();
+//! let rule = RuleRef::try_from(&list[1]).unwrap();
+//!
+//! assert_eq!(list.len(), 2);
+//! assert_eq!(rule.lhs(), &Expr::string("b"));
+//! assert!(rule.is_delayed());
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
+
+use crate::{association, Expr, Normal, RuleKind, Symbol};
+
+/// Error returned when an expression does not have the form required by a view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewError {
+    expected: &'static str,
+    expr: Expr,
+}
+
+impl ViewError {
+    /// The form that was expected, e.g. `` "List[...]" ``.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// The expression which does not have the expected form.
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+}
+
+/// If `expr` is a normal expression with the symbol `head`, return it.
+fn with_head<'e>(expr: &'e Expr, head: &str) -> Option<&'e Normal> {
+    let normal = expr.try_as_normal()?;
+    match normal.head.try_as_symbol()?.as_str() == head {
+        true => Some(normal),
+        false => None,
+    }
+}
+
+fn error(expected: &'static str, expr: &Expr) -> ViewError {
+    ViewError {
+        expected,
+        expr: expr.clone(),
+    }
+}
+
+//======================================
+// List
+//======================================
+
+/// View of a [`List`](https://reference.wolfram.com/language/ref/List.html)
+/// <sub>WL</sub> expression: `{elements...}`.
+///
+/// `ListRef` dereferences to the slice of its elements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ListRef<'e> {
+    normal: &'e Normal,
+}
+
+impl<'e> TryFrom<&'e Expr> for ListRef<'e> {
+    type Error = ViewError;
+
+    fn try_from(expr: &'e Expr) -> Result<Self, ViewError> {
+        match with_head(expr, "System`List") {
+            Some(normal) => Ok(ListRef { normal }),
+            None => Err(error("List[...]", expr)),
+        }
+    }
+}
+
+impl<'e> ListRef<'e> {
+    /// The elements of the list.
+    pub fn elements(&self) -> &'e [Expr] {
+        self.normal.elements()
+    }
+
+    /// The underlying normal expression.
+    pub fn as_normal(&self) -> &'e Normal {
+        self.normal
+    }
+}
+
+impl<'e> Deref for ListRef<'e> {
+    type Target = [Expr];
+
+    fn deref(&self) -> &[Expr] {
+        self.normal.elements()
+    }
+}
+
+impl<'e> IntoIterator for ListRef<'e> {
+    type Item = &'e Expr;
+    type IntoIter = std::slice::Iter<'e, Expr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements().iter()
+    }
+}
+
+//======================================
+// Rule
+//======================================
+
+/// View of a [`Rule`](https://reference.wolfram.com/language/ref/Rule.html)
+/// <sub>WL</sub> or
+/// [`RuleDelayed`](https://reference.wolfram.com/language/ref/RuleDelayed.html)
+/// <sub>WL</sub> expression: `lhs -> rhs` or `lhs :> rhs`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleRef<'e> {
+    lhs: &'e Expr,
+    rhs: &'e Expr,
+    kind: RuleKind,
+}
+
+impl<'e> TryFrom<&'e Expr> for RuleRef<'e> {
+    type Error = ViewError;
+
+    fn try_from(expr: &'e Expr) -> Result<Self, ViewError> {
+        let invalid = || error("Rule[lhs, rhs] or RuleDelayed[lhs, rhs]", expr);
+
+        let normal = expr.try_as_normal().ok_or_else(invalid)?;
+        let kind = match normal.head.try_as_symbol().map(Symbol::as_str) {
+            Some("System`Rule") => RuleKind::Rule,
+            Some("System`RuleDelayed") => RuleKind::RuleDelayed,
+            _ => return Err(invalid()),
+        };
+
+        match normal.elements() {
+            [lhs, rhs] => Ok(RuleRef { lhs, rhs, kind }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl<'e> RuleRef<'e> {
+    /// The left-hand side of the rule.
+    pub fn lhs(&self) -> &'e Expr {
+        self.lhs
+    }
+
+    /// The right-hand side of the rule.
+    pub fn rhs(&self) -> &'e Expr {
+        self.rhs
+    }
+
+    /// Whether this is a `Rule` or a `RuleDelayed`.
+    pub fn kind(&self) -> RuleKind {
+        self.kind
+    }
+
+    /// Returns `true` if this is a `RuleDelayed`.
+    pub fn is_delayed(&self) -> bool {
+        self.kind.is_delayed()
+    }
+}
+
+//======================================
+// Function
+//======================================
+
+/// View of a pure [`Function`](https://reference.wolfram.com/language/ref/Function.html)
+/// <sub>WL</sub>: `Function[body]`, `Function[params, body]` or
+/// `Function[params, body, attrs]`.
+///
+/// See also [`Expr::apply_function()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FunctionRef<'e> {
+    normal: &'e Normal,
+}
+
+impl<'e> TryFrom<&'e Expr> for FunctionRef<'e> {
+    type Error = ViewError;
+
+    fn try_from(expr: &'e Expr) -> Result<Self, ViewError> {
+        match with_head(expr, "System`Function") {
+            Some(normal) if (1..=3).contains(&normal.elements().len()) => {
+                Ok(FunctionRef { normal })
+            },
+            _ => Err(error(
+                "Function[body], Function[params, body] or Function[params, body, attrs]",
+                expr,
+            )),
+        }
+    }
+}
+
+impl<'e> FunctionRef<'e> {
+    /// The parameters of the function: a symbol, a list of symbols, or `Null`.
+    ///
+    /// Returns `None` for a `Function[body]` which uses `Slot`s.
+    pub fn params(&self) -> Option<&'e Expr> {
+        match self.normal.elements() {
+            [_] => None,
+            [params, ..] => Some(params),
+            [] => unreachable!(),
+        }
+    }
+
+    /// The named parameters of the function, or `None` if the function uses `Slot`s or
+    /// its parameters are not symbols.
+    pub fn param_symbols(&self) -> Option<Vec<&'e Symbol>> {
+        let params = self.params()?;
+        if let Some(symbol) = params.try_as_symbol() {
+            return match symbol.as_str() {
+                "System`Null" => None,
+                _ => Some(vec![symbol]),
+            };
+        }
+
+        ListRef::try_from(params)
+            .ok()?
+            .into_iter()
+            .map(Expr::try_as_symbol)
+            .collect()
+    }
+
+    /// The body of the function.
+    pub fn body(&self) -> &'e Expr {
+        match self.normal.elements() {
+            [body] | [_, body] | [_, body, _] => body,
+            _ => unreachable!(),
+        }
+    }
+
+    /// The attributes of the function, given as its third argument.
+    pub fn attributes(&self) -> Option<&'e Expr> {
+        self.normal.elements().get(2)
+    }
+
+    /// The underlying normal expression.
+    pub fn as_normal(&self) -> &'e Normal {
+        self.normal
+    }
+}
+
+//======================================
+// Failure
+//======================================
+
+/// View of a [`Failure`](https://reference.wolfram.com/language/ref/Failure.html)
+/// <sub>WL</sub> expression: `Failure[tag, assoc]`.
+///
+/// The association may be an [`Association`][crate::Association] or an
+/// `Association[rules...]` expression.
+///
+/// # Example
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use wolfram_expr::{Association, Expr, FailureRef};
+///
+/// let mut assoc = Association::new();
+/// assoc.insert("MessageTemplate", Expr::string("Cannot open `1`."));
+/// assoc.insert("MessageParameters", Expr::list(vec![Expr::string("file.txt")]));
+///
+/// let tag = Expr::string("NoFile");
+/// let expr = Expr::function("System`Failure", vec![tag, Expr::from(assoc)]);
+/// let failure = FailureRef::try_from(&expr).unwrap();
+///
+/// assert_eq!(failure.tag_str(), Some("NoFile"));
+/// assert_eq!(failure.message(), Some("Cannot open file.txt.".to_owned()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FailureRef<'e> {
+    tag: &'e Expr,
+    assoc: &'e Expr,
+}
+
+impl<'e> TryFrom<&'e Expr> for FailureRef<'e> {
+    type Error = ViewError;
+
+    fn try_from(expr: &'e Expr) -> Result<Self, ViewError> {
+        let elements = with_head(expr, "System`Failure").map(Normal::elements);
+
+        match elements {
+            Some([tag, assoc]) if is_association(assoc) => Ok(FailureRef { tag, assoc }),
+            _ => Err(error("Failure[tag, assoc]", expr)),
+        }
+    }
+}
+
+impl<'e> FailureRef<'e> {
+    /// The tag of the failure, usually a string.
+    pub fn tag(&self) -> &'e Expr {
+        self.tag
+    }
+
+    /// The tag of the failure, if it is a string.
+    pub fn tag_str(&self) -> Option<&'e str> {
+        self.tag.try_as_str()
+    }
+
+    /// The association of details about the failure.
+    pub fn association(&self) -> &'e Expr {
+        self.assoc
+    }
+
+    /// Look up `key` in the association of details about the failure.
+    pub fn get(&self, key: &str) -> Option<&'e Expr> {
+        association::lookup(self.assoc, &Expr::string(key))
+    }
+
+    /// The `"MessageTemplate"` of the failure, if it is a string.
+    pub fn message_template(&self) -> Option<&'e str> {
+        self.get("MessageTemplate")?.try_as_str()
+    }
+
+    /// The `"MessageParameters"` of the failure.
+    pub fn message_parameters(&self) -> Option<&'e Expr> {
+        self.get("MessageParameters")
+    }
+
+    /// The message of the failure: its `"MessageTemplate"` with the `` `n` `` and
+    /// `` `name` `` placeholders replaced by the corresponding `"MessageParameters"`.
+    ///
+    /// String parameters are inserted without quotes. Placeholders which have no
+    /// corresponding parameter are left unchanged.
+    pub fn message(&self) -> Option<String> {
+        let template = self.message_template()?;
+        let params = self.message_parameters();
+
+        let mut message = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('`') {
+            message.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+
+            let (name, remaining) = match after.find('`') {
+                Some(end) => (&after[..end], &after[end + 1..]),
+                // An unterminated placeholder is kept as written.
+                None => {
+                    rest = &rest[start..];
+                    break;
+                },
+            };
+
+            match params.and_then(|params| parameter(params, name)) {
+                Some(param) => match param.try_as_str() {
+                    Some(string) => message.push_str(string),
+                    None => message.push_str(&param.to_string()),
+                },
+                None => {
+                    message.push('`');
+                    message.push_str(name);
+                    message.push('`');
+                },
+            }
+
+            rest = remaining;
+        }
+
+        message.push_str(rest);
+        Some(message)
+    }
+}
+
+/// The parameter named `name` in a list or association of message parameters.
+fn parameter<'e>(params: &'e Expr, name: &str) -> Option<&'e Expr> {
+    match ListRef::try_from(params) {
+        Ok(list) => list.elements().get(name.parse::<usize>().ok()?.checked_sub(1)?),
+        Err(_) => association::lookup(params, &Expr::string(name)),
+    }
+}
+
+fn is_association(expr: &Expr) -> bool {
+    expr.try_as_association().is_some() || with_head(expr, "System`Association").is_some()
+}
+
+//======================================
+// Missing
+//======================================
+
+/// View of a [`Missing`](https://reference.wolfram.com/language/ref/Missing.html)
+/// <sub>WL</sub> expression: `Missing[]`, `Missing[reason]` or
+/// `Missing[reason, details...]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MissingRef<'e> {
+    normal: &'e Normal,
+}
+
+impl<'e> TryFrom<&'e Expr> for MissingRef<'e> {
+    type Error = ViewError;
+
+    fn try_from(expr: &'e Expr) -> Result<Self, ViewError> {
+        match with_head(expr, "System`Missing") {
+            Some(normal) => Ok(MissingRef { normal }),
+            None => Err(error("Missing[...]", expr)),
+        }
+    }
+}
+
+impl<'e> MissingRef<'e> {
+    /// The reason the value is missing, e.g. `"KeyAbsent"`.
+    pub fn reason(&self) -> Option<&'e Expr> {
+        self.normal.elements().first()
+    }
+
+    /// The reason the value is missing, if it is a string.
+    pub fn reason_str(&self) -> Option<&'e str> {
+        self.reason()?.try_as_str()
+    }
+
+    /// The details after the reason, e.g. the key in `Missing["KeyAbsent", key]`.
+    pub fn details(&self) -> &'e [Expr] {
+        self.normal.elements().get(1..).unwrap_or(&[])
+    }
+}
+
+impl fmt::Display for ViewError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} does not have the form {}.", self.expr, self.expected)
+    }
+}
+
+impl std::error::Error for ViewError {}
@@ -1,4 +1,4 @@
-use crate::{Expr, ExprKind, Normal, Symbol};
+use crate::{Expr, ExprKind, Normal, RuleKind, Symbol};
 use flate2::{write::ZlibEncoder, Compression};
 use integer_encoding::VarInt;
 use std::io::Write;
@@ -30,28 +30,67 @@ impl Expr {
     }
 
     fn write_internal(&self, out: &mut Vec<u8>) {
-        match self.kind() {
-            ExprKind::Integer(n) => {
-                out.push(b'L');
-                out.extend_from_slice(&n.to_le_bytes());
-            },
-            ExprKind::Real(n) => {
-                out.push(b'r');
-                out.extend_from_slice(&n.to_le_bytes());
-            },
-            ExprKind::String(s) => {
-                let len = s.len().encode_var_vec();
-                out.push(b'S');
-                out.extend_from_slice(&len);
-                out.extend_from_slice(s.as_bytes());
-            },
-            ExprKind::Symbol(s) => s.write_internal(out),
-            ExprKind::Normal(fx) => fx.write_internal(out),
+        /// Pending piece of output: an expression, or the marker which precedes the key
+        /// of each rule in an association.
+        enum Item<'e> {
+            Expr(&'e Expr),
+            Rule(RuleKind),
+        }
+
+        // Walk the expression with an explicit stack, in the same prefix order the
+        // WXF format lays out a normal expression: length, head, then elements.
+        let mut stack: Vec<Item> = vec![Item::Expr(self)];
+
+        while let Some(item) = stack.pop() {
+            let expr = match item {
+                Item::Expr(expr) => expr,
+                Item::Rule(RuleKind::Rule) => {
+                    out.push(b'-');
+                    continue;
+                },
+                Item::Rule(RuleKind::RuleDelayed) => {
+                    out.push(b':');
+                    continue;
+                },
+            };
+
+            match expr.kind() {
+                ExprKind::Integer(n) => {
+                    out.push(b'L');
+                    out.extend_from_slice(&n.to_le_bytes());
+                },
+                ExprKind::Real(n) => {
+                    out.push(b'r');
+                    out.extend_from_slice(&n.to_le_bytes());
+                },
+                ExprKind::String(s) => {
+                    let len = s.len().encode_var_vec();
+                    out.push(b'S');
+                    out.extend_from_slice(&len);
+                    out.extend_from_slice(s.as_bytes());
+                },
+                ExprKind::Symbol(s) => s.write_internal(out),
+                ExprKind::Normal(fx) => {
+                    fx.write_header(out);
+                    stack.extend(fx.contents.iter().rev().map(Item::Expr));
+                    stack.push(Item::Expr(&fx.head));
+                },
+                ExprKind::Association(assoc) => {
+                    // Each rule is laid out as its marker, key, then value.
+                    out.push(b'A');
+                    out.extend_from_slice(&assoc.len().encode_var_vec());
+                    let rules: Vec<_> = assoc.rules().collect();
+                    for (key, kind, value) in rules.into_iter().rev() {
+                        stack.push(Item::Expr(value));
+                        stack.push(Item::Expr(key));
+                        stack.push(Item::Rule(kind));
+                    }
+                },
+            }
         }
     }
 }
 
-
 impl Symbol {
     fn write_internal(&self, out: &mut Vec<u8>) {
         let s = self.as_str();
@@ -67,12 +106,8 @@ impl Symbol {
 }
 
 impl Normal {
-    fn write_internal(&self, out: &mut Vec<u8>) {
+    fn write_header(&self, out: &mut Vec<u8>) {
         out.push(b'f');
         out.extend_from_slice(&self.contents.len().encode_var_vec());
-        self.head.write_internal(out);
-        for v in self.contents.iter() {
-            v.write_internal(out)
-        }
     }
 }
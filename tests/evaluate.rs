@@ -0,0 +1,983 @@
+use wolfram_expr::{EvalError, Evaluator, Expr, FunctionError, Symbol};
+
+//======================================
+// Helpers
+//======================================
+
+fn sym(name: &str) -> Expr {
+    if name.contains('`') {
+        Expr::symbol(Symbol::new(name))
+    } else {
+        Expr::symbol(Symbol::new(&format!("Global`{}", name)))
+    }
+}
+
+fn call(head: &str, elems: Vec<Expr>) -> Expr {
+    Expr::normal(sym(head), elems)
+}
+
+/// `name_`
+fn blank(name: &str) -> Expr {
+    call("System`Pattern", vec![sym(name), call("System`Blank", vec![])])
+}
+
+fn list(elems: Vec<Expr>) -> Expr {
+    Expr::list(elems)
+}
+
+fn set(lhs: Expr, rhs: Expr) -> Expr {
+    call("System`Set", vec![lhs, rhs])
+}
+
+fn set_delayed(lhs: Expr, rhs: Expr) -> Expr {
+    call("System`SetDelayed", vec![lhs, rhs])
+}
+
+fn same_q(lhs: Expr, rhs: Expr) -> Expr {
+    call("System`SameQ", vec![lhs, rhs])
+}
+
+fn eval(evaluator: &mut Evaluator, expr: Expr) -> Expr {
+    evaluator.evaluate(&expr).unwrap()
+}
+
+//======================================
+// Tests
+//======================================
+
+#[test]
+fn own_values_and_down_values() {
+    let mut evaluator = Evaluator::new();
+
+    // x = 5; h[x]
+    assert_eq!(eval(&mut evaluator, set(sym("x"), Expr::from(5))), Expr::from(5));
+    assert_eq!(
+        eval(&mut evaluator, call("h", vec![sym("x")])),
+        call("h", vec![Expr::from(5)])
+    );
+
+    // add[z, y_] := y; add[s[x_], y_] := s[add[x, y]]
+    eval(
+        &mut evaluator,
+        set_delayed(call("add", vec![sym("z"), blank("y")]), sym("y")),
+    );
+    eval(
+        &mut evaluator,
+        set_delayed(
+            call("add", vec![call("s", vec![blank("n")]), blank("y")]),
+            call("s", vec![call("add", vec![sym("n"), sym("y")])]),
+        ),
+    );
+
+    let two = call("s", vec![call("s", vec![sym("z")])]);
+    let one = call("s", vec![sym("z")]);
+    assert_eq!(
+        eval(&mut evaluator, call("add", vec![two, one])).to_string(),
+        "Global`s[Global`s[Global`s[Global`z]]]"
+    );
+
+    // A definition without patterns is tried first: add[z, z] = done
+    eval(
+        &mut evaluator,
+        set(call("add", vec![sym("z"), sym("z")]), sym("done")),
+    );
+    let add = Symbol::new("Global`add");
+    assert_eq!(evaluator.definitions().down_values(&add).len(), 3);
+    assert_eq!(
+        eval(&mut evaluator, call("add", vec![sym("z"), sym("z")])),
+        sym("done")
+    );
+
+    // Clear[x]
+    eval(&mut evaluator, call("System`Clear", vec![sym("x")]));
+    assert_eq!(eval(&mut evaluator, sym("x")), sym("x"));
+}
+
+#[test]
+fn patterns() {
+    let mut evaluator = Evaluator::new();
+
+    // f[x__] := {x}
+    let seq = call("System`Pattern", vec![
+        sym("x"),
+        call("System`BlankSequence", vec![]),
+    ]);
+    eval(&mut evaluator, set_delayed(call("f", vec![seq]), list(vec![sym("x")])));
+    assert_eq!(
+        eval(&mut evaluator, call("f", vec![Expr::from(1), Expr::from(2)])),
+        list(vec![Expr::from(1), Expr::from(2)])
+    );
+    assert_eq!(eval(&mut evaluator, call("f", vec![])), call("f", vec![]));
+
+    // g[x_, x_] := same
+    eval(
+        &mut evaluator,
+        set_delayed(call("g", vec![blank("x"), blank("x")]), sym("same")),
+    );
+    assert_eq!(
+        eval(&mut evaluator, call("g", vec![Expr::from(1), Expr::from(1)])),
+        sym("same")
+    );
+    assert_eq!(
+        eval(&mut evaluator, call("g", vec![Expr::from(1), Expr::from(2)])),
+        call("g", vec![Expr::from(1), Expr::from(2)])
+    );
+
+    // one[x_ /; x === 1] := yes
+    let condition = call("System`Condition", vec![
+        blank("x"),
+        same_q(sym("x"), Expr::from(1)),
+    ]);
+    eval(&mut evaluator, set_delayed(call("one", vec![condition]), sym("yes")));
+    assert_eq!(eval(&mut evaluator, call("one", vec![Expr::from(1)])), sym("yes"));
+    assert_eq!(
+        eval(&mut evaluator, call("one", vec![Expr::from(2)])),
+        call("one", vec![Expr::from(2)])
+    );
+
+    // int[x_Integer] := x
+    let int = call("System`Pattern", vec![
+        sym("x"),
+        call("System`Blank", vec![sym("System`Integer")]),
+    ]);
+    eval(&mut evaluator, set_delayed(call("int", vec![int]), sym("x")));
+    assert_eq!(eval(&mut evaluator, call("int", vec![Expr::from(3)])), Expr::from(3));
+    assert_eq!(
+        eval(&mut evaluator, call("int", vec![Expr::string("3")])),
+        call("int", vec![Expr::string("3")])
+    );
+}
+
+#[test]
+fn patterns_respect_attributes() {
+    let mut evaluator = Evaluator::new();
+
+    // Plus is Flat and Orderless, so Plus[b, a, c] matches Plus[a, x_] with
+    // x -> Plus[b, c].
+    let plus = |elems| call("System`Plus", elems);
+    eval(
+        &mut evaluator,
+        set_delayed(call("f", vec![plus(vec![sym("a"), blank("x")])]), sym("x")),
+    );
+
+    assert_eq!(
+        eval(&mut evaluator, call("f", vec![plus(vec![sym("b"), sym("a")])])),
+        sym("b")
+    );
+    assert_eq!(
+        eval(
+            &mut evaluator,
+            call("f", vec![plus(vec![sym("c"), sym("b"), sym("a")])])
+        ),
+        plus(vec![sym("b"), sym("c")])
+    );
+}
+
+#[test]
+fn attributes_during_evaluation() {
+    let mut evaluator = Evaluator::new();
+    eval(&mut evaluator, set(sym("x"), Expr::from(1)));
+
+    // Hold[x] is not evaluated.
+    let held = call("System`Hold", vec![sym("x")]);
+    assert_eq!(eval(&mut evaluator, held.clone()), held);
+
+    // Evaluate[x] is evaluated even when held.
+    assert_eq!(
+        eval(
+            &mut evaluator,
+            call("System`Hold", vec![call("System`Evaluate", vec![sym("x")])])
+        ),
+        call("System`Hold", vec![Expr::from(1)])
+    );
+
+    // Listable: Sin[{a, b}] => {Sin[a], Sin[b]}
+    assert_eq!(
+        eval(&mut evaluator, call("System`Sin", vec![list(vec![sym("a"), sym("b")])])),
+        list(vec![
+            call("System`Sin", vec![sym("a")]),
+            call("System`Sin", vec![sym("b")])
+        ])
+    );
+
+    // Flat and Orderless: Plus[c, Plus[b, a]] => Plus[a, b, c]
+    assert_eq!(
+        eval(
+            &mut evaluator,
+            call("System`Plus", vec![
+                sym("c"),
+                call("System`Plus", vec![sym("b"), sym("a")])
+            ])
+        ),
+        call("System`Plus", vec![sym("a"), sym("b"), sym("c")])
+    );
+
+    // Sequence is spliced into the enclosing expression.
+    assert_eq!(
+        eval(
+            &mut evaluator,
+            call("f", vec![call("System`Sequence", vec![sym("a"), sym("b")])])
+        ),
+        call("f", vec![sym("a"), sym("b")])
+    );
+}
+
+#[test]
+fn control_flow() {
+    let mut evaluator = Evaluator::new();
+
+    // x = 1; If[x === 1, yes, no]
+    let program = call("System`CompoundExpression", vec![
+        set(sym("x"), Expr::from(1)),
+        call("System`If", vec![
+            same_q(sym("x"), Expr::from(1)),
+            sym("yes"),
+            sym("no"),
+        ]),
+    ]);
+    assert_eq!(eval(&mut evaluator, program), sym("yes"));
+
+    // If[cond, a, b] stays unevaluated.
+    let unevaluated = call("System`If", vec![sym("cond"), sym("a"), sym("b")]);
+    assert_eq!(eval(&mut evaluator, unevaluated.clone()), unevaluated);
+
+    // Which[x === 2, two, x === 1, one]
+    let which = call("System`Which", vec![
+        same_q(sym("x"), Expr::from(2)),
+        sym("two"),
+        same_q(sym("x"), Expr::from(1)),
+        sym("one"),
+    ]);
+    assert_eq!(eval(&mut evaluator, which), sym("one"));
+
+    // False && undefined[] does not evaluate its second argument.
+    eval(
+        &mut evaluator,
+        set_delayed(call("undefined", vec![]), call("System`Abort", vec![])),
+    );
+    assert_eq!(
+        eval(
+            &mut evaluator,
+            call("System`And", vec![sym("System`False"), call("undefined", vec![])])
+        ),
+        sym("System`False")
+    );
+}
+
+#[test]
+fn scoping() {
+    let mut evaluator = Evaluator::new();
+    eval(&mut evaluator, set(sym("x"), Expr::from(1)));
+
+    // Module[{x = 2}, {x, Hold[x]}] => {2, Hold[x$1]}
+    let module = call("System`Module", vec![
+        list(vec![set(sym("x"), Expr::from(2))]),
+        list(vec![sym("x"), call("System`Hold", vec![sym("x")])]),
+    ]);
+    assert_eq!(
+        eval(&mut evaluator, module).to_string(),
+        "System`List[2, System`Hold[Global`x$1]]"
+    );
+    let x_1 = Symbol::new("Global`x$1");
+    assert_eq!(evaluator.definitions().own_value(&x_1), Some(&Expr::from(2)));
+
+    // Module[{y = 2}, y + 1] => 3, and y$2 is cleared as it does not escape.
+    let module = call("System`Module", vec![
+        list(vec![set(sym("y"), Expr::from(2))]),
+        call("System`Plus", vec![sym("y"), Expr::from(1)]),
+    ]);
+    assert_eq!(eval(&mut evaluator, module), Expr::from(3));
+    let y_2 = Symbol::new("Global`y$2");
+    assert_eq!(evaluator.definitions().own_value(&y_2), None);
+
+    // Block[{x = 2}, f[x]] => f[2]
+    let block = call("System`Block", vec![
+        list(vec![set(sym("x"), Expr::from(2))]),
+        call("f", vec![sym("x")]),
+    ]);
+    assert_eq!(eval(&mut evaluator, block), call("f", vec![Expr::from(2)]));
+    assert_eq!(eval(&mut evaluator, sym("x")), Expr::from(1));
+
+    // With[{y = x}, Hold[y]] => Hold[1]
+    let with = call("System`With", vec![
+        list(vec![set(sym("y"), sym("x"))]),
+        call("System`Hold", vec![sym("y")]),
+    ]);
+    assert_eq!(
+        eval(&mut evaluator, with),
+        call("System`Hold", vec![Expr::from(1)])
+    );
+}
+
+#[test]
+fn pure_functions() {
+    let mut evaluator = Evaluator::new();
+
+    let slot = |n: i64| call("System`Slot", vec![Expr::from(n)]);
+
+    // Function[g[#2, #1]][1, 2] => g[2, 1]
+    let function = call("System`Function", vec![call("g", vec![slot(2), slot(1)])]);
+    assert_eq!(
+        eval(
+            &mut evaluator,
+            Expr::normal(function, vec![Expr::from(1), Expr::from(2)])
+        ),
+        call("g", vec![Expr::from(2), Expr::from(1)])
+    );
+
+    // Function[{a, b}, g[b, a]][1, 2] => g[2, 1]
+    let function = call("System`Function", vec![
+        list(vec![sym("a"), sym("b")]),
+        call("g", vec![sym("b"), sym("a")]),
+    ]);
+    assert_eq!(
+        eval(
+            &mut evaluator,
+            Expr::normal(function, vec![Expr::from(1), Expr::from(2)])
+        ),
+        call("g", vec![Expr::from(2), Expr::from(1)])
+    );
+}
+
+#[test]
+fn apply_function() {
+    let slot = |n: i64| call("System`Slot", vec![Expr::from(n)]);
+    let function = |elems: Vec<Expr>| call("System`Function", elems);
+    let plus = |elems: Vec<Expr>| call("System`Plus", elems);
+    let (x, y) = (|| sym("x"), || sym("y"));
+
+    // Function[# + 1][5] and Function[{x}, x^2][3] are not evaluated.
+    assert_eq!(
+        function(vec![plus(vec![slot(1), Expr::from(1)])])
+            .apply_function(&[Expr::from(5)]),
+        Ok(plus(vec![Expr::from(5), Expr::from(1)]))
+    );
+    assert_eq!(
+        function(vec![
+            list(vec![x()]),
+            call("System`Power", vec![x(), Expr::from(2)]),
+        ])
+        .apply_function(&[Expr::from(3)]),
+        Ok(call("System`Power", vec![Expr::from(3), Expr::from(2)]))
+    );
+
+    // Function[f[##2, g[#0]]][1, 2, 3]
+    let f = function(vec![call("f", vec![
+        call("System`SlotSequence", vec![Expr::from(2)]),
+        call("g", vec![slot(0)]),
+    ])]);
+    assert_eq!(
+        f.apply_function(&[Expr::from(1), Expr::from(2), Expr::from(3)]),
+        Ok(call("f", vec![Expr::from(2), Expr::from(3), call("g", vec![f.clone()])]))
+    );
+
+    // Function[##][1, 2] and Function[##3][1, 2]
+    let sequence = function(vec![call("System`SlotSequence", vec![])]);
+    assert_eq!(
+        sequence.apply_function(&[Expr::from(1), Expr::from(2)]),
+        Ok(call("System`Sequence", vec![Expr::from(1), Expr::from(2)]))
+    );
+    let empty = function(vec![call("System`SlotSequence", vec![Expr::from(3)])]);
+    assert_eq!(
+        empty.apply_function(&[Expr::from(1), Expr::from(2)]),
+        Ok(call("System`Sequence", vec![]))
+    );
+
+    // Slots in a nested Function[body] belong to the inner function.
+    let inner = function(vec![call("h", vec![slot(1)])]);
+    assert_eq!(
+        function(vec![call("g", vec![slot(1), inner.clone()])])
+            .apply_function(&[Expr::from(1)]),
+        Ok(call("g", vec![Expr::from(1), inner]))
+    );
+
+    // #a looks up a key in an association.
+    let assoc = call("System`Association", vec![Expr::rule(
+        Expr::string("a"),
+        Expr::from(1),
+    )]);
+    assert_eq!(
+        function(vec![call("System`Slot", vec![Expr::string("a")])])
+            .apply_function(&[assoc]),
+        Ok(Expr::from(1))
+    );
+
+    // Inner parameters shadow outer ones: Function[{x}, Function[{x}, x]][1]
+    let shadowed = function(vec![list(vec![x()]), x()]);
+    assert_eq!(
+        function(vec![list(vec![x()]), shadowed.clone()])
+            .apply_function(&[Expr::from(1)]),
+        Ok(shadowed)
+    );
+
+    // Inner parameters are renamed to avoid capture:
+    // Function[{x}, Module[{y = x}, x + y]][y]
+    let module = call("System`Module", vec![
+        list(vec![set(y(), x())]),
+        plus(vec![x(), y()]),
+    ]);
+    assert_eq!(
+        function(vec![list(vec![x()]), module]).apply_function(&[y()]),
+        Ok(call("System`Module", vec![
+            list(vec![set(sym("y$"), y())]),
+            plus(vec![y(), sym("y$")]),
+        ]))
+    );
+    // Function[Function[{y}, # + y]][y]
+    let f = function(vec![function(vec![
+        list(vec![y()]),
+        plus(vec![slot(1), y()]),
+    ])]);
+    assert_eq!(
+        f.apply_function(&[y()]),
+        Ok(function(vec![list(vec![sym("y$")]), plus(vec![y(), sym("y$")])]))
+    );
+
+    // Attributes
+    let listable = function(vec![
+        list(vec![x()]),
+        call("f", vec![x()]),
+        sym("System`Listable"),
+    ]);
+    assert_eq!(
+        listable.apply_function(&[list(vec![Expr::from(1), Expr::from(2)])]),
+        Ok(list(vec![call("f", vec![Expr::from(1)]), call("f", vec![Expr::from(2)])]))
+    );
+    let orderless = function(vec![
+        sym("System`Null"),
+        call("f", vec![call("System`SlotSequence", vec![])]),
+        list(vec![sym("System`Orderless")]),
+    ]);
+    assert_eq!(
+        orderless.apply_function(&[sym("b"), sym("a")]),
+        Ok(call("f", vec![sym("a"), sym("b")]))
+    );
+    let listable_2 = function(vec![
+        list(vec![x(), y()]),
+        call("f", vec![x(), y()]),
+        sym("System`Listable"),
+    ]);
+    assert_eq!(
+        listable_2.apply_function(&[
+            list(vec![Expr::from(1), Expr::from(2)]),
+            list(vec![Expr::from(1)])
+        ]),
+        Err(FunctionError::ListLengthMismatch)
+    );
+
+    // Errors
+    assert_eq!(
+        call("f", vec![]).apply_function(&[]),
+        Err(FunctionError::NotFunction {
+            expr: call("f", vec![])
+        })
+    );
+    assert_eq!(
+        listable_2.apply_function(&[Expr::from(1)]),
+        Err(FunctionError::TooFewArguments {
+            expected: 2,
+            given: 1
+        })
+    );
+
+    // HoldAll prevents the evaluator from evaluating the arguments.
+    let held = function(vec![
+        list(vec![x()]),
+        call("System`Hold", vec![x()]),
+        sym("System`HoldAll"),
+    ]);
+    let one_plus_one = plus(vec![Expr::from(1), Expr::from(1)]);
+    assert_eq!(
+        eval(&mut Evaluator::new(), Expr::normal(held, vec![one_plus_one.clone()])),
+        call("System`Hold", vec![one_plus_one])
+    );
+}
+
+#[test]
+fn limits() {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_iteration_limit(100);
+    evaluator.set_recursion_limit(50);
+
+    // f[a] := f[b]; f[b] := f[a]
+    eval(
+        &mut evaluator,
+        set_delayed(call("f", vec![sym("a")]), call("f", vec![sym("b")])),
+    );
+    eval(
+        &mut evaluator,
+        set_delayed(call("f", vec![sym("b")]), call("f", vec![sym("a")])),
+    );
+    assert!(matches!(
+        evaluator.evaluate(&call("f", vec![sym("a")])),
+        Err(EvalError::IterationLimit { limit: 100, .. })
+    ));
+
+    // g[n_] := h[g[n]]
+    eval(
+        &mut evaluator,
+        set_delayed(
+            call("g", vec![blank("n")]),
+            call("h", vec![call("g", vec![sym("n")])]),
+        ),
+    );
+    assert!(matches!(
+        evaluator.evaluate(&call("g", vec![sym("z")])),
+        Err(EvalError::RecursionLimit { limit: 50, .. })
+    ));
+
+    // The evaluator is still usable after an error.
+    assert_eq!(eval(&mut evaluator, sym("z")), sym("z"));
+}
+
+//======================================
+// Built-in functions
+//======================================
+
+fn int(value: i64) -> Expr {
+    Expr::from(value)
+}
+
+fn rational(numer: i64, denom: i64) -> Expr {
+    call("System`Rational", vec![int(numer), int(denom)])
+}
+
+fn ints(values: &[i64]) -> Expr {
+    list(values.iter().copied().map(int).collect())
+}
+
+fn strings(values: &[&str]) -> Expr {
+    list(values.iter().copied().map(Expr::string).collect())
+}
+
+/// Evaluate `head[elems...]`.
+fn eval_call(head: &str, elems: Vec<Expr>) -> Expr {
+    eval(&mut Evaluator::new(), call(head, elems))
+}
+
+#[test]
+fn arithmetic() {
+    let x = || sym("x");
+
+    // 1 + 2 + x + x
+    assert_eq!(
+        eval_call("System`Plus", vec![int(1), int(2), x(), x()]),
+        call("System`Plus", vec![int(3), call("System`Times", vec![int(2), x()])])
+    );
+    assert_eq!(eval_call("System`Plus", vec![int(1), Expr::real(2.5)]), Expr::real(3.5));
+    assert_eq!(
+        eval_call("System`Plus", vec![rational(1, 2), rational(1, 3)]),
+        rational(5, 6)
+    );
+    assert_eq!(eval_call("System`Times", vec![int(2), rational(1, 2)]), int(1));
+    assert_eq!(eval_call("System`Times", vec![int(0), x()]), int(0));
+
+    // x * x * x^2
+    assert_eq!(
+        eval_call("System`Times", vec![
+            x(),
+            x(),
+            call("System`Power", vec![x(), int(2)])
+        ]),
+        call("System`Power", vec![x(), int(4)])
+    );
+    assert_eq!(eval_call("System`Subtract", vec![x(), x()]), int(0));
+    assert_eq!(eval_call("System`Divide", vec![int(6), int(4)]), rational(3, 2));
+    assert_eq!(eval_call("System`Minus", vec![rational(1, 2)]), rational(-1, 2));
+
+    // Results which do not fit in an i64 are left unevaluated.
+    assert_eq!(
+        eval_call("System`Times", vec![int(i64::MAX), int(2)]),
+        call("System`Times", vec![int(2), int(i64::MAX)])
+    );
+}
+
+#[test]
+fn powers() {
+    let power = |base, exp| eval_call("System`Power", vec![base, exp]);
+
+    assert_eq!(power(int(2), int(10)), int(1024));
+    assert_eq!(power(int(2), int(-2)), rational(1, 4));
+    assert_eq!(power(rational(2, 3), int(2)), rational(4, 9));
+    assert_eq!(power(int(8), rational(2, 3)), int(4));
+    assert_eq!(power(Expr::real(4.0), rational(1, 2)), Expr::real(2.0));
+    assert_eq!(power(Expr::real(-2.0), Expr::real(2.0)), Expr::real(4.0));
+    assert_eq!(power(sym("x"), int(1)), sym("x"));
+    assert_eq!(power(sym("x"), int(0)), int(1));
+    assert_eq!(power(int(0), int(-1)), sym("System`ComplexInfinity"));
+
+    // Irrational and complex results are left unevaluated.
+    assert_eq!(
+        power(int(2), rational(1, 2)),
+        call("System`Power", vec![int(2), rational(1, 2)])
+    );
+    assert_eq!(
+        power(int(-1), Expr::real(0.5)),
+        call("System`Power", vec![int(-1), Expr::real(0.5)])
+    );
+
+    // (x^2)^3 == x^6
+    assert_eq!(
+        power(call("System`Power", vec![sym("x"), int(2)]), int(3)),
+        call("System`Power", vec![sym("x"), int(6)])
+    );
+}
+
+#[test]
+fn integer_division() {
+    let modulo = |m, n| eval_call("System`Mod", vec![m, n]);
+    let quotient = |m, n| eval_call("System`Quotient", vec![m, n]);
+
+    assert_eq!(modulo(int(7), int(3)), int(1));
+    assert_eq!(modulo(int(-7), int(3)), int(2));
+    assert_eq!(modulo(int(7), int(-3)), int(-2));
+    assert_eq!(modulo(Expr::real(5.5), int(2)), Expr::real(1.5));
+    assert_eq!(modulo(int(1), int(0)), sym("System`Indeterminate"));
+
+    assert_eq!(quotient(int(7), int(2)), int(3));
+    assert_eq!(quotient(int(-7), int(2)), int(-4));
+    assert_eq!(quotient(Expr::real(7.5), int(2)), int(3));
+}
+
+#[test]
+fn comparisons() {
+    let t = Expr::from(true);
+    let f = Expr::from(false);
+
+    assert_eq!(eval_call("System`Less", vec![int(1), int(2), int(3)]), t);
+    assert_eq!(eval_call("System`Less", vec![int(1), int(3), int(2)]), f);
+    assert_eq!(eval_call("System`LessEqual", vec![rational(1, 2), Expr::real(0.5)]), t);
+    assert_eq!(eval_call("System`Greater", vec![int(2), rational(3, 2)]), t);
+    assert_eq!(
+        eval_call("System`Less", vec![int(1), sym("x")]),
+        call("System`Less", vec![int(1), sym("x")])
+    );
+
+    assert_eq!(eval_call("System`Equal", vec![int(1), Expr::real(1.0)]), t);
+    assert_eq!(
+        eval_call("System`Equal", vec![Expr::real(0.1 + 0.2), Expr::real(0.3)]),
+        t
+    );
+    assert_eq!(eval_call("System`Equal", vec![sym("x"), sym("x")]), t);
+    assert_eq!(
+        eval_call("System`Equal", vec![Expr::string("a"), Expr::string("b")]),
+        f
+    );
+    assert_eq!(
+        eval_call("System`Equal", vec![sym("x"), sym("y")]),
+        call("System`Equal", vec![sym("x"), sym("y")])
+    );
+    assert_eq!(eval_call("System`Unequal", vec![int(1), int(2), int(3)]), t);
+    assert_eq!(eval_call("System`Unequal", vec![int(1), int(2), int(1)]), f);
+
+    assert_eq!(
+        eval_call("System`Max", vec![int(1), ints(&[5, 2]), Expr::real(3.5)]),
+        int(5)
+    );
+    assert_eq!(
+        eval_call("System`Max", vec![sym("x"), int(1), int(2)]),
+        call("System`Max", vec![int(2), sym("x")])
+    );
+    assert_eq!(eval_call("System`Min", vec![rational(1, 2), int(1)]), rational(1, 2));
+    assert_eq!(
+        eval_call("System`Min", vec![]),
+        call("System`DirectedInfinity", vec![int(1)])
+    );
+}
+
+#[test]
+fn recursive_arithmetic() {
+    let mut evaluator = Evaluator::new();
+
+    // fact[0] = 1; fact[n_] := n fact[n - 1]
+    eval(&mut evaluator, set(call("fact", vec![int(0)]), int(1)));
+    eval(
+        &mut evaluator,
+        set_delayed(
+            call("fact", vec![blank("n")]),
+            call("System`Times", vec![
+                sym("n"),
+                call("fact", vec![call("System`Subtract", vec![sym("n"), int(1)])]),
+            ]),
+        ),
+    );
+
+    assert_eq!(
+        eval(&mut evaluator, call("fact", vec![int(20)])),
+        int(2_432_902_008_176_640_000)
+    );
+}
+
+#[test]
+fn derivatives() {
+    let x = || sym("x");
+    let x_cubed = call("System`Power", vec![x(), int(3)]);
+
+    // D[x^3, x], D[x^3, {x, 2}] and D[x^2 y, x, y]
+    assert_eq!(
+        eval_call("System`D", vec![x_cubed.clone(), x()]),
+        call("System`Times", vec![int(3), call("System`Power", vec![x(), int(2)])])
+    );
+    assert_eq!(
+        eval_call("System`D", vec![x_cubed.clone(), list(vec![x(), int(2)])]),
+        call("System`Times", vec![int(6), x()])
+    );
+    let x_squared_y = call("System`Times", vec![
+        call("System`Power", vec![x(), int(2)]),
+        sym("y"),
+    ]);
+    assert_eq!(
+        eval_call("System`D", vec![x_squared_y, x(), sym("y")]),
+        call("System`Times", vec![int(2), x()])
+    );
+
+    // D[x^3, 2] is left unevaluated.
+    assert_eq!(
+        eval_call("System`D", vec![x_cubed.clone(), int(2)]),
+        call("System`D", vec![x_cubed, int(2)])
+    );
+}
+
+#[test]
+fn list_functions() {
+    let range = |n| call("System`Range", vec![int(n)]);
+
+    assert_eq!(eval_call("System`Length", vec![ints(&[1, 2, 3])]), int(3));
+    assert_eq!(eval_call("System`Length", vec![rational(1, 2)]), int(0));
+
+    let matrix = list(vec![ints(&[1, 2]), ints(&[3, 4])]);
+    assert_eq!(eval_call("System`Part", vec![matrix.clone(), int(2), int(1)]), int(3));
+    assert_eq!(
+        eval_call("System`Part", vec![
+            matrix.clone(),
+            sym("System`All"),
+            int(-1)
+        ]),
+        ints(&[2, 4])
+    );
+    assert_eq!(
+        eval_call("System`Part", vec![
+            range(3),
+            call("System`Span", vec![int(2), sym("System`All")])
+        ]),
+        ints(&[2, 3])
+    );
+    assert_eq!(
+        eval_call("System`Part", vec![ints(&[1]), int(5)]),
+        call("System`Part", vec![ints(&[1]), int(5)])
+    );
+
+    assert_eq!(eval_call("System`Take", vec![range(5), int(2)]), ints(&[1, 2]));
+    assert_eq!(eval_call("System`Take", vec![range(5), int(-2)]), ints(&[4, 5]));
+    assert_eq!(
+        eval_call("System`Take", vec![range(5), ints(&[2, 4])]),
+        ints(&[2, 3, 4])
+    );
+    assert_eq!(eval_call("System`Drop", vec![range(5), int(2)]), ints(&[3, 4, 5]));
+    assert_eq!(
+        eval_call("System`Drop", vec![range(5), ints(&[1, 5, 2])]),
+        ints(&[2, 4])
+    );
+
+    assert_eq!(
+        eval_call("System`Join", vec![ints(&[1]), ints(&[2, 3])]),
+        ints(&[1, 2, 3])
+    );
+
+    assert_eq!(eval_call("System`Range", vec![int(0)]), ints(&[]));
+    assert_eq!(
+        eval_call("System`Range", vec![int(2), int(8), int(3)]),
+        ints(&[2, 5, 8])
+    );
+    assert_eq!(
+        eval_call("System`Range", vec![int(0), int(1), Expr::real(0.5)]),
+        list(vec![Expr::real(0.0), Expr::real(0.5), Expr::real(1.0)])
+    );
+    assert_eq!(
+        eval_call("System`Range", vec![rational(1, 2), int(2)]),
+        list(vec![rational(1, 2), rational(3, 2)])
+    );
+
+    assert_eq!(eval_call("System`Total", vec![ints(&[1, 2, 3])]), int(6));
+    assert_eq!(eval_call("System`Total", vec![matrix]), ints(&[4, 6]));
+}
+
+#[test]
+fn table() {
+    let mut evaluator = Evaluator::new();
+    eval(&mut evaluator, set(sym("i"), sym("saved")));
+
+    // Table[i^2, {i, 3}]
+    let squares = call("System`Table", vec![
+        call("System`Power", vec![sym("i"), int(2)]),
+        list(vec![sym("i"), int(3)]),
+    ]);
+    assert_eq!(eval(&mut evaluator, squares), ints(&[1, 4, 9]));
+    assert_eq!(eval(&mut evaluator, sym("i")), sym("saved"));
+
+    // Table[x, 2]
+    assert_eq!(
+        eval(&mut evaluator, call("System`Table", vec![sym("x"), int(2)])),
+        list(vec![sym("x"), sym("x")])
+    );
+
+    // Table[{i, j}, {i, 2}, {j, i}]
+    let triangle = call("System`Table", vec![
+        list(vec![sym("i"), sym("j")]),
+        list(vec![sym("i"), int(2)]),
+        list(vec![sym("j"), sym("i")]),
+    ]);
+    assert_eq!(
+        eval(&mut evaluator, triangle),
+        list(vec![
+            list(vec![ints(&[1, 1])]),
+            list(vec![ints(&[2, 1]), ints(&[2, 2])])
+        ])
+    );
+
+    // Table[f[i], {i, {a, b}}]
+    let values = call("System`Table", vec![
+        call("f", vec![sym("i")]),
+        list(vec![sym("i"), list(vec![sym("a"), sym("b")])]),
+    ]);
+    assert_eq!(
+        eval(&mut evaluator, values),
+        list(vec![call("f", vec![sym("a")]), call("f", vec![sym("b")])])
+    );
+}
+
+#[test]
+fn functional_list_functions() {
+    let slot = call("System`Slot", vec![int(1)]);
+    let greater_than_3 =
+        call("System`Function", vec![call("System`Greater", vec![slot, int(3)])]);
+    let range = call("System`Range", vec![int(6)]);
+
+    assert_eq!(
+        eval_call("System`Map", vec![sym("f"), ints(&[1, 2])]),
+        list(vec![call("f", vec![int(1)]), call("f", vec![int(2)])])
+    );
+    assert_eq!(eval_call("System`Map", vec![sym("f"), sym("x")]), sym("x"));
+
+    assert_eq!(
+        eval_call("System`Select", vec![range.clone(), greater_than_3.clone()]),
+        ints(&[4, 5, 6])
+    );
+    assert_eq!(
+        eval_call("System`Select", vec![range, greater_than_3, int(1)]),
+        ints(&[4])
+    );
+
+    assert_eq!(eval_call("System`Sort", vec![ints(&[3, 1, 2])]), ints(&[1, 2, 3]));
+    assert_eq!(
+        eval_call("System`Sort", vec![ints(&[3, 1, 2]), sym("System`Greater")]),
+        ints(&[3, 2, 1])
+    );
+}
+
+#[test]
+fn string_functions() {
+    assert_eq!(
+        eval_call("System`StringJoin", vec![
+            Expr::string("a"),
+            strings(&["b", "c"])
+        ]),
+        Expr::string("abc")
+    );
+    assert_eq!(
+        eval_call("System`StringLength", vec![Expr::string("héllo")]),
+        int(5)
+    );
+    assert_eq!(
+        eval_call("System`StringLength", vec![strings(&["a", "bb"])]),
+        ints(&[1, 2])
+    );
+
+    assert_eq!(
+        eval_call("System`StringSplit", vec![Expr::string("  a b\tc ")]),
+        strings(&["a", "b", "c"])
+    );
+    assert_eq!(
+        eval_call("System`StringSplit", vec![
+            Expr::string(",a,b,,c,"),
+            Expr::string(",")
+        ]),
+        strings(&["a", "b", "", "c"])
+    );
+    assert_eq!(
+        eval_call("System`StringSplit", vec![
+            Expr::string("a-b+c"),
+            strings(&["-", "+"])
+        ]),
+        strings(&["a", "b", "c"])
+    );
+
+    assert_eq!(
+        eval_call("System`ToUpperCase", vec![Expr::string("abc")]),
+        Expr::string("ABC")
+    );
+    assert_eq!(
+        eval_call("System`ToLowerCase", vec![Expr::string("ABC")]),
+        Expr::string("abc")
+    );
+}
+
+#[test]
+fn associations() {
+    use wolfram_expr::{Association, RuleKind};
+
+    let mut evaluator = Evaluator::new();
+    let one_plus_one = call("System`Plus", vec![int(1), int(1)]);
+
+    // Association[{"a" -> 1 + 1}, "b" :> 1 + 1]
+    let expr = call("System`Association", vec![
+        list(vec![Expr::rule("a", one_plus_one.clone())]),
+        Expr::rule_delayed("b", one_plus_one.clone()),
+    ]);
+    let mut expected = Association::new();
+    expected.insert("a", int(2));
+    expected.insert_rule("b", RuleKind::RuleDelayed, one_plus_one.clone());
+    let expected = Expr::from(expected);
+    assert_eq!(eval(&mut evaluator, expr), expected);
+
+    // assoc = <|...|>
+    eval(&mut evaluator, set(sym("assoc"), expected));
+    let assoc = sym("assoc");
+
+    assert_eq!(
+        eval(&mut evaluator, Expr::normal(assoc.clone(), vec![Expr::string("a")])),
+        int(2)
+    );
+    assert_eq!(
+        eval(&mut evaluator, Expr::normal(assoc.clone(), vec![Expr::string("c")])),
+        call("System`Missing", vec![Expr::string("KeyAbsent"), Expr::string("c")])
+    );
+    assert_eq!(
+        eval(&mut evaluator, call("System`Length", vec![assoc.clone()])),
+        int(2)
+    );
+    assert_eq!(
+        eval(&mut evaluator, call("System`Keys", vec![assoc.clone()])),
+        strings(&["a", "b"])
+    );
+    assert_eq!(
+        eval(&mut evaluator, call("System`Values", vec![assoc.clone()])),
+        ints(&[2, 2])
+    );
+    assert_eq!(
+        eval(&mut evaluator, call("System`Lookup", vec![
+            assoc.clone(),
+            Expr::string("c"),
+            int(0)
+        ])),
+        int(0)
+    );
+    assert_eq!(
+        eval(&mut evaluator, call("System`Part", vec![assoc, int(1)])),
+        int(2)
+    );
+
+    // Invalid elements leave Association[...] unevaluated.
+    let invalid = call("System`Association", vec![int(1)]);
+    assert_eq!(eval(&mut evaluator, invalid.clone()), invalid);
+}
@@ -1,4 +1,32 @@
-use wolfram_expr::{Association, Expr};
+use wolfram_expr::{Association, Expr, Normal, PartError, PartSpec, Symbol};
+
+//======================================
+// Helpers
+//======================================
+
+fn sym(name: &str) -> Expr {
+    Expr::symbol(Symbol::new(name))
+}
+
+fn call(head: &str, elems: Vec<Expr>) -> Expr {
+    Expr::function(Symbol::new(head), elems)
+}
+
+fn plus(elems: Vec<Expr>) -> Expr {
+    call("System`Plus", elems)
+}
+
+fn times(elems: Vec<Expr>) -> Expr {
+    call("System`Times", elems)
+}
+
+fn power(base: Expr, exp: Expr) -> Expr {
+    call("System`Power", vec![base, exp])
+}
+
+fn rational(numer: i64, denom: i64) -> Expr {
+    call("System`Rational", vec![numer.into(), denom.into()])
+}
 
 #[test]
 fn association_display() {
@@ -15,6 +43,96 @@ fn association_display() {
     );
 }
 
+#[test]
+fn association_rules() {
+    use std::convert::TryFrom;
+    use wolfram_expr::AssociationError;
+
+    let mut assoc = Association::new();
+    assoc.insert("a", Expr::from(1));
+    assoc.insert_delayed("b", Expr::from(2));
+
+    let expr = Expr::from(assoc.clone());
+    assert_eq!(expr.try_as_association(), Some(&assoc));
+
+    // Display and WXF agree on which rule is delayed.
+    assert_eq!(assoc.to_string(), r#"<|"a" -> 1, "b" :> 2|>"#);
+    assert_eq!(expr.to_string(), r#"<|"a" -> 1, "b" :> 2|>"#);
+    let wxf_entry = |key: u8, value: u8| [b'S', 1, key, b'L', value, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(
+        expr.as_wxf(),
+        [
+            &b"8:A"[..],
+            &[2, b'-'],
+            &wxf_entry(b'a', 1),
+            b":",
+            &wxf_entry(b'b', 2),
+        ]
+        .concat()
+    );
+
+    // The unevaluated Association[...] form
+    let normal = Expr::function("System`Association", vec![
+        Expr::rule("a", Expr::from(1)),
+        Expr::rule_delayed("b", Expr::from(2)),
+    ]);
+    assert_eq!(
+        normal.to_string(),
+        r#"System`Association[System`Rule["a", 1], System`RuleDelayed["b", 2]]"#
+    );
+    let wxf_symbol = |name: &str| [&[b's', name.len() as u8], name.as_bytes()].concat();
+    let wxf_rule = |head: &str, key: u8, value: u8| {
+        [&[b'f', 2][..], &wxf_symbol(head), &wxf_entry(key, value)].concat()
+    };
+    assert_eq!(
+        normal.as_wxf(),
+        [
+            &b"8:"[..],
+            &[b'f', 2],
+            &wxf_symbol("System`Association"),
+            &wxf_rule("System`Rule", b'a', 1),
+            &wxf_rule("System`RuleDelayed", b'b', 2),
+        ]
+        .concat()
+    );
+
+    // Round trip
+    assert_eq!(Association::try_from(&normal), Ok(assoc.clone()));
+    assert_eq!(Association::try_from(&expr), Ok(assoc.clone()));
+    assert_eq!(Expr::from(Association::try_from(&normal).unwrap()), expr);
+
+    // Nested lists of rules and associations are accepted. Repeated keys keep their
+    // first position and last value.
+    let expr = Expr::function("System`Association", vec![
+        Expr::list(vec![
+            Expr::rule("a", Expr::from(1)),
+            Expr::list(vec![Expr::rule_delayed("b", Expr::from(2))]),
+        ]),
+        Expr::function("System`Association", vec![Expr::rule("c", Expr::from(3))]),
+        Expr::rule("a", Expr::from(4)),
+    ]);
+    assert_eq!(
+        Association::try_from(&expr).unwrap().to_string(),
+        r#"<|"a" -> 4, "b" :> 2, "c" -> 3|>"#
+    );
+
+    // Errors
+    let list = Expr::list(vec![]);
+    assert_eq!(
+        Association::try_from(&list).err(),
+        Some(AssociationError::NotAssociation { expr: list.clone() })
+    );
+    let expr = Expr::function("System`Association", vec![Expr::list(vec![
+        Expr::from(1),
+    ])]);
+    assert_eq!(
+        Association::try_from(&expr).err(),
+        Some(AssociationError::InvalidElement {
+            elem: Expr::from(1)
+        })
+    );
+}
+
 #[test]
 fn test_curry() {
     // Normal@BinarySerialize[Sin[1]]
@@ -40,3 +158,1781 @@ fn test_curry() {
     ]);
     assert_eq!(v.to_string(), "System`Sin[1][2][3]");
 }
+
+//======================================
+// Deeply nested expressions
+//======================================
+
+const DEPTH: usize = 1_000_000;
+
+/// Construct `f[f[f[...f[0]...]]]`, nested `depth` times.
+fn nested_elements(depth: usize) -> Expr {
+    let f = Expr::from(Symbol::new("Global`f"));
+    let mut expr = Expr::from(0);
+    for _ in 0..depth {
+        expr = Expr::normal(f.clone(), vec![expr]);
+    }
+    expr
+}
+
+/// Construct `f[0][0][0]...[0]`, with heads nested `depth` times.
+fn nested_heads(depth: usize) -> Expr {
+    let mut expr = Expr::symbol(Symbol::new("Global`f"));
+    for _ in 0..depth {
+        expr = Expr::normal(expr, vec![Expr::from(0)]);
+    }
+    expr
+}
+
+#[test]
+fn deep_drop() {
+    drop(nested_elements(DEPTH));
+    drop(nested_heads(DEPTH));
+
+    // Drop a deep expression whose inner part is still shared.
+    let shared = nested_elements(DEPTH);
+    let outer = Expr::function(Symbol::new("Global`g"), vec![shared.clone()]);
+    drop(outer);
+    assert_eq!(shared.ref_count(), 1);
+}
+
+#[test]
+fn deep_display() {
+    let string = nested_elements(DEPTH).to_string();
+    assert_eq!(string.len(), DEPTH * "Global`f[]".len() + 1);
+    assert!(string.starts_with("Global`f[Global`f["));
+    assert!(string.contains("Global`f[0]]]"));
+    assert!(string.ends_with("]]]"));
+
+    let string = nested_heads(DEPTH).to_string();
+    assert!(string.starts_with("Global`f[0][0]"));
+
+    assert_eq!(
+        format!("{:?}", nested_elements(2)),
+        "Global`f[Global`f[0]]"
+    );
+}
+
+#[test]
+fn deep_eq_and_hash() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash(expr: &Expr) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        expr.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let a = nested_elements(DEPTH);
+    let b = nested_elements(DEPTH);
+    assert_eq!(a, b);
+    assert_eq!(hash(&a), hash(&b));
+
+    let c = nested_elements(DEPTH - 1);
+    assert_ne!(a, c);
+
+    assert_eq!(nested_heads(DEPTH), nested_heads(DEPTH));
+    assert_ne!(nested_heads(DEPTH), nested_elements(DEPTH));
+}
+
+/// Construct `<|"a" -> <|"a" -> ...<|"a" -> 0|>...|>|>`, nested `depth` times.
+fn nested_associations(depth: usize) -> Expr {
+    let mut expr = Expr::from(0);
+    for _ in 0..depth {
+        let mut assoc = Association::new();
+        assoc.insert("a", expr);
+        expr = Expr::from(assoc);
+    }
+    expr
+}
+
+#[test]
+fn deep_associations() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash(expr: &Expr) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        expr.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let a = nested_associations(DEPTH);
+    let b = nested_associations(DEPTH);
+    assert_eq!(a, b);
+    assert_eq!(hash(&a), hash(&b));
+    assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+    assert_ne!(a, nested_associations(DEPTH - 1));
+
+    let string = a.to_string();
+    assert_eq!(string.len(), DEPTH * r#"<|"a" -> |>"#.len() + 1);
+    assert!(string.starts_with(r#"<|"a" -> <|"a" -> "#));
+    assert!(string.contains(r#"<|"a" -> 0|>|>"#));
+    assert!(string.ends_with("|>|>|>"));
+
+    #[cfg(feature = "wxf")]
+    {
+        // Per level: 'A', length 1, '-', 'S', length 1, "a"; then 'L' + 8 bytes.
+        assert_eq!(a.as_wxf().len(), 2 + DEPTH * 6 + 9);
+    }
+
+    drop(a);
+    drop(b);
+}
+
+#[cfg(feature = "wxf")]
+#[test]
+fn deep_wxf() {
+    let wxf = nested_elements(DEPTH).as_wxf();
+    // "8:", then per level: 'f', length 1, 's', length 8, "Global`f"; then 'L' + 8 bytes.
+    assert_eq!(wxf.len(), 2 + DEPTH * 12 + 9);
+    assert_eq!(&wxf[..6], b"8:f\x01s\x08");
+    assert_eq!(nested_heads(DEPTH).as_wxf().len(), 2 + DEPTH * 11 + 10);
+}
+
+//======================================
+// Part
+//======================================
+
+fn int_list(elements: &[i64]) -> Expr {
+    Expr::list(elements.iter().map(|&n| Expr::from(n)).collect())
+}
+
+#[test]
+fn part_indices() {
+    let list = int_list(&[10, 20, 30]);
+
+    assert_eq!(list.part(&[PartSpec::Index(1)]), Ok(Expr::from(10)));
+    assert_eq!(list.part(&[PartSpec::Index(-1)]), Ok(Expr::from(30)));
+    assert_eq!(
+        list.part(&[PartSpec::Index(0)]),
+        Ok(Expr::symbol(Symbol::new("System`List")))
+    );
+    assert_eq!(
+        Expr::from(5).part(&[PartSpec::Index(0)]),
+        Ok(Expr::symbol(Symbol::new("System`Integer")))
+    );
+
+    assert!(matches!(
+        list.part(&[PartSpec::Index(4)]),
+        Err(PartError::PartDoesNotExist { index: 4, .. })
+    ));
+    assert!(matches!(
+        list.part(&[PartSpec::Index(-4)]),
+        Err(PartError::PartDoesNotExist { index: -4, .. })
+    ));
+    assert!(matches!(
+        list.part(&[PartSpec::Index(1), PartSpec::Index(1)]),
+        Err(PartError::DepthExceeded { .. })
+    ));
+    assert_eq!(
+        list.part(&[PartSpec::Index(4)]).unwrap_err().to_string(),
+        "Part 4 of System`List[10, 20, 30] does not exist."
+    );
+}
+
+#[test]
+fn part_spans() {
+    let list = int_list(&[1, 2, 3, 4, 5]);
+
+    assert_eq!(list.part(&[PartSpec::All]), Ok(list.clone()));
+    assert_eq!(list.part(&[PartSpec::span(2, -2)]), Ok(int_list(&[2, 3, 4])));
+    assert_eq!(
+        list.part(&[PartSpec::Span {
+            start: 1,
+            end: -1,
+            step: 2
+        }]),
+        Ok(int_list(&[1, 3, 5]))
+    );
+    assert_eq!(
+        list.part(&[PartSpec::Span {
+            start: -1,
+            end: 1,
+            step: -2
+        }]),
+        Ok(int_list(&[5, 3, 1]))
+    );
+    assert_eq!(list.part(&[PartSpec::span(3, 2)]), Ok(int_list(&[])));
+
+    assert!(matches!(
+        list.part(&[PartSpec::span(2, 6)]),
+        Err(PartError::InvalidSpan { start: 2, end: 6, .. })
+    ));
+    assert_eq!(
+        list.part(&[PartSpec::Span {
+            start: 1,
+            end: 2,
+            step: 0
+        }]),
+        Err(PartError::ZeroStep)
+    );
+
+    // Steps which would overflow the position stop the span.
+    assert_eq!(
+        list.part(&[PartSpec::Span {
+            start: 1,
+            end: 3,
+            step: i64::MAX
+        }]),
+        Ok(int_list(&[1]))
+    );
+    assert_eq!(
+        list.part(&[PartSpec::Span {
+            start: -1,
+            end: 1,
+            step: i64::MIN
+        }]),
+        Ok(int_list(&[5]))
+    );
+
+    // {{1, 2, 3}, {4, 5, 6}}[[All, -1]]
+    let matrix = Expr::list(vec![int_list(&[1, 2, 3]), int_list(&[4, 5, 6])]);
+    assert_eq!(
+        matrix.part(&[PartSpec::All, PartSpec::Index(-1)]),
+        Ok(int_list(&[3, 6]))
+    );
+    assert_eq!(
+        matrix.part(&[PartSpec::Index(2), PartSpec::span(1, 2)]),
+        Ok(int_list(&[4, 5]))
+    );
+}
+
+#[test]
+fn part_editing() {
+    let shared = int_list(&[1, 2]);
+    let list = Expr::list(vec![shared.clone(), int_list(&[3, 4])]);
+
+    let updated = list.replace_part(&[2, 1], Expr::from(30)).unwrap();
+    assert_eq!(updated, Expr::list(vec![int_list(&[1, 2]), int_list(&[30, 4])]));
+    assert_eq!(list, Expr::list(vec![int_list(&[1, 2]), int_list(&[3, 4])]));
+    // The element that was not on the path is shared between `list` and `updated`.
+    assert_eq!(shared.ref_count(), 3);
+
+    assert_eq!(
+        list.replace_part(&[0], Expr::symbol(Symbol::new("Global`f")))
+            .unwrap()
+            .to_string(),
+        "Global`f[System`List[1, 2], System`List[3, 4]]"
+    );
+
+    assert_eq!(
+        list.insert_at(&[2, -1], Expr::from(5)).unwrap(),
+        Expr::list(vec![int_list(&[1, 2]), int_list(&[3, 4, 5])])
+    );
+    assert_eq!(
+        list.delete_at(&[1, 1]).unwrap(),
+        Expr::list(vec![int_list(&[2]), int_list(&[3, 4])])
+    );
+    assert_eq!(
+        list.append_to(&[1], Expr::from(0)).unwrap(),
+        Expr::list(vec![int_list(&[1, 2, 0]), int_list(&[3, 4])])
+    );
+    assert_eq!(
+        list.prepend_to(&[], Expr::from(0)).unwrap(),
+        Expr::list(vec![Expr::from(0), int_list(&[1, 2]), int_list(&[3, 4])])
+    );
+
+    assert!(matches!(
+        list.insert_at(&[1, 4], Expr::from(0)),
+        Err(PartError::PartDoesNotExist { index: 4, .. })
+    ));
+    assert!(matches!(
+        list.delete_at(&[1, 0]),
+        Err(PartError::PartDoesNotExist { index: 0, .. })
+    ));
+    for &index in &[i64::MIN, i64::MAX] {
+        assert!(matches!(
+            list.insert_at(&[index], Expr::from(0)),
+            Err(PartError::PartDoesNotExist { .. })
+        ));
+        assert!(matches!(
+            list.delete_at(&[index]),
+            Err(PartError::PartDoesNotExist { .. })
+        ));
+        assert!(matches!(
+            list.replace_part(&[1, index], Expr::from(0)),
+            Err(PartError::PartDoesNotExist { .. })
+        ));
+    }
+    assert!(matches!(
+        list.append_to(&[1, 1], Expr::from(0)),
+        Err(PartError::AtomicExpression { .. })
+    ));
+    assert_eq!(list.delete_at(&[]), Err(PartError::EmptyPath));
+
+    // Paths reach into the values of associations: {<|"a" -> {1, 2}|>}
+    let assoc = |value: Expr| {
+        let mut assoc = Association::new();
+        assoc.insert("a", value);
+        Expr::list(vec![Expr::from(assoc)])
+    };
+    let nested = assoc(int_list(&[1, 2]));
+    assert_eq!(
+        nested.replace_part(&[1, 1, 1], Expr::from(10)),
+        Ok(assoc(int_list(&[10, 2])))
+    );
+    assert_eq!(nested.delete_at(&[1, -1, 2]), Ok(assoc(int_list(&[1]))));
+    assert!(matches!(
+        nested.replace_part(&[1, 2], Expr::from(0)),
+        Err(PartError::PartDoesNotExist { index: 2, .. })
+    ));
+    assert!(matches!(
+        nested.replace_part(&[1, 0], Expr::from(0)),
+        Err(PartError::DepthExceeded { .. })
+    ));
+}
+
+#[test]
+fn normal_mutation() {
+    let mut expr = int_list(&[1, 2, 3]);
+    let original = expr.clone();
+
+    let normal = expr.try_as_normal_mut().unwrap();
+    normal.push(Expr::from(4));
+    normal.insert(0, Expr::from(0));
+    assert_eq!(normal.remove(1), Expr::from(1));
+    assert_eq!(normal.pop(), Some(Expr::from(4)));
+    normal.retain(|elem| *elem != Expr::from(2));
+    normal.extend(vec![Expr::from(5), Expr::from(6)]);
+    let removed: Vec<Expr> = normal.splice(1..3, vec![Expr::from(7)]).collect();
+    assert_eq!(removed, vec![Expr::from(3), Expr::from(5)]);
+    *normal.head_mut() = Expr::symbol(Symbol::new("Global`f"));
+
+    assert_eq!(expr.to_string(), "Global`f[0, 7, 6]");
+    // `expr` was shared with `original`, so it was cloned before being modified.
+    assert_eq!(original, int_list(&[1, 2, 3]));
+
+    assert!(Expr::from(1).try_as_normal_mut().is_none());
+
+    let elements = (1..=2).map(Expr::from);
+    let times = Normal::from_elements(Symbol::new("System`Times"), elements);
+    assert_eq!(Expr::from(times).to_string(), "System`Times[1, 2]");
+    let list: Normal = (1..=2).map(Expr::from).collect();
+    assert_eq!(Expr::from(list), int_list(&[1, 2]));
+}
+
+//======================================
+// Canonical order
+//======================================
+
+#[test]
+fn canonical_order() {
+    use std::cmp::Ordering;
+    use std::collections::BTreeMap;
+
+    let f = |elems: Vec<Expr>| Expr::function(Symbol::new("Global`f"), elems);
+    let g = |elems: Vec<Expr>| Expr::function(Symbol::new("Global`g"), elems);
+
+    let sorted = vec![
+        Expr::from(-3),
+        Expr::from(1),
+        Expr::real(1.0),
+        Expr::real(2.5),
+        Expr::string("a"),
+        Expr::string("A"),
+        Expr::string("ab"),
+        Expr::string("B"),
+        sym("Global`a"),
+        sym("System`a"),
+        sym("Global`B"),
+        f(vec![Expr::from(2)]),
+        g(vec![Expr::from(1)]),
+        f(vec![Expr::from(1), Expr::from(1)]),
+    ];
+
+    let mut exprs = sorted.clone();
+    exprs.reverse();
+    exprs.sort();
+    assert_eq!(exprs, sorted);
+
+    assert_eq!(
+        Expr::from(i64::MAX).cmp(&Expr::from(i64::MAX - 1)),
+        Ordering::Greater
+    );
+
+    // Integers and reals are compared exactly, even above 2^53.
+    let two_53 = 9_007_199_254_740_992_i64;
+    assert_eq!(Expr::from(two_53 + 1).cmp(&Expr::real(two_53 as f64)), Ordering::Greater);
+    assert_eq!(Expr::from(two_53).cmp(&Expr::real(two_53 as f64)), Ordering::Less);
+    assert_eq!(Expr::from(two_53 - 1).cmp(&Expr::real(two_53 as f64)), Ordering::Less);
+    assert_eq!(Expr::real(-0.5).cmp(&Expr::from(-1)), Ordering::Greater);
+    assert_eq!(Expr::real(1e300).cmp(&Expr::from(i64::MAX)), Ordering::Greater);
+    assert_eq!(Expr::real(1e-300).cmp(&Expr::from(0)), Ordering::Greater);
+    let third = rational(1, 3);
+    assert_eq!(third.cmp(&Expr::real(1.0 / 3.0)), Ordering::Greater);
+
+    let mut map = BTreeMap::new();
+    map.insert(sym("Global`x"), 1);
+    map.insert(Expr::from(5), 2);
+    assert_eq!(map.keys().next(), Some(&Expr::from(5)));
+
+    // Numbers with the same value are only `Equal` if they are the same expression.
+    assert_eq!(Expr::from(2).cmp(&rational(2, 1)), Ordering::Less);
+    assert_eq!(rational(1, 2).cmp(&rational(2, 4)), Ordering::Less);
+    let mut map = BTreeMap::new();
+    for key in [rational(2, 4), rational(1, 2), Expr::from(2), rational(2, 1)] {
+        map.insert(key, ());
+    }
+    assert_eq!(map.len(), 4);
+
+    let deep = nested_elements(DEPTH);
+    assert_eq!(deep.cmp(&nested_elements(DEPTH)), Ordering::Equal);
+}
+
+#[test]
+fn sort_union_delete_duplicates() {
+    let list = int_list(&[3, 1, 2, 3, 1]);
+
+    assert_eq!(list.sort(), Some(int_list(&[1, 1, 2, 3, 3])));
+    assert_eq!(list.union(), Some(int_list(&[1, 2, 3])));
+    assert_eq!(list.delete_duplicates(), Some(int_list(&[3, 1, 2])));
+    assert_eq!(Expr::from(1).sort(), None);
+}
+
+//======================================
+// Attributes
+//======================================
+
+#[test]
+fn canonicalize() {
+    use wolfram_expr::{Attribute, AttributeTable};
+
+    // Plus[b, a, Plus[c, Times[y, x]]] => Plus[a, b, c, Times[x, y]]
+    let expr = call("System`Plus", vec![
+        sym("Global`b"),
+        sym("Global`a"),
+        call("System`Plus", vec![
+            sym("Global`c"),
+            call("System`Times", vec![sym("Global`y"), sym("Global`x")]),
+        ]),
+    ]);
+    assert_eq!(
+        expr.canonicalize().to_string(),
+        "System`Plus[Global`a, Global`b, Global`c, System`Times[Global`x, Global`y]]"
+    );
+
+    // OneIdentity: Plus[Plus[x]] => x
+    let expr = call("System`Plus", vec![call("System`Plus", vec![sym("Global`x")])]);
+    assert_eq!(expr.canonicalize(), sym("Global`x"));
+
+    // Held elements are left unchanged.
+    let held = call("System`Plus", vec![sym("Global`b"), sym("Global`a")]);
+    let expr = call("System`Hold", vec![held.clone()]);
+    assert_eq!(expr.canonicalize(), expr);
+    let expr = call("System`If", vec![held.clone(), held.clone()]);
+    assert_eq!(
+        expr.canonicalize(),
+        call("System`If", vec![held.canonicalize(), held])
+    );
+
+    // User-defined attributes.
+    let mut table = AttributeTable::system().clone();
+    table.insert(Symbol::new("Global`f"), Attribute::Orderless);
+    let expr = call("Global`f", vec![Expr::from(2), Expr::from(1)]);
+    assert_eq!(expr.canonicalize(), expr);
+    assert_eq!(
+        expr.canonicalize_with(&table),
+        call("Global`f", vec![Expr::from(1), Expr::from(2)])
+    );
+
+    assert!(Symbol::new("System`Times")
+        .attributes()
+        .contains(Attribute::Orderless));
+    assert_eq!(
+        Symbol::new("System`Set").attributes().to_expr().to_string(),
+        "System`List[System`HoldFirst, System`Protected]"
+    );
+}
+
+#[test]
+fn numeric_value() {
+    use std::f64::consts::{E, PI};
+    use wolfram_expr::{Complex, NumericError};
+
+    let close = |actual: Complex, re: f64, im: f64| {
+        (actual.re - re).abs() < 1e-12 && (actual.im - im).abs() < 1e-12
+    };
+
+    assert_eq!(Expr::from(3).to_f64(), Ok(3.0));
+    assert_eq!(sym("System`Pi").to_f64(), Ok(PI));
+    assert_eq!(call("System`Log", vec![sym("System`E")]).to_f64(), Ok(1.0));
+    assert_eq!(
+        rational(3, 4).to_f64(),
+        Ok(0.75)
+    );
+
+    // Sin[Pi / 6] + Exp[2]
+    let expr = call("System`Plus", vec![
+        call("System`Sin", vec![call("System`Times", vec![
+            sym("System`Pi"),
+            call("System`Power", vec![Expr::from(6), Expr::from(-1)]),
+        ])]),
+        call("System`Exp", vec![Expr::from(2)]),
+    ]);
+    assert!((expr.to_f64().unwrap() - (0.5 + E * E)).abs() < 1e-12);
+
+    // Complex results
+    let sqrt_minus_4 = call("System`Sqrt", vec![Expr::from(-4)]);
+    assert!(close(sqrt_minus_4.numeric_value().unwrap(), 0.0, 2.0));
+    assert!(matches!(sqrt_minus_4.to_f64(), Err(NumericError::NotReal { .. })));
+    let cube_root = call("System`Power", vec![
+        Expr::from(-8),
+        rational(1, 3),
+    ]);
+    assert!(close(cube_root.numeric_value().unwrap(), 1.0, 3f64.sqrt()));
+    // E^(I Pi) + 1 == 0
+    let euler = call("System`Plus", vec![
+        call("System`Power", vec![
+            sym("System`E"),
+            call("System`Times", vec![sym("System`I"), sym("System`Pi")]),
+        ]),
+        Expr::from(1),
+    ]);
+    assert!(close(euler.numeric_value().unwrap(), 0.0, 0.0));
+    assert!(close(
+        call("System`Complex", vec![Expr::from(1), Expr::from(2)])
+            .numeric_value()
+            .unwrap(),
+        1.0,
+        2.0
+    ));
+
+    // The parts of complex numbers are displayed like reals.
+    assert_eq!(Complex::new(1.5, 0.0).to_string(), "1.5");
+    assert_eq!(Complex::new(2.0, -1e-7).to_string(), "2. - 1.*^-7 I");
+    assert_eq!(Complex::new(0.0, 1e6).to_string(), "0. + 1.*^6 I");
+
+    // Errors name the first non-numeric sub-expression.
+    let f_x = call("Global`f", vec![sym("Global`x")]);
+    let expr = call("System`Plus", vec![
+        Expr::from(1),
+        call("System`Cos", vec![f_x.clone()]),
+        sym("Global`y"),
+    ]);
+    assert_eq!(expr.numeric_value(), Err(NumericError::NonNumeric { expr: f_x }));
+    assert_eq!(
+        Expr::string("1").to_f64(),
+        Err(NumericError::NonNumeric {
+            expr: Expr::string("1")
+        })
+    );
+    let one_over_zero = call("System`Divide", vec![Expr::from(1), Expr::from(0)]);
+    assert_eq!(
+        one_over_zero.to_f64(),
+        Err(NumericError::NotFinite {
+            expr: one_over_zero.clone()
+        })
+    );
+}
+
+#[test]
+fn compile() {
+    use wolfram_expr::CompileError;
+
+    let (x, y) = (Symbol::new("Global`x"), Symbol::new("Global`y"));
+
+    // x^2 + Sin[y]
+    let expr = call("System`Plus", vec![
+        call("System`Power", vec![sym("Global`x"), Expr::from(2)]),
+        call("System`Sin", vec![sym("Global`y")]),
+    ]);
+    let function = expr.compile(&[x.clone(), y.clone()]).unwrap();
+    assert_eq!(function.params(), &[x.clone(), y.clone()][..]);
+    assert_eq!(function.call(&[3.0, 0.0]), 9.0);
+    assert!((function.call(&[0.5, 2.0]) - (0.25 + 2f64.sin())).abs() < 1e-12);
+
+    let mut registers = Vec::new();
+    for i in 0..10 {
+        let (a, b) = (f64::from(i), f64::from(i) / 3.0);
+        assert_eq!(function.call_with(&[a, b], &mut registers), a * a + b.sin());
+    }
+
+    // Pi * (1/2) * 2 + x is folded to a single constant and one addition.
+    let expr = call("System`Plus", vec![
+        call("System`Times", vec![
+            sym("System`Pi"),
+            rational(1, 2),
+            Expr::from(2),
+        ]),
+        sym("Global`x"),
+    ]);
+    let function = expr.compile(std::slice::from_ref(&x)).unwrap();
+    assert_eq!(function.instruction_count(), 3);
+    assert_eq!(function.call(&[1.0]), std::f64::consts::PI + 1.0);
+
+    // 0 * Log[x] is not folded to 0, since Log[0] is -Infinity and the product is NaN.
+    let expr = call("System`Times", vec![
+        Expr::from(0),
+        call("System`Log", vec![sym("Global`x")]),
+    ]);
+    let function = expr.compile(std::slice::from_ref(&x)).unwrap();
+    assert!(function.call(&[0.0]).is_nan());
+    assert_eq!(function.call(&[1.0]), 0.0);
+
+    // Common subexpressions are computed once: Sin[x] + Sin[x] * Sin[x].
+    let sin_x = call("System`Sin", vec![sym("Global`x")]);
+    let expr = call("System`Plus", vec![
+        sin_x.clone(),
+        call("System`Times", vec![sin_x.clone(), sin_x]),
+    ]);
+    let function = expr.compile(std::slice::from_ref(&x)).unwrap();
+    assert_eq!(function.instruction_count(), 4);
+    assert_eq!(function.call(&[1.0]), 1f64.sin() + 1f64.sin() * 1f64.sin());
+
+    // A shared sub-expression is lowered once, without walking the 2^64 paths of
+    // x + x, (x + x) + (x + x), ...
+    let mut doubled = sym("Global`x");
+    for _ in 0..64 {
+        doubled = call("System`Plus", vec![doubled.clone(), doubled]);
+    }
+    let function = doubled.compile(std::slice::from_ref(&x)).unwrap();
+    assert_eq!(function.instruction_count(), 65);
+    assert_eq!(function.call(&[1.0]), 2f64.powi(64));
+
+    // Errors
+    assert_eq!(
+        expr.compile(&[]).unwrap_err(),
+        CompileError::UnknownSymbol { symbol: x.clone() }
+    );
+    let f_x = call("Global`f", vec![sym("Global`x")]);
+    assert_eq!(
+        call("System`Plus", vec![f_x.clone(), Expr::from(1)])
+            .compile(std::slice::from_ref(&x))
+            .unwrap_err(),
+        CompileError::Unsupported { expr: f_x }
+    );
+    assert!(matches!(
+        Expr::string("x").compile(&[x]),
+        Err(CompileError::Unsupported { .. })
+    ));
+}
+
+#[test]
+fn derivative_polynomials() {
+    let x = Symbol::new("Global`x");
+    let (x_, y_) = (sym("Global`x"), sym("Global`y"));
+
+    assert_eq!(Expr::from(5).derivative(&x), Expr::from(0));
+    assert_eq!(y_.derivative(&x), Expr::from(0));
+    assert_eq!(x_.derivative(&x), Expr::from(1));
+
+    // 3 x^2 + 2 x + 7
+    let polynomial = call("System`Plus", vec![
+        call("System`Times", vec![3.into(), power(x_.clone(), 2.into())]),
+        call("System`Times", vec![2.into(), x_.clone()]),
+        7.into(),
+    ]);
+    assert_eq!(
+        polynomial.derivative(&x),
+        call("System`Plus", vec![2.into(), call("System`Times", vec![
+            6.into(),
+            x_.clone()
+        ])])
+    );
+
+    // Product rule: x x y
+    assert_eq!(
+        call("System`Times", vec![x_.clone(), x_.clone(), y_.clone()]).derivative(&x),
+        call("System`Times", vec![2.into(), x_.clone(), y_.clone()])
+    );
+}
+
+#[test]
+fn derivative_chain_and_quotient_rules() {
+    let x = Symbol::new("Global`x");
+    let x_ = sym("Global`x");
+    let sin = call("System`Sin", vec![x_.clone()]);
+    let cos = call("System`Cos", vec![x_.clone()]);
+
+    // Chain rule: Sin[x]^2 and Exp[Cos[x]]
+    assert_eq!(
+        power(sin.clone(), 2.into()).derivative(&x),
+        call("System`Times", vec![2.into(), cos.clone(), sin.clone()])
+    );
+    let exp_cos = call("System`Exp", vec![cos.clone()]);
+    assert_eq!(
+        exp_cos.derivative(&x),
+        call("System`Times", vec![(-1).into(), exp_cos, sin.clone()])
+    );
+
+    // Quotient rule: Sin[x] / x
+    assert_eq!(
+        call("System`Divide", vec![sin.clone(), x_.clone()]).derivative(&x),
+        call("System`Plus", vec![
+            call("System`Times", vec![cos, power(x_.clone(), (-1).into())]),
+            call("System`Times", vec![
+                (-1).into(),
+                sin,
+                power(x_.clone(), (-2).into())
+            ]),
+        ])
+    );
+}
+
+#[test]
+fn derivative_elementary_functions() {
+    let x = Symbol::new("Global`x");
+    let x_ = sym("Global`x");
+
+    assert_eq!(
+        call("System`Sqrt", vec![x_.clone()]).derivative(&x),
+        call("System`Times", vec![
+            rational(1, 2),
+            power(x_.clone(), rational(-1, 2))
+        ])
+    );
+    assert_eq!(
+        call("System`Log", vec![x_.clone()]).derivative(&x),
+        power(x_.clone(), (-1).into())
+    );
+    assert_eq!(
+        call("System`ArcTan", vec![x_.clone()]).derivative(&x),
+        power(
+            call("System`Plus", vec![1.into(), power(x_.clone(), 2.into())]),
+            (-1).into()
+        )
+    );
+
+    // Reciprocal hyperbolic functions
+    let apply = |name: &str| call(name, vec![x_.clone()]);
+    let (sech, csch) = (apply("System`Sech"), apply("System`Csch"));
+    let (tanh, coth) = (apply("System`Tanh"), apply("System`Coth"));
+    assert_eq!(
+        sech.derivative(&x),
+        times(vec![(-1).into(), sech.clone(), tanh])
+    );
+    assert_eq!(
+        csch.derivative(&x),
+        times(vec![(-1).into(), coth.clone(), csch.clone()])
+    );
+    assert_eq!(
+        coth.derivative(&x),
+        times(vec![(-1).into(), power(csch, 2.into())])
+    );
+
+    // Reciprocal inverse functions
+    let inverse_square = power(x_.clone(), (-2).into());
+    let minus_inverse_square = times(vec![(-1).into(), inverse_square.clone()]);
+    let root = power(plus(vec![1.into(), minus_inverse_square]), rational(-1, 2));
+    assert_eq!(
+        apply("System`ArcSec").derivative(&x),
+        times(vec![inverse_square.clone(), root.clone()])
+    );
+    assert_eq!(
+        apply("System`ArcCsc").derivative(&x),
+        times(vec![(-1).into(), inverse_square.clone(), root])
+    );
+    assert_eq!(
+        apply("System`ArcCoth").derivative(&x),
+        power(
+            plus(vec![1.into(), times(vec![(-1).into(), power(x_.clone(), 2.into())])]),
+            (-1).into()
+        )
+    );
+    let one_plus_x = plus(vec![1.into(), x_.clone()]);
+    let one_minus_x = plus(vec![1.into(), times(vec![(-1).into(), x_.clone()])]);
+    assert_eq!(
+        apply("System`ArcSech").derivative(&x),
+        times(vec![
+            (-1).into(),
+            power(x_.clone(), (-1).into()),
+            power(one_plus_x.clone(), (-1).into()),
+            power(
+                times(vec![one_minus_x, power(one_plus_x, (-1).into())]),
+                rational(-1, 2)
+            ),
+        ])
+    );
+    assert_eq!(
+        apply("System`ArcCsch").derivative(&x),
+        times(vec![
+            (-1).into(),
+            inverse_square.clone(),
+            power(plus(vec![1.into(), inverse_square]), rational(-1, 2)),
+        ])
+    );
+
+    // E^(2 x) and x^x
+    let e_2x = power(
+        sym("System`E"),
+        call("System`Times", vec![2.into(), x_.clone()]),
+    );
+    assert_eq!(
+        e_2x.derivative(&x),
+        call("System`Times", vec![2.into(), e_2x])
+    );
+    let x_x = power(x_.clone(), x_.clone());
+    assert_eq!(
+        x_x.derivative(&x),
+        call("System`Times", vec![
+            call("System`Plus", vec![1.into(), call("System`Log", vec![x_.clone()])]),
+            x_x
+        ])
+    );
+}
+
+#[test]
+fn derivative_unknown_functions() {
+    let x = Symbol::new("Global`x");
+    let (x_, y_) = (sym("Global`x"), sym("Global`y"));
+
+    // Unknown functions: f[x, x^2] and Derivative[1][f][x]
+    let derivative = |orders: Vec<Expr>, args: Vec<Expr>| {
+        let head = Expr::normal(call("System`Derivative", orders), vec![sym("Global`f")]);
+        Expr::normal(head, args)
+    };
+    let args = vec![x_.clone(), power(x_.clone(), 2.into())];
+    assert_eq!(
+        call("Global`f", args.clone()).derivative(&x),
+        call("System`Plus", vec![
+            derivative(vec![1.into(), 0.into()], args.clone()),
+            call("System`Times", vec![
+                2.into(),
+                x_.clone(),
+                derivative(vec![0.into(), 1.into()], args)
+            ]),
+        ])
+    );
+    assert_eq!(
+        derivative(vec![1.into()], vec![x_.clone()]).derivative(&x),
+        derivative(vec![2.into()], vec![x_.clone()])
+    );
+
+    // An order that would overflow is left as the derivative of an unknown function.
+    let max_order = Expr::normal(call("System`Derivative", vec![i64::MAX.into()]), vec![
+        sym("Global`f"),
+    ]);
+    let first_derivative = call("System`Derivative", vec![1.into()]);
+    assert_eq!(
+        Expr::normal(max_order.clone(), vec![x_.clone()]).derivative(&x),
+        Expr::normal(Expr::normal(first_derivative, vec![max_order]), vec![x_.clone()])
+    );
+
+    assert_eq!(
+        Expr::list(vec![x_.clone(), y_]).derivative(&x),
+        Expr::list(vec![1.into(), 0.into()])
+    );
+}
+
+#[test]
+fn polynomial_expand() {
+    let (x, y) = (Symbol::new("Global`x"), Symbol::new("Global`y"));
+    let (x_, y_) = (sym("Global`x"), sym("Global`y"));
+
+    // (x + 1)^3
+    let cube = power(plus(vec![x_.clone(), 1.into()]), 3.into());
+    assert_eq!(
+        cube.expand().unwrap(),
+        plus(vec![
+            1.into(),
+            power(x_.clone(), 3.into()),
+            times(vec![3.into(), x_.clone()]),
+            times(vec![3.into(), power(x_.clone(), 2.into())]),
+        ])
+    );
+    assert_eq!(cube.exponent(&x), Ok(Some(3)));
+    assert_eq!(cube.exponent(&y), Ok(Some(0)));
+    assert_eq!(cube.coefficient(&x, 2), Ok(3.into()));
+    assert_eq!(Expr::from(0).exponent(&x), Ok(None));
+
+    // (x - y) (x + y) / 2
+    let expr = call("System`Divide", vec![
+        times(vec![
+            call("System`Subtract", vec![x_.clone(), y_.clone()]),
+            plus(vec![x_.clone(), y_.clone()]),
+        ]),
+        2.into(),
+    ]);
+    let half = rational(1, 2);
+    let minus_half = rational(-1, 2);
+    assert_eq!(
+        expr.expand().unwrap(),
+        plus(vec![
+            times(vec![minus_half.clone(), power(y_.clone(), 2.into())]),
+            times(vec![half.clone(), power(x_.clone(), 2.into())]),
+        ])
+    );
+    assert_eq!(expr.coefficient(&x, 1), Ok(0.into()));
+    assert_eq!(expr.coefficient(&y, 2), Ok(minus_half));
+}
+
+#[test]
+fn polynomial_collect() {
+    let x = Symbol::new("Global`x");
+    let (x_, y_, a_) = (sym("Global`x"), sym("Global`y"), sym("Global`a"));
+
+    // a x + a^2 x + x y + 3 collected in x
+    let expr = plus(vec![
+        times(vec![a_.clone(), x_.clone()]),
+        times(vec![power(a_.clone(), 2.into()), x_.clone()]),
+        times(vec![x_.clone(), y_.clone()]),
+        3.into(),
+    ]);
+    assert_eq!(
+        expr.collect(std::slice::from_ref(&x)).unwrap(),
+        plus(vec![
+            3.into(),
+            times(vec![
+                x_.clone(),
+                plus(vec![a_.clone(), y_.clone(), power(a_.clone(), 2.into())])
+            ]),
+        ])
+    );
+    assert_eq!(
+        expr.coefficient(&x, 1),
+        Ok(plus(vec![a_.clone(), y_.clone(), power(a_, 2.into())]))
+    );
+}
+
+#[test]
+fn polynomial_arithmetic() {
+    use wolfram_expr::Polynomial;
+
+    let (x, y) = (Symbol::new("Global`x"), Symbol::new("Global`y"));
+    let (x_, y_) = (sym("Global`x"), sym("Global`y"));
+
+    let x_plus_1 = plus(vec![x_.clone(), 1.into()]);
+    let p = Polynomial::from_expr(&x_plus_1, std::slice::from_ref(&x)).unwrap();
+    let q = Polynomial::from_expr(&plus(vec![y_.clone(), (-1).into()]), &[y]).unwrap();
+    let product = p.checked_mul(&q).unwrap();
+    assert_eq!(product.term_count(), 4);
+    assert_eq!(product.total_degree(), Some(2));
+    assert_eq!(product.variables().len(), 2);
+    assert!(p.checked_sub(&p).unwrap().is_zero());
+    assert_eq!(
+        p.checked_pow(2).unwrap(),
+        p.checked_mul(&p).unwrap()
+    );
+    assert_eq!(
+        Polynomial::variable(x.clone()).checked_add(&Polynomial::from(1)).unwrap(),
+        p
+    );
+}
+
+#[test]
+fn polynomial_opaque_subexpressions() {
+    let (x, a) = (Symbol::new("Global`x"), Symbol::new("Global`a"));
+    let (x_, y_, a_) = (sym("Global`x"), sym("Global`y"), sym("Global`a"));
+    let sin_x = call("System`Sin", vec![x_.clone()]);
+
+    // (a + Sin[x])^2
+    let square = power(plus(vec![a_.clone(), sin_x.clone()]), 2.into());
+    assert_eq!(
+        square.expand(),
+        Ok(plus(vec![
+            power(a_.clone(), 2.into()),
+            power(sin_x.clone(), 2.into()),
+            times(vec![2.into(), a_.clone(), sin_x.clone()]),
+        ]))
+    );
+    assert_eq!(square.exponent(&x), Ok(Some(0)));
+    assert_eq!(square.exponent(&a), Ok(Some(2)));
+    assert_eq!(square.coefficient(&a, 1), Ok(times(vec![2.into(), sin_x.clone()])));
+    assert_eq!(
+        square.collect(std::slice::from_ref(&a)),
+        Ok(plus(vec![
+            power(a_.clone(), 2.into()),
+            power(sin_x.clone(), 2.into()),
+            times(vec![2.into(), a_.clone(), sin_x]),
+        ]))
+    );
+
+    // x^-1 and x / y are left alone, but 2^-1 is a number.
+    let inverse = power(x_.clone(), (-1).into());
+    assert_eq!(inverse.expand(), Ok(inverse.clone()));
+    assert_eq!(
+        call("System`Divide", vec![x_.clone(), y_.clone()]).expand(),
+        Ok(times(vec![x_.clone(), power(y_.clone(), (-1).into())]))
+    );
+    assert_eq!(
+        times(vec![x_.clone(), power(2.into(), (-1).into())]).expand(),
+        Ok(times(vec![rational(1, 2), x_.clone()]))
+    );
+
+    // (x + 1.5)^2 and x / 2.
+    assert_eq!(
+        power(plus(vec![x_.clone(), Expr::real(1.5)]), 2.into()).expand(),
+        Ok(plus(vec![
+            Expr::real(2.25),
+            power(x_.clone(), 2.into()),
+            times(vec![Expr::real(3.0), x_.clone()]),
+        ]))
+    );
+    assert_eq!(
+        call("System`Divide", vec![x_.clone(), Expr::real(2.0)]).expand(),
+        Ok(times(vec![Expr::real(0.5), x_]))
+    );
+}
+
+#[test]
+fn polynomial_errors() {
+    use wolfram_expr::{Polynomial, PolynomialError};
+
+    let x = Symbol::new("Global`x");
+    let (x_, y_) = (sym("Global`x"), sym("Global`y"));
+
+    let sin_x = call("System`Sin", vec![x_.clone()]);
+    assert_eq!(
+        Polynomial::from_expr(&sin_x, std::slice::from_ref(&x)),
+        Err(PolynomialError::NotPolynomial { expr: sin_x })
+    );
+    assert_eq!(
+        Polynomial::from_expr(&y_, std::slice::from_ref(&x)),
+        Err(PolynomialError::NotPolynomial { expr: y_.clone() })
+    );
+    let invalid_power = call("System`Power", vec![x_.clone()]);
+    assert_eq!(
+        plus(vec![y_, invalid_power.clone()]).expand(),
+        Err(PolynomialError::NotPolynomial {
+            expr: invalid_power
+        })
+    );
+    assert_eq!(
+        power(plus(vec![x_, 1.into()]), 100.into()).expand(),
+        Err(PolynomialError::Overflow)
+    );
+}
+
+#[test]
+fn simplify_identities() {
+    let (x, y, z) = (sym("Global`x"), sym("Global`y"), sym("Global`z"));
+
+    assert_eq!(plus(vec![x.clone(), 0.into()]).simplify(), x);
+    assert_eq!(times(vec![1.into(), y.clone()]).simplify(), y);
+    assert_eq!(power(z.clone(), 1.into()).simplify(), z);
+    assert_eq!(power(z.clone(), 0.into()).simplify(), Expr::from(1));
+    assert_eq!(times(vec![x.clone(), 0.into(), y.clone()]).simplify(), Expr::from(0));
+}
+
+#[test]
+fn simplify_like_terms_and_powers() {
+    let (x, y) = (sym("Global`x"), sym("Global`y"));
+
+    // Constant folding, flattening and like terms: 2 + Plus[y, Plus[3, x]] + y
+    assert_eq!(
+        plus(vec![
+            2.into(),
+            plus(vec![y.clone(), plus(vec![3.into(), x.clone()])]),
+            y.clone()
+        ])
+        .simplify(),
+        plus(vec![5.into(), x.clone(), times(vec![2.into(), y.clone()])])
+    );
+    assert_eq!(
+        call("System`Subtract", vec![x.clone(), x.clone()]).simplify(),
+        Expr::from(0)
+    );
+
+    // Power merging: x * x^2 * y / x and (x^2)^3
+    let expr = call("System`Divide", vec![
+        times(vec![x.clone(), power(x.clone(), 2.into()), y.clone()]),
+        x.clone(),
+    ]);
+    assert_eq!(
+        expr.simplify(),
+        times(vec![y.clone(), power(x.clone(), 2.into())])
+    );
+    assert_eq!(
+        power(power(x.clone(), 2.into()), 3.into()).simplify(),
+        power(x.clone(), 6.into())
+    );
+}
+
+#[test]
+fn simplify_other_heads() {
+    let (x, y) = (sym("Global`x"), sym("Global`y"));
+
+    // Other heads are left alone, but their elements are simplified unless held.
+    let f = call("Global`f", vec![y.clone(), plus(vec![x.clone(), 0.into()])]);
+    assert_eq!(f.simplify(), call("Global`f", vec![y.clone(), x.clone()]));
+    let held = call("System`Hold", vec![plus(vec![x.clone(), 0.into()])]);
+    assert_eq!(held.simplify(), held);
+}
+
+#[test]
+fn simplify_user_rules() {
+    use wolfram_expr::Simplifier;
+
+    let x = sym("Global`x");
+
+    // User rules: Log[E] -> 1, after which the built-in identities apply again.
+    let mut simplifier = Simplifier::new();
+    let log_e = call("System`Log", vec![sym("System`E")]);
+    let rule_lhs = log_e.clone();
+    simplifier.add_rule(move |expr| match *expr == rule_lhs {
+        true => Some(Expr::from(1)),
+        false => None,
+    });
+    assert_eq!(
+        simplifier.simplify(&plus(vec![log_e.clone(), 2.into(), x.clone()])),
+        plus(vec![3.into(), x.clone()])
+    );
+    assert_eq!(
+        simplifier.simplify(&times(vec![log_e, x.clone()])),
+        x.clone()
+    );
+
+    // Rules which never reach a fixed point stop at the iteration limit.
+    let mut simplifier = Simplifier::new();
+    simplifier.set_iteration_limit(10);
+    let (a, b) = (sym("Global`a"), sym("Global`b"));
+    let (a2, b2) = (a.clone(), b.clone());
+    simplifier.add_rule(move |expr| match *expr == a2 {
+        true => Some(b2.clone()),
+        false => None,
+    });
+    let (a3, b3) = (a.clone(), b);
+    simplifier.add_rule(move |expr| match *expr == b3 {
+        true => Some(a3.clone()),
+        false => None,
+    });
+    assert_eq!(simplifier.iteration_limit(), 10);
+    assert_eq!(simplifier.simplify(&a), a);
+}
+
+#[test]
+fn options() {
+    use wolfram_expr::{OptionName, Options, RuleKind};
+
+    let plot_range = Symbol::new("System`PlotRange");
+    let axes = Symbol::new("System`Axes");
+    let rule = Expr::rule(Symbol::new("Global`x"), Expr::from(1));
+
+    // Plot[x -> 1, f, PlotRange -> All, "Axes" :> False, PlotRange -> Full]
+    let elements = vec![
+        rule.clone(),
+        sym("Global`f"),
+        Expr::rule(plot_range.clone(), sym("System`All")),
+        Expr::rule_delayed(Expr::string("Axes"), sym("System`False")),
+        Expr::rule(plot_range.clone(), sym("System`Full")),
+    ];
+    let plot = Expr::function(Symbol::new("System`Plot"), elements.clone());
+    let options = Options::from_normal(plot.try_as_normal().unwrap());
+
+    assert_eq!(options.positional(), &[rule, sym("Global`f")]);
+    assert_eq!(options.len(), 3);
+    assert_eq!(options.get(&plot_range), Some(&sym("System`All")));
+    assert_eq!(options.get("PlotRange"), Some(&sym("System`All")));
+    assert_eq!(options.get(&axes), Some(&sym("System`False")));
+    assert_eq!(options.rule_kind(&axes), Some(RuleKind::RuleDelayed));
+    assert_eq!(
+        options.rules().map(|(_, kind, _)| kind).collect::<Vec<_>>(),
+        vec![RuleKind::Rule, RuleKind::RuleDelayed, RuleKind::Rule]
+    );
+    assert_eq!(options.get(Symbol::new("Global`PlotRange")), None);
+    assert_eq!(options.to_normal(Symbol::new("System`Plot")), plot);
+
+    // Defaults
+    let defaults = Options::from_rule_list(&Expr::list(vec![
+        Expr::rule(plot_range.clone(), sym("System`Automatic")),
+        Expr::rule(Symbol::new("System`Frame"), sym("System`False")),
+    ]))
+    .unwrap();
+    assert_eq!(
+        options.option_value(&defaults, "Frame"),
+        Some(&sym("System`False"))
+    );
+    assert_eq!(
+        options.option_value(&defaults, &plot_range),
+        Some(&sym("System`All"))
+    );
+    assert_eq!(Options::from_rule_list(&Expr::list(vec![Expr::from(1)])), None);
+
+    let merged = options.clone().with_defaults(&defaults);
+    assert_eq!(merged.len(), 4);
+    assert_eq!(merged.get("Frame"), Some(&sym("System`False")));
+
+    // FilterRules
+    let known = [OptionName::from(&plot_range), OptionName::from("Frame")];
+    assert_eq!(
+        merged.filter_rules(&known).rule_list(),
+        Expr::list(vec![
+            Expr::rule(plot_range.clone(), sym("System`All")),
+            Expr::rule(plot_range.clone(), sym("System`Full")),
+            Expr::rule(Symbol::new("System`Frame"), sym("System`False")),
+        ])
+    );
+    assert_eq!(
+        merged.filter_rules_except(&known).rule_list(),
+        Expr::list(vec![Expr::rule_delayed(
+            Expr::string("Axes"),
+            sym("System`False")
+        )])
+    );
+    assert_eq!(options.filter_known(&defaults).len(), 2);
+
+    // Modification
+    let mut options = options;
+    options.set("PlotRange", sym("System`Full"));
+    options.set(axes.clone(), sym("System`True"));
+    assert_eq!(
+        options.rule_list(),
+        Expr::list(vec![
+            Expr::rule(Expr::string("PlotRange"), sym("System`Full")),
+            Expr::rule(axes.clone(), sym("System`True")),
+        ])
+    );
+    assert_eq!(options.remove(&axes), Some(sym("System`True")));
+    assert!(!options.contains("Axes"));
+}
+
+#[test]
+fn association_queries() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use wolfram_expr::RuleKind;
+
+    let hash = |assoc: &Association| {
+        let mut hasher = DefaultHasher::new();
+        assoc.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    // <|"a" -> 1, "b" :> 2|>
+    let mut ab = Association::new();
+    ab.insert("a", Expr::from(1));
+    ab.insert_delayed("b", Expr::from(2));
+    let mut ba = Association::new();
+    ba.insert_delayed("b", Expr::from(2));
+    ba.insert("a", Expr::from(1));
+
+    // Equality is order-sensitive, like SameQ.
+    assert_eq!(ab, ab.clone());
+    assert_eq!(hash(&ab), hash(&ab.clone()));
+    assert_ne!(ab, ba);
+    assert_eq!(ab, ba.key_sort());
+    assert_eq!(hash(&ab), hash(&ba.key_sort()));
+    let mut not_delayed = Association::new();
+    not_delayed.insert("a", Expr::from(1));
+    not_delayed.insert("b", Expr::from(2));
+    assert_ne!(ab, not_delayed);
+
+    assert_eq!(
+        ab.rules().collect::<Vec<_>>(),
+        vec![
+            (&Expr::string("a"), RuleKind::Rule, &Expr::from(1)),
+            (&Expr::string("b"), RuleKind::RuleDelayed, &Expr::from(2)),
+        ]
+    );
+    assert_eq!(
+        ab.get_rule(&Expr::string("b")),
+        Some((RuleKind::RuleDelayed, &Expr::from(2)))
+    );
+    assert_eq!(ab.get_str("a"), Some(&Expr::from(1)));
+    assert_eq!(ab.get_str("c"), None);
+    assert_eq!(ab.get_or_missing(&Expr::string("a")), Expr::from(1));
+    assert_eq!(
+        ab.get_or_missing(&Expr::string("c")),
+        Expr::function("System`Missing", vec![
+            Expr::string("KeyAbsent"),
+            Expr::string("c")
+        ])
+    );
+
+    // <|"x" -> <|"a" -> 1, "b" :> 2|>|>
+    let mut nested = Association::new();
+    nested.insert_rule("x", RuleKind::Rule, ab.clone());
+    assert_eq!(nested.lookup_path(&["x", "b"]), Some(&Expr::from(2)));
+    assert_eq!(nested.lookup_path(&["x"]), Some(&Expr::from(ab.clone())));
+    assert_eq!(nested.lookup_path(&["x", "c"]), None);
+    assert_eq!(nested.lookup_path(&["x", "a", "b"]), None);
+    assert_eq!(nested.lookup_path(&[]), None);
+
+    // KeyDrop and KeyTake
+    assert_eq!(ab.key_drop(vec!["a", "c"]).to_string(), r#"<|"b" :> 2|>"#);
+    assert_eq!(ab.key_take(vec!["b", "c", "a"]), ba);
+
+    // Merge[{<|"a" -> 1, "b" :> 2|>, <|"a" -> 3|>}, List]
+    let mut a3 = Association::new();
+    a3.insert("a", Expr::from(3));
+    assert_eq!(
+        Association::merge(&[ab, a3], |values| Expr::list(values.to_vec())).to_string(),
+        r#"<|"a" -> System`List[1, 3], "b" -> System`List[2]|>"#
+    );
+}
+
+#[test]
+fn association_exprs() {
+    use std::convert::TryFrom;
+    use wolfram_expr::{ExprKind, PartSpec, RuleKind};
+
+    let x = Symbol::new("Global`x");
+    let assoc = |rules: Vec<(&str, RuleKind, Expr)>| {
+        let mut assoc = Association::new();
+        for (key, kind, value) in rules {
+            assoc.insert_rule(key, kind, value);
+        }
+        Expr::from(assoc)
+    };
+
+    // <|"a" -> 1, "b" :> x^2|>
+    let x_squared = call("System`Power", vec![Expr::from(x.clone()), Expr::from(2)]);
+    let expr = assoc(vec![
+        ("a", RuleKind::Rule, Expr::from(1)),
+        ("b", RuleKind::RuleDelayed, x_squared.clone()),
+    ]);
+    assert!(matches!(expr.kind(), ExprKind::Association(_)));
+    assert_eq!(expr.try_as_normal(), None);
+    assert_eq!(expr.tag(), Some(Symbol::new("System`Association")));
+    assert_eq!(
+        expr.try_as_association().unwrap().get_str("b"),
+        Some(&x_squared)
+    );
+
+    // Associations are distinct from the unevaluated Association[...] form, and come
+    // after normal expressions in canonical order.
+    let normal = call("System`Association", vec![
+        Expr::rule("a", Expr::from(1)),
+        Expr::rule_delayed("b", x_squared.clone()),
+    ]);
+    assert_ne!(expr, normal);
+    assert_eq!(Expr::from(Association::try_from(&normal).unwrap()), expr);
+    assert!(normal < expr);
+    assert!(assoc(vec![("b", RuleKind::Rule, Expr::from(1))]) < expr);
+    assert!(
+        assoc(vec![
+            ("a", RuleKind::Rule, Expr::from(1)),
+            ("b", RuleKind::Rule, x_squared.clone()),
+        ]) < expr
+    );
+
+    // Parts are values.
+    assert_eq!(expr.part(&[PartSpec::Index(-1)]), Ok(x_squared.clone()));
+    assert_eq!(
+        expr.part(&[PartSpec::Index(0)]),
+        Ok(Expr::symbol(Symbol::new("System`Association")))
+    );
+    assert_eq!(
+        expr.part(&[PartSpec::span(2, 2), PartSpec::Index(2)]),
+        Ok(assoc(vec![("b", RuleKind::RuleDelayed, Expr::from(2))]))
+    );
+    assert!(expr.part(&[PartSpec::Index(3)]).is_err());
+
+    // Substitution and differentiation reach into values.
+    let function = call("System`Function", vec![Expr::from(x.clone()), expr.clone()]);
+    assert_eq!(
+        function.apply_function(&[Expr::from(3)]),
+        Ok(assoc(vec![
+            ("a", RuleKind::Rule, Expr::from(1)),
+            (
+                "b",
+                RuleKind::RuleDelayed,
+                call("System`Power", vec![Expr::from(3), Expr::from(2)])
+            ),
+        ]))
+    );
+    assert_eq!(
+        expr.derivative(&x),
+        assoc(vec![
+            ("a", RuleKind::Rule, Expr::from(0)),
+            (
+                "b",
+                RuleKind::RuleDelayed,
+                call("System`Times", vec![Expr::from(2), Expr::from(x)])
+            ),
+        ])
+    );
+}
+
+#[test]
+fn views() {
+    use std::convert::TryFrom;
+    use wolfram_expr::{
+        FailureRef, FunctionRef, ListRef, MissingRef, RuleKind, RuleRef,
+    };
+
+    // Lists
+    let list = Expr::list(vec![Expr::from(1), Expr::from(2)]);
+    let view = ListRef::try_from(&list).unwrap();
+    assert_eq!(view.len(), 2);
+    assert_eq!(view.into_iter().collect::<Vec<_>>(), [&Expr::from(1), &Expr::from(2)]);
+
+    let error = ListRef::try_from(&Expr::from(1)).unwrap_err();
+    assert_eq!(error.expected(), "List[...]");
+    assert_eq!(error.expr(), &Expr::from(1));
+
+    // Rules
+    let rule = Expr::rule("a", Expr::from(1));
+    let view = RuleRef::try_from(&rule).unwrap();
+    assert_eq!(
+        (view.lhs(), view.rhs(), view.kind()),
+        (&Expr::string("a"), &Expr::from(1), RuleKind::Rule)
+    );
+    let rule = Expr::rule_delayed("a", Expr::from(1));
+    assert!(RuleRef::try_from(&rule).unwrap().is_delayed());
+    let invalid = call("System`Rule", vec![Expr::from(1)]);
+    assert!(RuleRef::try_from(&invalid).is_err());
+
+    // Functions
+    let slot = call("System`Slot", vec![Expr::from(1)]);
+    let function = call("System`Function", vec![slot.clone()]);
+    let view = FunctionRef::try_from(&function).unwrap();
+    assert_eq!((view.params(), view.body()), (None, &slot));
+    assert_eq!(view.param_symbols(), None);
+
+    let params = Expr::list(vec![sym("Global`x"), sym("Global`y")]);
+    let function = call("System`Function", vec![
+        params.clone(),
+        sym("Global`x"),
+        sym("System`HoldAll"),
+    ]);
+    let view = FunctionRef::try_from(&function).unwrap();
+    assert_eq!(view.params(), Some(&params));
+    assert_eq!(
+        view.param_symbols(),
+        Some(vec![&Symbol::new("Global`x"), &Symbol::new("Global`y")])
+    );
+    assert_eq!(view.attributes(), Some(&sym("System`HoldAll")));
+
+    let function = call("System`Function", vec![sym("System`Null"), slot]);
+    assert_eq!(FunctionRef::try_from(&function).unwrap().param_symbols(), None);
+    assert!(FunctionRef::try_from(&call("System`Function", vec![])).is_err());
+
+    // Failures, with an atomic association and with named parameters
+    let mut assoc = Association::new();
+    assoc.insert("MessageTemplate", Expr::string("`1` is not `2`; `3`."));
+    assoc.insert("MessageParameters", Expr::list(vec![Expr::from(5), Expr::string("x")]));
+    let failure = call("System`Failure", vec![Expr::string("Tag"), Expr::from(assoc)]);
+    let view = FailureRef::try_from(&failure).unwrap();
+    assert_eq!(view.tag_str(), Some("Tag"));
+    assert_eq!(view.message(), Some("5 is not x; `3`.".to_owned()));
+
+    let details = call("System`Association", vec![
+        Expr::rule("MessageTemplate", Expr::string("Missing `name`.")),
+        Expr::rule(
+            "MessageParameters",
+            call("System`Association", vec![Expr::rule("name", Expr::string("f"))]),
+        ),
+    ]);
+    let failure = call("System`Failure", vec![Expr::string("Tag"), details]);
+    let view = FailureRef::try_from(&failure).unwrap();
+    assert_eq!(view.message_template(), Some("Missing `name`."));
+    assert_eq!(view.message(), Some("Missing f.".to_owned()));
+    assert_eq!(view.get("Other"), None);
+
+    // An unterminated placeholder is kept as written.
+    let failure = call("System`Failure", vec![
+        Expr::string("Tag"),
+        call("System`Association", vec![Expr::rule(
+            "MessageTemplate",
+            Expr::string("abc `x"),
+        )]),
+    ]);
+    let view = FailureRef::try_from(&failure).unwrap();
+    assert_eq!(view.message(), Some("abc `x".to_owned()));
+
+    let invalid = call("System`Failure", vec![Expr::string("Tag"), Expr::from(1)]);
+    assert!(FailureRef::try_from(&invalid).is_err());
+
+    // Missing
+    let missing = call("System`Missing", vec![Expr::string("KeyAbsent"), Expr::from(1)]);
+    let view = MissingRef::try_from(&missing).unwrap();
+    assert_eq!(view.reason_str(), Some("KeyAbsent"));
+    assert_eq!(view.details(), &[Expr::from(1)]);
+    let missing = call("System`Missing", vec![]);
+    let view = MissingRef::try_from(&missing).unwrap();
+    assert_eq!((view.reason(), view.details()), (None, &[][..]));
+}
+
+#[test]
+fn system_symbols() {
+    use wolfram_expr::{system, SystemSymbol};
+
+    // Every cached name is a valid symbol, and can be looked up by name.
+    for symbol in SystemSymbol::all() {
+        assert_eq!(Symbol::try_new(symbol.as_str()).as_ref(), Some(symbol.symbol()));
+        assert_eq!(SystemSymbol::lookup(symbol.as_str()), Some(symbol));
+    }
+    assert!(SystemSymbol::all().count() > 300);
+
+    assert_eq!(SystemSymbol::lookup("System`Plus"), Some(system::Plus));
+    assert_eq!(SystemSymbol::lookup("Global`Plus"), None);
+    assert_eq!(SystemSymbol::lookup("System`NotASymbol"), None);
+
+    assert_eq!(system::Rule.to_string(), "System`Rule");
+    assert_eq!(Symbol::new("System`List"), system::List);
+    assert_eq!(Expr::from(system::Null), Expr::null());
+    assert_eq!(Expr::symbol(system::List), Expr::from(system::List));
+    assert_eq!(Expr::from(true), Expr::from(system::True));
+    assert_eq!(
+        Expr::rule("a", Expr::from(1)),
+        Expr::normal(system::Rule.into(), vec![Expr::string("a"), Expr::from(1)])
+    );
+    assert_eq!(
+        Expr::list(vec![]).normal_head(),
+        Some(Expr::symbol(Symbol::new("System`List")))
+    );
+}
+
+#[test]
+fn symbol_interning() {
+    use std::collections::{BTreeSet, HashSet};
+    use std::thread;
+
+    let a = Symbol::new("Global`interned");
+    let b = Symbol::try_new("Global`interned").unwrap();
+    assert_eq!(a, b);
+    assert_ne!(a, Symbol::new("Global`other"));
+
+    let set: HashSet<Symbol> = vec![a.clone(), b, Symbol::new("Global`other")]
+        .into_iter()
+        .collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&Symbol::new("Global`interned")));
+
+    // Symbols are ordered by name, not by address.
+    let sorted: Vec<Symbol> = vec![Symbol::new("Global`b"), Symbol::new("Global`a")]
+        .into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    assert_eq!(sorted, vec![Symbol::new("Global`a"), Symbol::new("Global`b")]);
+
+    // A symbol whose previous instances were all dropped is recreated.
+    drop(set);
+    drop(a);
+    for i in 0..2000 {
+        drop(Symbol::new(&format!("Global`temporary{}", i)));
+    }
+    assert_eq!(Symbol::new("Global`interned").as_str(), "Global`interned");
+
+    // Symbols created on different threads are equal.
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            thread::spawn(|| {
+                (0..100)
+                    .map(|i| Symbol::new(&format!("Global`shared{}", i)))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let results: Vec<Vec<Symbol>> =
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    for symbols in &results[1..] {
+        assert_eq!(symbols, &results[0]);
+    }
+}
+
+#[test]
+fn templates() {
+    use wolfram_expr::{ExprTemplate, TemplateError};
+
+    let template = ExprTemplate::parse("Plot[`1`, {x, 0, `2`}]").unwrap();
+    let expr = template
+        .apply(&[Expr::string("x] ; Quit["), Expr::real(2.5)])
+        .unwrap();
+    assert_eq!(
+        expr.to_string(),
+        "System`Plot[\"x] ; Quit[\", System`List[Global`x, 0, 2.5]]"
+    );
+
+    // Inserted symbols and expressions are not re-parsed or evaluated.
+    let expr = template
+        .apply(&[Expr::from(Symbol::new("Global`y")), Expr::function(
+            Symbol::new("System`Plus"),
+            vec![Expr::from(1), Expr::from(1)],
+        )])
+        .unwrap();
+    assert_eq!(
+        expr.to_string(),
+        "System`Plot[Global`y, System`List[Global`x, 0, System`Plus[1, 1]]]"
+    );
+
+    // Named slots and `#` slots; slots in nested pure functions are left alone.
+    let template: ExprTemplate = "Map[#1 + `offset` &, #list] // f".parse().unwrap();
+    let mut values = Association::new();
+    values.insert("offset", Expr::from(10));
+    values.insert("list", Expr::list(vec![Expr::from(1)]));
+    assert_eq!(
+        template.apply_named(&values).unwrap().to_string(),
+        "Global`f[System`Map[System`Function[System`Plus[System`Slot[1], 10]], \
+         System`List[1]]]"
+    );
+
+    assert_eq!(
+        template.apply_named(&values.key_drop(vec!["offset"])),
+        Err(TemplateError::MissingSlot {
+            slot: Expr::function(Symbol::new("System`TemplateSlot"), vec![Expr::string(
+                "offset"
+            )]),
+        })
+    );
+    assert_eq!(
+        ExprTemplate::parse("f[`1`, `3`]")
+            .unwrap()
+            .apply(&[Expr::from(1)])
+            .unwrap_err()
+            .to_string(),
+        "No value was given for the template slot System`TemplateSlot[3]."
+    );
+
+    // Operators, patterns, associations and numbers.
+    assert_eq!(
+        ExprTemplate::parse(r#"g[x_Integer] := -x^2 + 1.5*^-1 (* c *); <|"a" -> 2 y|>"#)
+            .unwrap()
+            .to_string(),
+        "System`CompoundExpression[System`SetDelayed[Global`g[System`Pattern[Global`x, \
+         System`Blank[System`Integer]]], System`Plus[System`Times[-1, \
+         System`Power[Global`x, 2]], 0.15]], <|\"a\" -> System`Times[2, Global`y]|>]"
+    );
+    assert_eq!(
+        ExprTemplate::parse("x[[1]] === f @ y[2]").unwrap().to_string(),
+        "System`SameQ[System`Part[Global`x, 1], Global`f[Global`y[2]]]"
+    );
+    assert_eq!(
+        ExprTemplate::parse("f /@ x").unwrap().to_string(),
+        "System`Map[Global`f, Global`x]"
+    );
+
+    // Repeated prefix minus.
+    assert_eq!(
+        ExprTemplate::parse("x - -1").unwrap().to_string(),
+        "System`Plus[Global`x, 1]"
+    );
+    assert_eq!(ExprTemplate::parse("- -x").unwrap().to_string(), "Global`x");
+    assert_eq!(
+        ExprTemplate::parse("x - - -y z").unwrap().to_string(),
+        "System`Plus[Global`x, System`Times[-1, Global`y, Global`z]]"
+    );
+
+    // The smallest machine integer, whose magnitude is only in range when negated.
+    let min = Expr::from(i64::MIN);
+    let parse = |source: &str| ExprTemplate::parse(source).map(|t| t.expr().clone());
+    assert_eq!(parse("-9223372036854775808"), Ok(min.clone()));
+    assert_eq!(
+        parse("{x - 9223372036854775808, -9223372036854775808 y}"),
+        Ok(Expr::list(vec![
+            call("System`Plus", vec![sym("Global`x"), min.clone()]),
+            call("System`Times", vec![min, sym("Global`y")]),
+        ]))
+    );
+    let out_of_range = [
+        "9223372036854775808",
+        "- -9223372036854775808",
+        "-9223372036854775809",
+        "-9223372036854775808^2",
+    ];
+    for source in out_of_range.iter() {
+        assert!(matches!(
+            parse(source),
+            Err(TemplateError::Syntax { message, .. })
+                if message == "integer is out of range"
+        ));
+    }
+
+    // Syntax errors report the offset of the problem.
+    assert_eq!(
+        ExprTemplate::parse("f[x, y"),
+        Err(TemplateError::Syntax {
+            offset: 6,
+            message: "expected `,` or `]`".to_owned(),
+        })
+    );
+    assert!(matches!(
+        ExprTemplate::parse("f[\"unterminated]"),
+        Err(TemplateError::Syntax { offset: 2, .. })
+    ));
+    assert!(matches!(
+        ExprTemplate::parse("x < y > z"),
+        Err(TemplateError::Syntax { offset: 6, .. })
+    ));
+}
+
+#[test]
+fn string_escaping() {
+    use wolfram_expr::{ExprTemplate, TemplateError};
+
+    let cases = [
+        ("plain", r#""plain""#),
+        ("say \"hi\"", r#""say \"hi\"""#),
+        ("a\\b", r#""a\\b""#),
+        ("line\nbreak\ttab\r", r#""line\nbreak\ttab\r""#),
+        ("it's", r#""it's""#),
+        ("\u{7}\u{7f}", r#""\:0007\:007f""#),
+        ("α + β ≤ ∞", r#""\[Alpha] + \[Beta] \[LessEqual] \[Infinity]""#),
+        ("héllo", r#""h\:00e9llo""#),
+        ("\u{1f600}", r#""\|01f600""#),
+    ];
+
+    for (string, expected) in cases.iter() {
+        let expr = Expr::string(*string);
+        assert_eq!(expr.to_string(), *expected);
+
+        // The displayed form parses back to the same string.
+        assert_eq!(ExprTemplate::parse(expected).unwrap().expr(), &expr);
+    }
+
+    // Other escapes accepted by the parser.
+    assert_eq!(
+        ExprTemplate::parse(r#""\.41\:00e9\[CapitalOmega]\b\f\q""#)
+            .unwrap()
+            .expr(),
+        &Expr::string("Aé\u{3a9}\u{8}\u{c}\\q")
+    );
+
+    assert_eq!(
+        ExprTemplate::parse(r#"f["ok", "\:12"]"#),
+        Err(TemplateError::Syntax {
+            offset: 9,
+            message: "invalid hexadecimal character escape".to_owned(),
+        })
+    );
+    assert!(matches!(
+        ExprTemplate::parse(r#""\[NotACharacter]""#),
+        Err(TemplateError::Syntax { offset: 1, .. })
+    ));
+    assert!(matches!(
+        ExprTemplate::parse(r#""\|110000""#),
+        Err(TemplateError::Syntax { offset: 1, .. })
+    ));
+}
+
+#[test]
+fn real_formatting() {
+    use wolfram_expr::{ExprTemplate, Number, RealFormat};
+
+    let cases = [
+        (0.0, "0."),
+        (1.0, "1."),
+        (-2.5, "-2.5"),
+        (1500.0, "1500."),
+        (123456.75, "123456.75"),
+        (1e6, "1.*^6"),
+        (0.1, "0.1"),
+        (0.00001, "0.00001"),
+        (1e-7, "1.*^-7"),
+        (-1.25e-300, "-1.25*^-300"),
+        (1e300, "1.*^300"),
+        (std::f64::consts::PI, "3.141592653589793"),
+        (f64::MAX, "1.7976931348623157*^308"),
+        (f64::INFINITY, "System`DirectedInfinity[1]"),
+    ];
+
+    for (real, expected) in cases.iter() {
+        // `Number` and `Expr` are displayed the same way.
+        assert_eq!(Number::real(*real).to_string(), *expected);
+        assert_eq!(Expr::real(*real).to_string(), *expected);
+    }
+
+    let full_form = RealFormat::full_form();
+    assert_eq!(full_form.display(2.0).to_string(), "2.`");
+    assert_eq!(full_form.display(1e-7).to_string(), "1.`*^-7");
+    assert_eq!(full_form.number_marks(false).display(2.0).to_string(), "2.");
+    assert_eq!(RealFormat::default(), RealFormat::input_form());
+    assert_eq!(full_form.display(f64::NAN).to_string(), "System`Indeterminate");
+    assert_eq!(
+        full_form.display(f64::NEG_INFINITY).to_string(),
+        "System`DirectedInfinity[-1]"
+    );
+
+    // The displayed forms read back as the same value.
+    for &real in &[0.1 + 0.2, 1.0 / 3.0, 6.02214076e23, 5e-324, -0.0001234, 42.0] {
+        for format in &[RealFormat::input_form(), full_form] {
+            let source = format.display(real).to_string();
+            let template = ExprTemplate::parse(&format!("{{{}}}", source)).unwrap();
+            assert_eq!(template.expr(), &Expr::list(vec![Expr::real(real)]));
+        }
+    }
+}
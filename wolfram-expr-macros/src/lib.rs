@@ -0,0 +1,302 @@
+//! Compile-time validated symbol and expression macros for [`wolfram_expr`].
+//!
+//! * [`sym!`] constructs a [`Symbol`][wolfram_expr::Symbol] from a string literal,
+//!   which is checked to be a valid absolute symbol when the program is compiled.
+//! * [`wl!`] constructs an [`Expr`][wolfram_expr::Expr] from Wolfram Language syntax,
+//!   which is parsed when the program is compiled.
+//!
+//! # Example
+//!
+//! ```
+//! use wolfram_expr::{Expr, Symbol};
+//! use wolfram_expr_macros::{sym, wl};
+//!
+//! let y = 5;
+//! let expr = wl! { f[x, {1, 2}, "s" -> #y] };
+//!
+//! assert_eq!(
+//!     expr.to_string(),
+//!     r#"Global`f[Global`x, System`List[1, 2], System`Rule["s", 5]]"#
+//! );
+//! assert_eq!(sym!("System`Plus"), Symbol::new("System`Plus"));
+//! ```
+
+mod parse;
+
+use std::convert::TryFrom;
+
+use proc_macro::{
+    Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree,
+};
+
+use wolfram_expr::symbol::SymbolRef;
+use wolfram_expr::SystemSymbol;
+
+use crate::parse::Node;
+
+/// Construct a [`Symbol`][wolfram_expr::Symbol] from a string literal containing an
+/// absolute symbol, e.g. ``sym!("System`Plus")``.
+///
+/// The literal is validated at compile time using the same grammar as
+/// [`Symbol::new()`][wolfram_expr::Symbol::new], so unlike `Symbol::new()`, `sym!` can
+/// never panic. Symbols in the [`wolfram_expr::system`] module are taken from its cache.
+///
+/// # Example
+///
+/// ```
+/// use wolfram_expr::Symbol;
+/// use wolfram_expr_macros::sym;
+///
+/// assert_eq!(sym!("MyPackage`Utils`foo").context().as_str(), "MyPackage`Utils`");
+/// ```
+///
+/// Relative symbols and malformed symbols are rejected:
+///
+/// ```compile_fail
+/// use wolfram_expr_macros::sym;
+///
+/// let plus = sym!("Plus");
+/// ```
+///
+/// ```compile_fail
+/// use wolfram_expr_macros::sym;
+///
+/// let invalid = sym!("System`1x");
+/// ```
+#[proc_macro]
+pub fn sym(input: TokenStream) -> TokenStream {
+    let mut tokens = input.into_iter();
+
+    let literal = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(literal)), None) => literal,
+        (Some(token), _) => return error(token.span(), "expected a string literal"),
+        (None, _) => return error(Span::call_site(), "expected a string literal"),
+    };
+
+    let name = match parse::string_value(&literal) {
+        Some(name) => name,
+        None => return error(literal.span(), "expected a string literal"),
+    };
+
+    if SymbolRef::try_new(&name).is_none() {
+        return error(
+            literal.span(),
+            &format!("invalid symbol `{}`: expected an absolute symbol", name),
+        );
+    }
+
+    match SystemSymbol::lookup(&name) {
+        Some(symbol) => code(&format!(
+            "::wolfram_expr::Symbol::from(::wolfram_expr::system::{})",
+            system_name(symbol)
+        )),
+        None => {
+            let mut tokens = code("::wolfram_expr::Symbol::new");
+            let literal = TokenTree::Literal(literal).into();
+            tokens.extend(group(Delimiter::Parenthesis, literal));
+            tokens
+        },
+    }
+}
+
+/// Construct an [`Expr`][wolfram_expr::Expr] from Wolfram Language syntax, e.g.
+/// `wl! { f[x, {1, 2}, "s" -> #y] }`.
+///
+/// The input is parsed at compile time, and expands to calls to the `Expr`
+/// constructors, so syntax errors are reported by the compiler.
+///
+/// # Syntax
+///
+/// The input must consist of valid Rust tokens, so the following differ from the
+/// Wolfram Language:
+///
+/// * Contexts are separated by `::` instead of `` ` ``: `MyPackage::foo` is
+///   `` MyPackage`foo ``. A name without a context, like `Plus` or `f`, refers to the
+///   `` System` `` symbol if it is in the [`wolfram_expr::system`] module, and to a
+///   `` Global` `` symbol otherwise.
+/// * `#y` and `#(expr)` interpolate a Rust value, which is converted with
+///   [`Expr::from()`][wolfram_expr::Expr]. There is no syntax for `Slot`; use
+///   `Slot[1]` instead.
+/// * String literals use Rust escape sequences.
+/// * `//` starts a Rust comment, so `x // f` and `x //. rules` can't be written; use
+///   `f[x]` and `ReplaceRepeated[x, rules]` instead.
+///
+/// The operators are parsed with the same grammar as
+/// [`ExprTemplate::parse()`][wolfram_expr::ExprTemplate::parse]. The following
+/// syntax is supported, listed from the highest to the lowest precedence:
+///
+/// | Syntax                                | Expression
+/// |---------------------------------------|-----------------------------------
+/// | `f[x, y]`, `expr[[i, j]]`             | `f[x, y]`, `Part[expr, i, j]`
+/// | `{x, y}`, `<\|a -> 1\|>`, `(x)`       | lists, associations, grouping
+/// | `x_`, `x__h`, `___`                   | `Pattern` and `Blank` patterns
+/// | `f @ x`, `f @@ x`, `f /@ x`           | `f[x]`, `Apply[f, x]`, `Map[f, x]`
+/// | `x ^ y`                               | `Power[x, y]`
+/// | `x * y`, `x y`, `x / y`, `-x`         | `Times[x, y]`, `Times[x, Power[y, -1]]`, ...
+/// | `x + y`, `x - y`                      | `Plus[x, y]`, `Plus[x, Times[-1, y]]`
+/// | `x == y`, `!=`, `===`, `<`, ...       | `Equal[x, y]`, `Unequal`, `SameQ`, ...
+/// | `!x`, `x && y`, `x \|\| y`            | `Not[x]`, `And[x, y]`, `Or[x, y]`
+/// | `x -> y`, `x :> y`                    | `Rule[x, y]`, `RuleDelayed[x, y]`
+/// | `x /. rules`                          | `ReplaceAll[x, rules]`
+/// | `body &`                              | `Function[body]`
+/// | `x = y`, `x := y`                     | `Set[x, y]`, `SetDelayed[x, y]`
+/// | `x; y`                                | `CompoundExpression[x, y]`
+///
+/// # Example
+///
+/// ```
+/// use wolfram_expr::{Expr, Symbol};
+/// use wolfram_expr_macros::wl;
+///
+/// let n = Expr::from(3);
+/// let expr = wl! { g[x_] := x ^ 2 + #(n.clone()) };
+///
+/// assert_eq!(
+///     expr.to_string(),
+///     "System`SetDelayed[Global`g[System`Pattern[Global`x, System`Blank[]]], \
+///      System`Plus[System`Power[Global`x, 2], 3]]"
+/// );
+/// ```
+#[proc_macro]
+pub fn wl(input: TokenStream) -> TokenStream {
+    match parse::parse(input).and_then(|node| expand(&node)) {
+        Ok(tokens) => tokens,
+        Err(parse::Error { span, message }) => error(span, &message),
+    }
+}
+
+//======================================
+// Code generation
+//======================================
+
+fn expand(node: &Node) -> Result<TokenStream, parse::Error> {
+    let tokens = match node {
+        Node::Integer(value, span) => match i64::try_from(*value) {
+            Ok(value) => code(&format!("::wolfram_expr::Expr::from({}i64)", value)),
+            Err(_) => {
+                return Err(parse::Error::new(
+                    *span,
+                    "integer literal does not fit in an `i64`",
+                ))
+            },
+        },
+        Node::Real(value) => code(&format!("::wolfram_expr::Expr::real({:?}f64)", value)),
+        Node::String(literal) => {
+            let mut tokens = code("::wolfram_expr::Expr::string");
+            tokens.extend(group(
+                Delimiter::Parenthesis,
+                TokenTree::Literal(literal.clone()).into(),
+            ));
+            tokens
+        },
+        Node::Symbol(name) => match SystemSymbol::lookup(name) {
+            Some(symbol) => code(&format!(
+                "::wolfram_expr::Expr::from(::wolfram_expr::system::{})",
+                system_name(symbol)
+            )),
+            None => {
+                let mut tokens = code("::wolfram_expr::Expr::symbol");
+                let mut symbol = code("::wolfram_expr::Symbol::new");
+                symbol.extend(group(
+                    Delimiter::Parenthesis,
+                    TokenTree::Literal(Literal::string(name)).into(),
+                ));
+                tokens.extend(group(Delimiter::Parenthesis, symbol));
+                tokens
+            },
+        },
+        Node::Interpolated(value) => {
+            let mut tokens = code("::wolfram_expr::Expr::from");
+            tokens.extend(group(Delimiter::Parenthesis, value.clone()));
+            tokens
+        },
+        Node::Normal(head, elements) => {
+            let mut elements_vec = code("::std::vec!");
+            elements_vec.extend(group(Delimiter::Bracket, comma_separated(elements)?));
+
+            let mut args = expand(head)?;
+            args.extend(code(","));
+            args.extend(elements_vec);
+
+            let mut tokens = code("::wolfram_expr::Expr::normal");
+            tokens.extend(group(Delimiter::Parenthesis, args));
+            tokens
+        },
+        Node::Association(rules) => {
+            // Use a hygienic name, so that interpolated values can refer to a variable
+            // named `assoc`.
+            let assoc = || -> TokenStream {
+                TokenTree::Ident(Ident::new("assoc", Span::mixed_site())).into()
+            };
+
+            let mut block = code("let mut");
+            block.extend(assoc());
+            block.extend(code("= ::wolfram_expr::Association::new();"));
+
+            for (key, delayed, value) in rules {
+                let kind = match delayed {
+                    true => "RuleDelayed",
+                    false => "Rule",
+                };
+
+                let mut args = expand(key)?;
+                args.extend(code(&format!(", ::wolfram_expr::RuleKind::{}, ", kind)));
+                args.extend(expand(value)?);
+
+                block.extend(assoc());
+                block.extend(code(".insert_rule"));
+                block.extend(group(Delimiter::Parenthesis, args));
+                block.extend(code(";"));
+            }
+
+            block.extend(code("::wolfram_expr::Expr::from"));
+            block.extend(group(Delimiter::Parenthesis, assoc()));
+
+            group(Delimiter::Brace, block)
+        },
+    };
+
+    Ok(tokens)
+}
+
+fn comma_separated(nodes: &[Node]) -> Result<TokenStream, parse::Error> {
+    let mut tokens = TokenStream::new();
+    for node in nodes {
+        tokens.extend(expand(node)?);
+        tokens.extend(code(","));
+    }
+    Ok(tokens)
+}
+
+/// The name of the constant for `symbol` in the `wolfram_expr::system` module.
+fn system_name(symbol: SystemSymbol) -> &'static str {
+    symbol.symbol().symbol_name().as_str()
+}
+
+fn code(source: &str) -> TokenStream {
+    source.parse().expect("invalid generated code")
+}
+
+fn group(delimiter: Delimiter, tokens: TokenStream) -> TokenStream {
+    TokenTree::Group(Group::new(delimiter, tokens)).into()
+}
+
+/// Expand to `compile_error!("message")`, reported at `span`.
+fn error(span: Span, message: &str) -> TokenStream {
+    let mut literal = Literal::string(message);
+    literal.set_span(span);
+
+    let mut body = Group::new(Delimiter::Brace, TokenTree::Literal(literal).into());
+    body.set_span(span);
+
+    let mut bang = Punct::new('!', Spacing::Alone);
+    bang.set_span(span);
+
+    vec![
+        TokenTree::Ident(Ident::new("compile_error", span)),
+        TokenTree::Punct(bang),
+        TokenTree::Group(body),
+    ]
+    .into_iter()
+    .collect()
+}
@@ -0,0 +1,503 @@
+//! Parsing of Wolfram Language syntax from Rust tokens, for [`wl!`][crate::wl!].
+
+use proc_macro::{Delimiter, Literal, Span, TokenStream, TokenTree};
+
+use wolfram_expr::grammar::{self, Syntax};
+use wolfram_expr::symbol::SymbolRef;
+use wolfram_expr::SystemSymbol;
+
+/// A parsed expression.
+pub(crate) enum Node {
+    /// An integer, which is checked to fit in an `i64` when it is expanded.
+    Integer(i128, Span),
+    Real(f64),
+    /// A string literal, which is passed through unchanged.
+    String(Literal),
+    /// An absolute symbol.
+    Symbol(String),
+    /// A Rust value, which is converted with `Expr::from()`.
+    Interpolated(TokenStream),
+    Normal(Box<Node>, Vec<Node>),
+    /// (key, is_delayed, value)
+    Association(Vec<(Node, bool, Node)>),
+}
+
+pub(crate) struct Error {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Error {
+    pub(crate) fn new(span: Span, message: &str) -> Self {
+        Error {
+            span,
+            message: message.to_owned(),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Parse `input` as a single expression.
+pub(crate) fn parse(input: TokenStream) -> Result<Node> {
+    let mut parser = Parser::new(input, Span::call_site());
+    let node = grammar::compound(&mut parser)?;
+    parser.expect_end()?;
+    Ok(node)
+}
+
+struct Parser {
+    tokens: Vec<TokenTree>,
+    position: usize,
+    /// Where to report errors about unexpected end of input.
+    end: Span,
+}
+
+impl Node {
+    fn call(head: &str, elements: Vec<Node>) -> Node {
+        Node::Normal(Box::new(Node::Symbol(head.to_owned())), elements)
+    }
+}
+
+impl Parser {
+    fn new(input: TokenStream, end: Span) -> Self {
+        Parser {
+            tokens: input.into_iter().collect(),
+            position: 0,
+            end,
+        }
+    }
+
+    //==================================
+    // Operands of the operators in `grammar`
+    //==================================
+
+    /// `f[x, y]` and `expr[[i, j]]`
+    fn postfix(&mut self) -> Result<Node> {
+        let mut node = self.primary()?;
+
+        while let Some(TokenTree::Group(group)) = self.peek() {
+            if group.delimiter() != Delimiter::Bracket {
+                break;
+            }
+            let group = group.clone();
+            self.position += 1;
+
+            let mut inner = group.stream().into_iter();
+            node = match (inner.next(), inner.next()) {
+                (Some(TokenTree::Group(part)), None)
+                    if part.delimiter() == Delimiter::Bracket =>
+                {
+                    let mut elements = vec![node];
+                    elements.extend(elements_of(part.stream(), part.span_close())?);
+                    Node::call("System`Part", elements)
+                },
+                _ => Node::Normal(
+                    Box::new(node),
+                    elements_of(group.stream(), group.span_close())?,
+                ),
+            };
+        }
+
+        Ok(node)
+    }
+
+    //==================================
+    // Atoms and groups
+    //==================================
+
+    fn primary(&mut self) -> Result<Node> {
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return Err(Error::new(self.end, "expected an expression")),
+        };
+
+        match token {
+            TokenTree::Literal(literal) => {
+                self.position += 1;
+                literal_node(literal)
+            },
+            TokenTree::Ident(_) => self.symbol(),
+            TokenTree::Punct(punct) if punct.as_char() == '$' => self.symbol(),
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                self.position += 1;
+                match self.next() {
+                    Some(TokenTree::Ident(ident)) => {
+                        Ok(Node::Interpolated(TokenTree::Ident(ident).into()))
+                    },
+                    Some(TokenTree::Group(group))
+                        if group.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        Ok(Node::Interpolated(group.stream()))
+                    },
+                    _ => Err(Error::new(
+                        punct.span(),
+                        "expected `#name` or `#(expr)`; use `Slot[n]` for slots",
+                    )),
+                }
+            },
+            TokenTree::Punct(_) if self.at_op("<|") => {
+                self.eat_op("<|");
+                self.association()
+            },
+            TokenTree::Group(group) => {
+                self.position += 1;
+                match group.delimiter() {
+                    Delimiter::Parenthesis => {
+                        let mut parser = Parser::new(group.stream(), group.span_close());
+                        let node = grammar::compound(&mut parser)?;
+                        parser.expect_end()?;
+                        Ok(node)
+                    },
+                    Delimiter::Brace => Ok(Node::call(
+                        "System`List",
+                        elements_of(group.stream(), group.span_close())?,
+                    )),
+                    _ => Err(Error::new(group.span(), "expected an expression")),
+                }
+            },
+            TokenTree::Punct(punct) => {
+                Err(Error::new(punct.span(), "expected an expression"))
+            },
+        }
+    }
+
+    /// The rules of an association, after the opening `<|`.
+    fn association(&mut self) -> Result<Node> {
+        let mut rules = Vec::new();
+
+        while !self.eat_op("|>") {
+            let span = self.span();
+            match grammar::replace(self)? {
+                Node::Normal(head, mut elements) if elements.len() == 2 => {
+                    let delayed = match &*head {
+                        Node::Symbol(head) if head == "System`Rule" => false,
+                        Node::Symbol(head) if head == "System`RuleDelayed" => true,
+                        _ => return Err(Error::new(span, "expected a rule")),
+                    };
+                    let value = elements.pop().unwrap();
+                    let key = elements.pop().unwrap();
+                    rules.push((key, delayed, value));
+                },
+                _ => return Err(Error::new(span, "expected a rule")),
+            }
+
+            if !self.eat_op(",") && !self.at_op("|>") {
+                return Err(Error::new(self.span(), "expected `,` or `|>`"));
+            }
+        }
+
+        Ok(Node::Association(rules))
+    }
+
+    /// A symbol, like `x`, `$Version` or `MyPackage::x`, or a pattern, like `x_` or
+    /// `x__Integer`.
+    fn symbol(&mut self) -> Result<Node> {
+        let span = self.span();
+        let mut components = vec![self.symbol_component()?];
+        while self.eat_op("::") {
+            components.push(self.symbol_component()?);
+        }
+
+        if components.len() > 1 {
+            return absolute_symbol(components.join("`"), span);
+        }
+
+        let name = components.pop().unwrap();
+        match name.find('_') {
+            Some(index) => pattern(&name[..index], &name[index..], span),
+            None => resolve(&name, span),
+        }
+    }
+
+    fn symbol_component(&mut self) -> Result<String> {
+        let dollar = self.eat_op("$");
+
+        match self.next() {
+            Some(TokenTree::Ident(ident)) => {
+                let name = ident.to_string();
+                let name = name.strip_prefix("r#").unwrap_or(&name);
+                Ok(if dollar { format!("${}", name) } else { name.to_owned() })
+            },
+            Some(token) => Err(Error::new(token.span(), "expected a symbol name")),
+            None => Err(Error::new(self.end, "expected a symbol name")),
+        }
+    }
+
+    //==================================
+    // Tokens
+    //==================================
+
+    fn peek(&self) -> Option<&TokenTree> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<TokenTree> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn span(&self) -> Span {
+        self.peek().map_or(self.end, TokenTree::span)
+    }
+
+    fn at_end(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    fn expect_end(&self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(Error::new(token.span(), "unexpected token")),
+        }
+    }
+
+    /// Returns `true` if the next tokens are the punctuation characters of `op`.
+    fn at_op(&self, op: &str) -> bool {
+        let count = op.chars().count();
+
+        op.chars().enumerate().all(|(index, char)| {
+            match self.tokens.get(self.position + index) {
+                Some(TokenTree::Punct(punct)) => {
+                    punct.as_char() == char
+                        && (index + 1 == count
+                            || punct.spacing() == proc_macro::Spacing::Joint)
+                },
+                _ => false,
+            }
+        })
+    }
+
+    /// Consume `op` if it is next.
+    fn eat_op(&mut self, op: &str) -> bool {
+        let matched = self.at_op(op);
+        if matched {
+            self.position += op.chars().count();
+        }
+        matched
+    }
+}
+
+impl Syntax for Parser {
+    type Node = Node;
+    type Error = Error;
+
+    fn peek_operator(&self) -> Option<&'static str> {
+        grammar::OPERATORS.iter().copied().find(|op| self.at_op(op))
+    }
+
+    fn skip_operator(&mut self) {
+        if let Some(op) = self.peek_operator() {
+            self.position += op.chars().count();
+        }
+    }
+
+    fn at_element_end(&self) -> bool {
+        self.at_end() || self.at_op(",")
+    }
+
+    fn at_operand(&self) -> bool {
+        match self.peek() {
+            Some(TokenTree::Literal(_) | TokenTree::Ident(_)) => true,
+            Some(TokenTree::Punct(punct)) => {
+                matches!(punct.as_char(), '$' | '#') || self.at_op("<|")
+            },
+            Some(TokenTree::Group(group)) => {
+                matches!(group.delimiter(), Delimiter::Parenthesis | Delimiter::Brace)
+            },
+            None => false,
+        }
+    }
+
+    fn operand(&mut self) -> Result<Node> {
+        self.postfix()
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::new(self.span(), message)
+    }
+
+    fn symbol(&self, symbol: SystemSymbol) -> Node {
+        Node::Symbol(symbol.as_str().to_owned())
+    }
+
+    fn apply(&self, head: Node, elements: Vec<Node>) -> Node {
+        Node::Normal(Box::new(head), elements)
+    }
+
+    fn integer(&self, value: i64) -> Node {
+        Node::Integer(i128::from(value), Span::call_site())
+    }
+
+    fn negate_number(&self, node: &Node) -> Option<Node> {
+        match node {
+            Node::Integer(value, span) => Some(Node::Integer(-value, *span)),
+            Node::Real(value) => Some(Node::Real(-value)),
+            _ => None,
+        }
+    }
+}
+/// Parse the comma-separated elements of a `[...]` or `{...}` group.
+///
+/// As in the Wolfram Language, an empty element is `Null`, so `f[x, ]` is
+/// `f[x, Null]`.
+fn elements_of(stream: TokenStream, end: Span) -> Result<Vec<Node>> {
+    let mut parser = Parser::new(stream, end);
+    let mut elements = Vec::new();
+    if parser.at_end() {
+        return Ok(elements);
+    }
+
+    loop {
+        if parser.at_end() || parser.at_op(",") {
+            elements.push(Node::Symbol("System`Null".to_owned()));
+        } else {
+            elements.push(grammar::compound(&mut parser)?);
+        }
+
+        if parser.at_end() {
+            return Ok(elements);
+        }
+        if !parser.eat_op(",") {
+            return Err(Error::new(parser.span(), "expected `,`"));
+        }
+    }
+}
+
+/// A pattern like `x_`, `x__h` or `___`: `name` is the part before the first `_`, and
+/// `blank` the rest.
+fn pattern(name: &str, blank: &str, span: Span) -> Result<Node> {
+    let underscores = blank.len() - blank.trim_start_matches('_').len();
+    let head = match underscores {
+        1 => "System`Blank",
+        2 => "System`BlankSequence",
+        3 => "System`BlankNullSequence",
+        _ => return Err(Error::new(span, "expected `_`, `__` or `___`")),
+    };
+
+    let blank_head = &blank[underscores..];
+    let blank = match blank_head {
+        "" => Node::call(head, vec![]),
+        _ => Node::call(head, vec![resolve(blank_head, span)?]),
+    };
+
+    match name {
+        "" => Ok(blank),
+        _ => Ok(Node::call("System`Pattern", vec![resolve(name, span)?, blank])),
+    }
+}
+
+/// Resolve a symbol name without a context to `` System`name `` or `` Global`name ``.
+fn resolve(name: &str, span: Span) -> Result<Node> {
+    let system = format!("System`{}", name);
+    if SystemSymbol::lookup(&system).is_some() {
+        return Ok(Node::Symbol(system));
+    }
+
+    absolute_symbol(format!("Global`{}", name), span)
+}
+
+fn absolute_symbol(name: String, span: Span) -> Result<Node> {
+    match SymbolRef::try_new(&name) {
+        Some(_) => Ok(Node::Symbol(name)),
+        None => Err(Error::new(span, &format!("invalid symbol `{}`", name))),
+    }
+}
+
+fn literal_node(literal: Literal) -> Result<Node> {
+    if string_value(&literal).is_some() {
+        return Ok(Node::String(literal));
+    }
+
+    let span = literal.span();
+    let source = literal.to_string().replace('_', "");
+    let unsupported = || Error::new(span, "expected an integer, real or string literal");
+
+    let radix = match source.get(..2) {
+        Some("0x") => Some(16),
+        Some("0o") => Some(8),
+        Some("0b") => Some(2),
+        _ => None,
+    };
+
+    if let Some(radix) = radix {
+        return i128::from_str_radix(&source[2..], radix)
+            .map(|value| Node::Integer(value, span))
+            .map_err(|_| unsupported());
+    }
+
+    if source.contains(['.', 'e', 'E']) {
+        return match source.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(Node::Real(value)),
+            _ => Err(unsupported()),
+        };
+    }
+
+    source
+        .parse::<i128>()
+        .map(|value| Node::Integer(value, span))
+        .map_err(|_| unsupported())
+}
+
+/// The value of a string literal, or `None` if `literal` is not a string literal.
+pub(crate) fn string_value(literal: &Literal) -> Option<String> {
+    let source = literal.to_string();
+
+    if let Some(raw) = source.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let closing = format!("\"{}", &raw[..hashes]);
+        return raw[hashes..]
+            .strip_prefix('"')?
+            .strip_suffix(closing.as_str())
+            .map(str::to_owned);
+    }
+
+    unescape(source.strip_prefix('"')?.strip_suffix('"')?)
+}
+
+/// Process the escape sequences in the contents of a Rust string literal.
+fn unescape(source: &str) -> Option<String> {
+    let mut string = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            string.push(char);
+            continue;
+        }
+
+        let escaped = match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            '\\' => '\\',
+            '\'' => '\'',
+            '"' => '"',
+            'x' => {
+                let digits: String = chars.by_ref().take(2).collect();
+                char::from(u8::from_str_radix(&digits, 16).ok()?)
+            },
+            'u' => {
+                if chars.next()? != '{' {
+                    return None;
+                }
+                let digits: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                char::from_u32(u32::from_str_radix(&digits.replace('_', ""), 16).ok()?)?
+            },
+            // A line continuation: skip the newline and any leading whitespace.
+            '\n' => {
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                continue;
+            },
+            _ => return None,
+        };
+
+        string.push(escaped);
+    }
+
+    Some(string)
+}
@@ -0,0 +1,235 @@
+use wolfram_expr::{system, Association, Expr, Symbol};
+use wolfram_expr_macros::{sym, wl};
+
+fn sym(name: &str) -> Expr {
+    Expr::symbol(Symbol::new(name))
+}
+
+fn call(head: &str, elements: Vec<Expr>) -> Expr {
+    Expr::function(Symbol::new(head), elements)
+}
+
+#[test]
+fn symbols() {
+    assert_eq!(sym!("System`Plus"), Symbol::new("System`Plus"));
+    assert_eq!(sym!("System`Plus"), system::Plus);
+    assert_eq!(sym!("Global`x"), Symbol::new("Global`x"));
+    assert_eq!(sym!(r"MyPackage`Utils`$x1"), Symbol::new("MyPackage`Utils`$x1"));
+}
+
+#[test]
+fn atoms() {
+    let (x, f) = (sym("Global`x"), sym("Global`f"));
+
+    assert_eq!(wl! { 5 }, Expr::from(5));
+    assert_eq!(wl! { -5 }, Expr::from(-5));
+    assert_eq!(wl! { 0x10 }, Expr::from(16));
+    assert_eq!(wl! { 1_000 }, Expr::from(1000));
+    assert_eq!(wl! { 2.5 }, Expr::real(2.5));
+    assert_eq!(wl! { -1e3 }, Expr::real(-1000.0));
+    assert_eq!(wl! { "a\tb" }, Expr::string("a\tb"));
+    assert_eq!(wl! { r"a\tb" }, Expr::string("a\\tb"));
+
+    assert_eq!(wl! { x }, x);
+    assert_eq!(wl! { f[x] }, Expr::normal(f, vec![x]));
+    assert_eq!(wl! { Plus }, Expr::from(system::Plus));
+    assert_eq!(wl! { Sinc }, sym("Global`Sinc"));
+    assert_eq!(wl! { System::Sinc }, sym("System`Sinc"));
+    assert_eq!(wl! { MyPackage::Utils::foo }, sym("MyPackage`Utils`foo"));
+    assert_eq!(wl! { $Version }, sym("Global`$Version"));
+    assert_eq!(wl! { System::r#true }, sym("System`true"));
+}
+
+#[test]
+fn structure() {
+    let x = || sym("Global`x");
+    let list = |elements: Vec<Expr>| Expr::list(elements);
+
+    assert_eq!(
+        wl! { f[x, {1, 2}, "s" -> y] },
+        call("Global`f", vec![
+            x(),
+            list(vec![Expr::from(1), Expr::from(2)]),
+            Expr::rule("s", sym("Global`y")),
+        ])
+    );
+    assert_eq!(wl! { f[x][] }, Expr::normal(call("Global`f", vec![x()]), vec![]));
+    assert_eq!(wl! { {} }, list(vec![]));
+    assert_eq!(wl! { {x,} }, list(vec![x(), Expr::null()]));
+    assert_eq!(
+        wl! { x[[1, -1]] },
+        call("System`Part", vec![x(), Expr::from(1), Expr::from(-1)])
+    );
+
+    let mut assoc = Association::new();
+    assoc.insert("a", Expr::from(1));
+    assoc.insert_delayed(x(), list(vec![]));
+    assert_eq!(wl! { <|"a" -> 1, x :> {}|> }, Expr::from(assoc));
+    assert_eq!(wl! { <||> }, Expr::from(Association::new()));
+}
+
+#[test]
+fn operators() {
+    let (x, y, z) = (sym("Global`x"), sym("Global`y"), sym("Global`z"));
+    let int = Expr::from;
+
+    assert_eq!(
+        wl! { x + 2 * y ^ 3 },
+        call("System`Plus", vec![
+            x.clone(),
+            call("System`Times", vec![
+                int(2),
+                call("System`Power", vec![y.clone(), int(3)])
+            ]),
+        ])
+    );
+    assert_eq!(
+        wl! { x - y - 2 },
+        call("System`Plus", vec![
+            x.clone(),
+            call("System`Times", vec![int(-1), y.clone()]),
+            int(-2),
+        ])
+    );
+    assert_eq!(
+        wl! { -x / y },
+        call("System`Times", vec![
+            int(-1),
+            x.clone(),
+            call("System`Power", vec![y.clone(), int(-1)]),
+        ])
+    );
+    assert_eq!(
+        wl! { -2 ^ 2 },
+        call("System`Times", vec![
+            int(-1),
+            call("System`Power", vec![int(2), int(2)])
+        ])
+    );
+    assert_eq!(
+        wl! { x ^ -y ^ 2 },
+        call("System`Power", vec![
+            x.clone(),
+            call("System`Times", vec![
+                int(-1),
+                call("System`Power", vec![y.clone(), int(2)]),
+            ]),
+        ])
+    );
+    assert_eq!(
+        wl! { (x + y) * z },
+        call("System`Times", vec![
+            call("System`Plus", vec![x.clone(), y.clone()]),
+            z.clone()
+        ])
+    );
+
+    assert_eq!(
+        wl! { x < y < z },
+        call("System`Less", vec![x.clone(), y.clone(), z.clone()])
+    );
+    assert_eq!(
+        wl! { !(x == y) || x != z && y >= 1 },
+        call("System`Or", vec![
+            call("System`Not", vec![call("System`Equal", vec![x.clone(), y.clone()])]),
+            call("System`And", vec![
+                call("System`Unequal", vec![x.clone(), z.clone()]),
+                call("System`GreaterEqual", vec![y.clone(), int(1)]),
+            ]),
+        ])
+    );
+
+    assert_eq!(
+        wl! { x /. x -> y -> z },
+        call("System`ReplaceAll", vec![
+            x.clone(),
+            Expr::rule(x.clone(), Expr::rule(y.clone(), z.clone())),
+        ])
+    );
+    assert_eq!(
+        wl! { x = y; z := x; },
+        call("System`CompoundExpression", vec![
+            call("System`Set", vec![x.clone(), y.clone()]),
+            call("System`SetDelayed", vec![z.clone(), x.clone()]),
+            Expr::null(),
+        ])
+    );
+}
+
+#[test]
+fn operators_shared_with_templates() {
+    let (f, x, y) = (sym("Global`f"), sym("Global`x"), sym("Global`y"));
+    let int = Expr::from;
+
+    assert_eq!(wl! { x - -1 }, call("System`Plus", vec![x.clone(), int(1)]));
+    assert_eq!(wl! { - -x }, x);
+    assert_eq!(
+        wl! { x - - -y },
+        call("System`Plus", vec![
+            x.clone(),
+            call("System`Times", vec![int(-1), y.clone()]),
+        ])
+    );
+    assert_eq!(wl! { 2 x }, call("System`Times", vec![int(2), x.clone()]));
+
+    assert_eq!(wl! { f @ x }, Expr::normal(f.clone(), vec![x.clone()]));
+    assert_eq!(wl! { f @@ x }, call("System`Apply", vec![f.clone(), x.clone()]));
+    assert_eq!(wl! { f /@ x }, call("System`Map", vec![f.clone(), x.clone()]));
+    assert_eq!(
+        wl! { Slot[1] + 1 & },
+        call("System`Function", vec![call("System`Plus", vec![
+            call("System`Slot", vec![int(1)]),
+            int(1),
+        ])])
+    );
+    assert_eq!(wl! { x === y }, call("System`SameQ", vec![x.clone(), y.clone()]));
+    assert_eq!(wl! { x =!= y }, call("System`UnsameQ", vec![x, y]));
+}
+
+#[test]
+fn patterns() {
+    let x = sym("Global`x");
+    assert_eq!(wl! { _ }, call("System`Blank", vec![]));
+    assert_eq!(
+        wl! { x__ },
+        call("System`Pattern", vec![x.clone(), call("System`BlankSequence", vec![])])
+    );
+    assert_eq!(
+        wl! { ___Integer },
+        call("System`BlankNullSequence", vec![Expr::from(system::Integer)])
+    );
+    assert_eq!(
+        wl! { f[x_h] },
+        call("Global`f", vec![call("System`Pattern", vec![
+            x,
+            call("System`Blank", vec![sym("Global`h")]),
+        ])])
+    );
+}
+
+#[test]
+fn interpolation() {
+    let n = 3;
+    let name = "s";
+    let expr = Expr::list(vec![]);
+    let assoc = Expr::from(true);
+
+    assert_eq!(
+        wl! { f[#n, #name, #(expr.clone()), #(n + 1)] },
+        call("Global`f", vec![
+            Expr::from(3),
+            Expr::string("s"),
+            expr.clone(),
+            Expr::from(4),
+        ])
+    );
+    assert_eq!(
+        wl! { #(Symbol::new("Global`g"))[#(expr.clone())] },
+        call("Global`g", vec![expr])
+    );
+
+    // A variable named `assoc` is not shadowed by the expansion of `<|...|>`.
+    let mut expected = Association::new();
+    expected.insert("a", Expr::from(true));
+    assert_eq!(wl! { <|"a" -> #assoc|> }, Expr::from(expected));
+}